@@ -24,7 +24,9 @@ use windows::{
 
 use anyhow::Result;
 
-use wintab_lite::{cast_void, Packet, AXIS, CXO, DVC, HCTX, LOGCONTEXT, WT, WTI, WTPKT, XYZ};
+use wintab_lite::{
+    cast_void, Packet, ScreenRect, AXIS, CXO, DVC, HCTX, LOGCONTEXT, WT, WTI, WTPKT, XYZ,
+};
 
 static mut CONTEXT_HANDEL: *mut HCTX = std::ptr::null_mut();
 static mut X: i32 = 0;
@@ -32,21 +34,13 @@ static mut Y: i32 = 0;
 static mut P: u32 = 0;
 static mut REDRAW: bool = false;
 
-#[derive(Debug)]
-struct RECTWH {
-    left: i32,
-    top: i32,
-    width: i32,
-    height: i32,
-}
-
-unsafe fn get_virtual_screen_rect() -> RECTWH {
-    RECTWH {
-        left: GetSystemMetrics(SM_XVIRTUALSCREEN),
-        top: GetSystemMetrics(SM_YVIRTUALSCREEN),
-        width: GetSystemMetrics(SM_CXVIRTUALSCREEN),
-        height: GetSystemMetrics(SM_CYVIRTUALSCREEN),
-    }
+unsafe fn get_virtual_screen_rect() -> ScreenRect {
+    ScreenRect::new(
+        GetSystemMetrics(SM_XVIRTUALSCREEN),
+        GetSystemMetrics(SM_YVIRTUALSCREEN),
+        GetSystemMetrics(SM_CXVIRTUALSCREEN),
+        GetSystemMetrics(SM_CYVIRTUALSCREEN),
+    )
 }
 
 fn main() -> Result<()> {
@@ -149,22 +143,20 @@ fn main() -> Result<()> {
         let virtual_screen_rect = get_virtual_screen_rect();
         println!(
             "Virtual Screen: {virtual_screen_rect:#?} (right: {} bottom: {})",
-            virtual_screen_rect.left + virtual_screen_rect.width,
-            virtual_screen_rect.top + virtual_screen_rect.height
+            virtual_screen_rect.right(),
+            virtual_screen_rect.bottom()
         );
 
         // Guarantee the output coordinate space to be in screen coordinates.
-        log_context.lcOutOrgXYZ.x = virtual_screen_rect.left;
-        log_context.lcOutOrgXYZ.y = virtual_screen_rect.top;
-        log_context.lcOutExtXYZ.x = virtual_screen_rect.width;
-        log_context.lcOutExtXYZ.y = -virtual_screen_rect.height;
+        // See MonitorLayout for mapping to a single monitor or a window instead.
+        virtual_screen_rect.apply_output(&mut log_context);
 
         // Leave the system origin and extents as received:
         // lcSysOrgX, lcSysOrgY, lcSysExtX, lcSysExtY
         println!("{log_context:#?}\n{tablet_x:?}\n{tablet_y:?}");
         // open the tablet context
         // The Wintab spec says we must open the context disabled if we are using cursor masks.
-        CONTEXT_HANDEL = wintab_lite::open(window_handel.0, &mut log_context, 0);
+        CONTEXT_HANDEL = wintab_lite::open(window_handel, &mut log_context, 0);
         println!("Log context after open \n{log_context:#?}");
 
         let mut message = MSG::default();
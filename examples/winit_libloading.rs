@@ -1,9 +1,9 @@
 use std::sync::Arc;
 
-use anyhow::{anyhow, Result};
+use anyhow::Result;
 use libloading::Library;
 use windows::Win32::{
-    Foundation::{HWND, RECT},
+    Foundation::RECT,
     Graphics::Gdi::{
         BeginPaint, Ellipse, EndPaint, FillRect, GetStockObject, InvalidateRect, SelectObject,
         BLACK_PEN, HBRUSH, PAINTSTRUCT, WHITE_BRUSH,
@@ -13,27 +13,14 @@ use winit::{
     event::{Event, WindowEvent},
     event_loop::{ControlFlow, EventLoop},
     keyboard::KeyCode,
-    raw_window_handle::{HasWindowHandle, RawWindowHandle, Win32WindowHandle},
-    window::{Window, WindowBuilder},
+    window::WindowBuilder,
 };
 
 use wintab_lite::{
-    cast_void, Packet, WTClose, WTDataGet, WTInfo, WTOpen, WTQueuePacketsEx, AXIS, CXO, DVC,
-    LOGCONTEXT, WTI, WTPKT, XYZ,
+    cast_void, window_hwnd, Packet, WTClose, WTDataGet, WTInfo, WTOpen, WTQueuePacketsEx, AXIS,
+    CXO, DVC, LOGCONTEXT, WTI, WTPKT, XYZ,
 };
 
-fn extract_window_handel(window_holder: &Window) -> Result<HWND> {
-    let handel = window_holder.window_handle()?;
-    match handel.as_raw() {
-        RawWindowHandle::Win32(Win32WindowHandle {
-            hinstance: Some(_),
-            hwnd,
-            ..
-        }) => Ok(HWND(hwnd.into())),
-        _ => Err(anyhow!("Not windows, or invalid instance.")),
-    }
-}
-
 fn main() -> Result<()> {
     // ==================
     // winit setup hijinks
@@ -61,7 +48,7 @@ fn main() -> Result<()> {
 
     // =============================================
     // persuade winit to disclose the  window handel
-    let hwnd = extract_window_handel(&window_holder)?;
+    let hwnd = window_hwnd(&*window_holder)?;
     // ======================================
     // Query wintab for its default 'context'
     let return_value = unsafe { wintab_info(WTI::DEFSYSCTX, 0, cast_void!(log_context)) };
@@ -0,0 +1,562 @@
+//! A safe layer over the Unicode (`W`) wintab functions.
+//!
+//! The [WintabApi] trait abstracts over how the wintab functions were linked so the same code works
+//! with the `raw-dylib` feature ([RawDylibApi]), the `libloading` feature ([LibloadingApi]) or a
+//! fake function table in tests. The provided methods always call the `W` variants and return
+//! Rust [String]s, so device and context names are not limited to the ANSI code page.
+#![allow(non_snake_case)]
+use windows::Win32::Foundation::HWND;
+
+use crate::{
+    c_type_aliases::{BOOL, HCTX, HMGR, INT, LPVOID, UINT},
+    information_categories::{CRC, CSR, DVC, HWC, IFC, WTI},
+    Bitmask, CString40, CString40W, Orientation, Rotation, AXIS, CXL, CXO, CXS, FIX32, LOGCONTEXT,
+    LOGCONTEXTW, TPS, WTPKT, XY, XYZ,
+};
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// Plain data which [WintabApi::info_value] and [WintabApi::info_array] can read: integers,
+/// [FIX32], the bitflags types, and the structs and arrays made only of those.
+///
+/// Every bit pattern, including all zeros, is a valid value of these types, so whatever bytes
+/// the driver writes can be read safely. This trait is sealed; read other types with
+/// [WintabApi::info_w].
+pub trait InfoValue: Copy + sealed::Sealed {}
+
+macro_rules! info_value_types {
+    ($($type:ty),* $(,)?) => {$(
+        impl sealed::Sealed for $type {}
+        impl InfoValue for $type {}
+    )*};
+}
+
+info_value_types! {
+    u8, u16, u32, u64, i8, i16, i32, i64, FIX32, CXO, CXS, CXL, WTPKT, TPS, CRC, HWC, AXIS,
+    Orientation, Rotation, CString40, CString40W, LOGCONTEXT, LOGCONTEXTW,
+}
+
+impl<T: InfoValue + std::fmt::Binary> sealed::Sealed for Bitmask<T> {}
+impl<T: InfoValue + std::fmt::Binary> InfoValue for Bitmask<T> {}
+impl<T: InfoValue> sealed::Sealed for XY<T> {}
+impl<T: InfoValue> InfoValue for XY<T> {}
+impl<T: InfoValue> sealed::Sealed for XYZ<T> {}
+impl<T: InfoValue> InfoValue for XYZ<T> {}
+impl<T: InfoValue, const N: usize> sealed::Sealed for [T; N] {}
+impl<T: InfoValue, const N: usize> InfoValue for [T; N] {}
+
+/// The table of wintab functions used by the safe parts of this crate.
+///
+/// The unsafe methods mirror the C functions one to one; see the documentation of the matching
+/// `extern_functions` (`raw-dylib` feature) or `extern_function_types` (`libloading` feature) item.
+/// The manager functions have default implementations which report failure, since not every
+/// driver exports them.
+///
+/// # Safety
+/// Implementors must behave like the functions described in the wintab specification. In
+/// particular `info_w` must never write more bytes to `lpOutput` than it reports when called with
+/// a null `lpOutput`, and the packet functions must never write more than `cMaxPkts` packets.
+///
+/// Callers of the unsafe methods must uphold the same requirements as for the C functions: handles
+/// must be valid and buffers must be large enough for what the driver writes.
+#[allow(clippy::missing_safety_doc)]
+pub unsafe trait WintabApi {
+    /// `WTInfoW`
+    unsafe fn info_w(&self, wCategory: UINT, nIndex: UINT, lpOutput: LPVOID) -> UINT;
+    /// `WTOpenW`
+    unsafe fn open_w(&self, hWnd: HWND, lpLogCtx: *mut LOGCONTEXTW, fEnable: BOOL) -> *mut HCTX;
+    /// `WTClose`
+    unsafe fn close(&self, hCtx: *mut HCTX) -> BOOL;
+    /// `WTEnable`
+    unsafe fn enable(&self, hCtx: *mut HCTX, fEnable: BOOL) -> BOOL;
+    /// `WTGetW`
+    unsafe fn get_w(&self, hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL;
+    /// `WTSetW`
+    unsafe fn set_w(&self, hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL;
+    /// `WTPacket`
+    unsafe fn packet(&self, hCtx: *mut HCTX, wSerial: UINT, lpPkt: LPVOID) -> BOOL;
+    /// `WTPacketsGet`
+    unsafe fn packets_get(&self, hCtx: *mut HCTX, cMaxPkts: INT, lpPkts: LPVOID) -> INT;
+    /// `WTDataGet`
+    unsafe fn data_get(
+        &self,
+        hCtx: *mut HCTX,
+        wBegin: UINT,
+        wEnd: UINT,
+        cMaxPkts: INT,
+        lpPkts: LPVOID,
+        lpNPkts: *mut INT,
+    ) -> INT;
+    /// `WTQueuePacketsEx`
+    unsafe fn queue_packets_ex(&self, hCtx: *mut HCTX, lpOld: *mut UINT, lpNew: *mut UINT) -> BOOL;
+
+    /// `WTMgrOpen`
+    unsafe fn mgr_open(&self, _hWnd: HWND, _wMsgBase: UINT) -> *mut HMGR {
+        std::ptr::null_mut()
+    }
+    /// `WTMgrClose`
+    unsafe fn mgr_close(&self, _hMgr: *mut HMGR) -> BOOL {
+        0
+    }
+    /// `WTMgrDefContextEx`
+    unsafe fn mgr_def_context_ex(&self, _hMgr: *mut HMGR, _wDevice: UINT, _fSystem: BOOL) -> *mut HCTX {
+        std::ptr::null_mut()
+    }
+
+    /// Returns the size in bytes of an information item, or zero if it is not supported.
+    fn info_size(&self, category: UINT, index: UINT) -> UINT {
+        unsafe { self.info_w(category, index, std::ptr::null_mut()) }
+    }
+
+    /// Reads a fixed size information item such as a [UINT], an [AXIS](crate::AXIS) or a
+    /// [LOGCONTEXTW].
+    ///
+    /// Returns `None` if the item is not supported, or if the driver reports that it is larger
+    /// than `T` (in which case reading it would overflow the buffer). If the item is smaller than
+    /// `T` then the remaining bytes are zero.
+    fn info_value<T: InfoValue>(&self, category: UINT, index: UINT) -> Option<T> {
+        let size = self.info_size(category, index) as usize;
+        if size == 0 || size > std::mem::size_of::<T>() {
+            return None;
+        }
+        let mut buffer = vec![0u8; std::mem::size_of::<T>()];
+        let written = unsafe { self.info_w(category, index, buffer.as_mut_ptr() as LPVOID) };
+        // the size may have changed since it was queried
+        if written == 0 || written as usize > buffer.len() {
+            return None;
+        }
+        // SAFETY: the buffer holds `size_of::<T>()` bytes, and any bytes are a valid `T`
+        Some(unsafe { std::ptr::read_unaligned(buffer.as_ptr() as *const T) })
+    }
+
+    /// Reads a variable length array information item such as [CSR::NPRESPONSE].
+    /// Returns `None` if the item is not supported. A trailing partial element is ignored.
+    fn info_array<T: InfoValue>(&self, category: UINT, index: UINT) -> Option<Vec<T>> {
+        let size = self.info_size(category, index) as usize;
+        if size == 0 {
+            return None;
+        }
+        let mut buffer = vec![0u8; size];
+        let written = unsafe { self.info_w(category, index, buffer.as_mut_ptr() as LPVOID) };
+        if written == 0 {
+            return None;
+        }
+        let written = (written as usize).min(buffer.len());
+        let element_size = std::mem::size_of::<T>().max(1);
+        // SAFETY: each chunk holds `size_of::<T>()` bytes, and any bytes are a valid `T`
+        let read = |chunk: &[u8]| unsafe { std::ptr::read_unaligned(chunk.as_ptr() as *const T) };
+        Some(buffer[..written].chunks_exact(element_size).map(read).collect())
+    }
+
+    /// Reads a `TCHAR[]` information item as a [String].
+    /// Returns `None` if the item is not supported.
+    fn info_string(&self, category: UINT, index: UINT) -> Option<String> {
+        let size = self.info_size(category, index) as usize;
+        if size == 0 {
+            return None;
+        }
+        // round up, and leave room for a terminator in case the driver forgets one
+        let mut buffer = vec![0u16; size.div_ceil(2) + 1];
+        let written = unsafe { self.info_w(category, index, buffer.as_mut_ptr() as LPVOID) };
+        if written == 0 {
+            return None;
+        }
+        let end = buffer.iter().position(|c| *c == 0).unwrap_or(buffer.len());
+        Some(String::from_utf16_lossy(&buffer[..end]))
+    }
+
+    /// Reads an information item holding a list of zero-terminated strings, ending with an empty
+    /// string, such as [CSR::BTNNAMES]. Returns `None` if the item is not supported.
+    fn info_string_list(&self, category: UINT, index: UINT) -> Option<Vec<String>> {
+        let size = self.info_size(category, index) as usize;
+        if size == 0 {
+            return None;
+        }
+        // leave room for the terminators in case the driver forgets them
+        let mut buffer = vec![0u16; size.div_ceil(2) + 2];
+        let written = unsafe { self.info_w(category, index, buffer.as_mut_ptr() as LPVOID) };
+        if written == 0 {
+            return None;
+        }
+        let end = buffer.windows(2).position(|pair| pair == [0, 0]).unwrap_or(buffer.len());
+        if end == 0 {
+            return Some(Vec::new());
+        }
+        Some(buffer[..end].split(|c| *c == 0).map(String::from_utf16_lossy).collect())
+    }
+
+    /// The tablet hardware identification string ([IFC::WINTABID])
+    fn interface_id(&self) -> Option<String> {
+        self.info_string(WTI::INTERFACE as UINT, IFC::WINTABID as UINT)
+    }
+
+    /// The name of a device ([DVC::NAME]), where `device` is the zero based device index.
+    fn device_name(&self, device: UINT) -> Option<String> {
+        self.info_string(WTI::DEVICES as UINT + device, DVC::NAME as UINT)
+    }
+
+    /// The Plug and Play ID of a device ([DVC::PNPID]), where `device` is the zero based device
+    /// index.
+    fn device_pnp_id(&self, device: UINT) -> Option<String> {
+        self.info_string(WTI::DEVICES as UINT + device, DVC::PNPID as UINT)
+    }
+
+    /// The name of a cursor ([CSR::NAME]), where `cursor` is the zero based cursor index.
+    fn cursor_name(&self, cursor: UINT) -> Option<String> {
+        self.info_string(WTI::CURSORS as UINT + cursor, CSR::NAME as UINT)
+    }
+
+    /// The names of a cursor's physical buttons ([CSR::BTNNAMES]), where `cursor` is the zero
+    /// based cursor index.
+    fn cursor_button_names(&self, cursor: UINT) -> Option<Vec<String>> {
+        self.info_string_list(WTI::CURSORS as UINT + cursor, CSR::BTNNAMES as UINT)
+    }
+
+    /// The current default digitizing context ([WTI::DEFCONTEXT])
+    fn default_context(&self) -> Option<LOGCONTEXTW> {
+        self.info_value(WTI::DEFCONTEXT as UINT, 0)
+    }
+
+    /// The current default system context ([WTI::DEFSYSCTX])
+    fn default_system_context(&self) -> Option<LOGCONTEXTW> {
+        self.info_value(WTI::DEFSYSCTX as UINT, 0)
+    }
+}
+
+/// Forwards every function to the api behind a pointer, so a [Context](crate::Context) can borrow
+/// or share its function table
+macro_rules! forward_wintab_api {
+    ($($pointer:ty),*) => {$(
+        unsafe impl<T: WintabApi + ?Sized> WintabApi for $pointer {
+            unsafe fn info_w(&self, wCategory: UINT, nIndex: UINT, lpOutput: LPVOID) -> UINT {
+                (**self).info_w(wCategory, nIndex, lpOutput)
+            }
+            unsafe fn open_w(&self, hWnd: HWND, lpLogCtx: *mut LOGCONTEXTW, fEnable: BOOL) -> *mut HCTX {
+                (**self).open_w(hWnd, lpLogCtx, fEnable)
+            }
+            unsafe fn close(&self, hCtx: *mut HCTX) -> BOOL {
+                (**self).close(hCtx)
+            }
+            unsafe fn enable(&self, hCtx: *mut HCTX, fEnable: BOOL) -> BOOL {
+                (**self).enable(hCtx, fEnable)
+            }
+            unsafe fn get_w(&self, hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL {
+                (**self).get_w(hCtx, lpLogCtx)
+            }
+            unsafe fn set_w(&self, hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL {
+                (**self).set_w(hCtx, lpLogCtx)
+            }
+            unsafe fn packet(&self, hCtx: *mut HCTX, wSerial: UINT, lpPkt: LPVOID) -> BOOL {
+                (**self).packet(hCtx, wSerial, lpPkt)
+            }
+            unsafe fn packets_get(&self, hCtx: *mut HCTX, cMaxPkts: INT, lpPkts: LPVOID) -> INT {
+                (**self).packets_get(hCtx, cMaxPkts, lpPkts)
+            }
+            unsafe fn data_get(
+                &self,
+                hCtx: *mut HCTX,
+                wBegin: UINT,
+                wEnd: UINT,
+                cMaxPkts: INT,
+                lpPkts: LPVOID,
+                lpNPkts: *mut INT,
+            ) -> INT {
+                (**self).data_get(hCtx, wBegin, wEnd, cMaxPkts, lpPkts, lpNPkts)
+            }
+            unsafe fn queue_packets_ex(&self, hCtx: *mut HCTX, lpOld: *mut UINT, lpNew: *mut UINT) -> BOOL {
+                (**self).queue_packets_ex(hCtx, lpOld, lpNew)
+            }
+            unsafe fn mgr_open(&self, hWnd: HWND, wMsgBase: UINT) -> *mut HMGR {
+                (**self).mgr_open(hWnd, wMsgBase)
+            }
+            unsafe fn mgr_close(&self, hMgr: *mut HMGR) -> BOOL {
+                (**self).mgr_close(hMgr)
+            }
+            unsafe fn mgr_def_context_ex(&self, hMgr: *mut HMGR, wDevice: UINT, fSystem: BOOL) -> *mut HCTX {
+                (**self).mgr_def_context_ex(hMgr, wDevice, fSystem)
+            }
+        }
+    )*};
+}
+forward_wintab_api!(&T, Box<T>, std::sync::Arc<T>);
+
+/// Calls the functions linked by the `raw-dylib` feature
+#[cfg(feature="raw-dylib")]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RawDylibApi;
+
+#[cfg(feature="raw-dylib")]
+unsafe impl WintabApi for RawDylibApi {
+    unsafe fn info_w(&self, wCategory: UINT, nIndex: UINT, lpOutput: LPVOID) -> UINT {
+        crate::extern_functions::info_w(wCategory, nIndex, lpOutput)
+    }
+    unsafe fn open_w(&self, hWnd: HWND, lpLogCtx: *mut LOGCONTEXTW, fEnable: BOOL) -> *mut HCTX {
+        crate::extern_functions::open_w(hWnd, lpLogCtx, fEnable)
+    }
+    unsafe fn close(&self, hCtx: *mut HCTX) -> BOOL {
+        crate::extern_functions::close(hCtx)
+    }
+    unsafe fn enable(&self, hCtx: *mut HCTX, fEnable: BOOL) -> BOOL {
+        crate::extern_functions::enable(hCtx, fEnable)
+    }
+    unsafe fn get_w(&self, hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL {
+        crate::extern_functions::get_w(hCtx, lpLogCtx)
+    }
+    unsafe fn set_w(&self, hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL {
+        crate::extern_functions::set_w(hCtx, lpLogCtx)
+    }
+    unsafe fn packet(&self, hCtx: *mut HCTX, wSerial: UINT, lpPkt: LPVOID) -> BOOL {
+        crate::extern_functions::packet(hCtx, wSerial, lpPkt)
+    }
+    unsafe fn packets_get(&self, hCtx: *mut HCTX, cMaxPkts: INT, lpPkts: LPVOID) -> INT {
+        crate::extern_functions::packets_get(hCtx, cMaxPkts, lpPkts)
+    }
+    unsafe fn data_get(
+        &self,
+        hCtx: *mut HCTX,
+        wBegin: UINT,
+        wEnd: UINT,
+        cMaxPkts: INT,
+        lpPkts: LPVOID,
+        lpNPkts: *mut INT,
+    ) -> INT {
+        crate::extern_functions::data_get(hCtx, wBegin, wEnd, cMaxPkts, lpPkts, lpNPkts)
+    }
+    unsafe fn queue_packets_ex(&self, hCtx: *mut HCTX, lpOld: *mut UINT, lpNew: *mut UINT) -> BOOL {
+        crate::extern_functions::queue_packets_extent(hCtx, lpOld, lpNew)
+    }
+    unsafe fn mgr_open(&self, hWnd: HWND, wMsgBase: UINT) -> *mut HMGR {
+        crate::extern_functions::mgr_open(hWnd, wMsgBase)
+    }
+    unsafe fn mgr_close(&self, hMgr: *mut HMGR) -> BOOL {
+        crate::extern_functions::mgr_close(hMgr)
+    }
+    unsafe fn mgr_def_context_ex(&self, hMgr: *mut HMGR, wDevice: UINT, fSystem: BOOL) -> *mut HCTX {
+        crate::extern_functions::mgr_def_context_ex(hMgr, wDevice, fSystem)
+    }
+}
+
+#[cfg(feature="libloading")]
+mod libloading_api {
+    use super::*;
+    use libloading::Library;
+
+    type InfoW = unsafe extern "system" fn(UINT, UINT, LPVOID) -> UINT;
+    type OpenW = unsafe extern "system" fn(HWND, *mut LOGCONTEXTW, BOOL) -> *mut HCTX;
+    type Close = unsafe extern "system" fn(*mut HCTX) -> BOOL;
+    type Enable = unsafe extern "system" fn(*mut HCTX, BOOL) -> BOOL;
+    type GetSetW = unsafe extern "system" fn(*mut HCTX, *mut LOGCONTEXTW) -> BOOL;
+    type Packet = unsafe extern "system" fn(*mut HCTX, UINT, LPVOID) -> BOOL;
+    type PacketsGet = unsafe extern "system" fn(*mut HCTX, INT, LPVOID) -> INT;
+    type DataGet = unsafe extern "system" fn(*mut HCTX, UINT, UINT, INT, LPVOID, *mut INT) -> INT;
+    type QueuePacketsEx = unsafe extern "system" fn(*mut HCTX, *mut UINT, *mut UINT) -> BOOL;
+    type MgrOpen = unsafe extern "system" fn(HWND, UINT) -> *mut HMGR;
+    type MgrClose = unsafe extern "system" fn(*mut HMGR) -> BOOL;
+    type MgrDefContextEx = unsafe extern "system" fn(*mut HMGR, UINT, BOOL) -> *mut HCTX;
+
+    /// Calls the functions of a `Wintab32.dll` loaded at runtime using the `libloading` crate.
+    /// The library is kept loaded for as long as this struct is alive.
+    pub struct LibloadingApi {
+        info_w: InfoW,
+        open_w: OpenW,
+        close: Close,
+        enable: Enable,
+        get_w: GetSetW,
+        set_w: GetSetW,
+        packet: Packet,
+        packets_get: PacketsGet,
+        data_get: DataGet,
+        queue_packets_ex: QueuePacketsEx,
+        mgr_open: Option<MgrOpen>,
+        mgr_close: Option<MgrClose>,
+        mgr_def_context_ex: Option<MgrDefContextEx>,
+        _library: Library,
+    }
+
+    impl LibloadingApi {
+        /// Loads `Wintab32.dll` and looks up all the functions.
+        ///
+        /// # Safety
+        /// Loading a library runs its initialisation code, see [Library::new]
+        pub unsafe fn load() -> Result<Self, libloading::Error> {
+            Self::from_library(Library::new("Wintab32.dll")?)
+        }
+
+        /// Looks up all the functions in an already loaded wintab library.
+        /// The manager functions are optional; all others must be present.
+        ///
+        /// # Safety
+        /// The library must export functions with the signatures described by the wintab
+        /// specification.
+        pub unsafe fn from_library(library: Library) -> Result<Self, libloading::Error> {
+            Ok(Self {
+                info_w: *library.get::<InfoW>(c"WTInfoW".to_bytes())?,
+                open_w: *library.get::<OpenW>(c"WTOpenW".to_bytes())?,
+                close: *library.get::<Close>(c"WTClose".to_bytes())?,
+                enable: *library.get::<Enable>(c"WTEnable".to_bytes())?,
+                get_w: *library.get::<GetSetW>(c"WTGetW".to_bytes())?,
+                set_w: *library.get::<GetSetW>(c"WTSetW".to_bytes())?,
+                packet: *library.get::<Packet>(c"WTPacket".to_bytes())?,
+                packets_get: *library.get::<PacketsGet>(c"WTPacketsGet".to_bytes())?,
+                data_get: *library.get::<DataGet>(c"WTDataGet".to_bytes())?,
+                queue_packets_ex: *library.get::<QueuePacketsEx>(c"WTQueuePacketsEx".to_bytes())?,
+                mgr_open: library.get::<MgrOpen>(c"WTMgrOpen".to_bytes()).ok().map(|f| *f),
+                mgr_close: library.get::<MgrClose>(c"WTMgrClose".to_bytes()).ok().map(|f| *f),
+                mgr_def_context_ex: library
+                    .get::<MgrDefContextEx>(c"WTMgrDefContextEx".to_bytes())
+                    .ok()
+                    .map(|f| *f),
+                _library: library,
+            })
+        }
+    }
+
+    unsafe impl WintabApi for LibloadingApi {
+        unsafe fn info_w(&self, wCategory: UINT, nIndex: UINT, lpOutput: LPVOID) -> UINT {
+            (self.info_w)(wCategory, nIndex, lpOutput)
+        }
+        unsafe fn open_w(&self, hWnd: HWND, lpLogCtx: *mut LOGCONTEXTW, fEnable: BOOL) -> *mut HCTX {
+            (self.open_w)(hWnd, lpLogCtx, fEnable)
+        }
+        unsafe fn close(&self, hCtx: *mut HCTX) -> BOOL {
+            (self.close)(hCtx)
+        }
+        unsafe fn enable(&self, hCtx: *mut HCTX, fEnable: BOOL) -> BOOL {
+            (self.enable)(hCtx, fEnable)
+        }
+        unsafe fn get_w(&self, hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL {
+            (self.get_w)(hCtx, lpLogCtx)
+        }
+        unsafe fn set_w(&self, hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL {
+            (self.set_w)(hCtx, lpLogCtx)
+        }
+        unsafe fn packet(&self, hCtx: *mut HCTX, wSerial: UINT, lpPkt: LPVOID) -> BOOL {
+            (self.packet)(hCtx, wSerial, lpPkt)
+        }
+        unsafe fn packets_get(&self, hCtx: *mut HCTX, cMaxPkts: INT, lpPkts: LPVOID) -> INT {
+            (self.packets_get)(hCtx, cMaxPkts, lpPkts)
+        }
+        unsafe fn data_get(
+            &self,
+            hCtx: *mut HCTX,
+            wBegin: UINT,
+            wEnd: UINT,
+            cMaxPkts: INT,
+            lpPkts: LPVOID,
+            lpNPkts: *mut INT,
+        ) -> INT {
+            (self.data_get)(hCtx, wBegin, wEnd, cMaxPkts, lpPkts, lpNPkts)
+        }
+        unsafe fn queue_packets_ex(&self, hCtx: *mut HCTX, lpOld: *mut UINT, lpNew: *mut UINT) -> BOOL {
+            (self.queue_packets_ex)(hCtx, lpOld, lpNew)
+        }
+        unsafe fn mgr_open(&self, hWnd: HWND, wMsgBase: UINT) -> *mut HMGR {
+            match self.mgr_open {
+                Some(mgr_open) => mgr_open(hWnd, wMsgBase),
+                None => std::ptr::null_mut(),
+            }
+        }
+        unsafe fn mgr_close(&self, hMgr: *mut HMGR) -> BOOL {
+            match self.mgr_close {
+                Some(mgr_close) => mgr_close(hMgr),
+                None => 0,
+            }
+        }
+        unsafe fn mgr_def_context_ex(&self, hMgr: *mut HMGR, wDevice: UINT, fSystem: BOOL) -> *mut HCTX {
+            match self.mgr_def_context_ex {
+                Some(mgr_def_context_ex) => mgr_def_context_ex(hMgr, wDevice, fSystem),
+                None => std::ptr::null_mut(),
+            }
+        }
+    }
+}
+#[cfg(feature="libloading")]
+pub use libloading_api::LibloadingApi;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FakeWintab, CString40W, CXO};
+
+    fn fake_with_one_tablet() -> FakeWintab {
+        let api = FakeWintab::new();
+        api.set_info_string(1, 1, "Wacom Tablet Wintab");
+        api.set_info_string(100, 1, "Intuos Pro Ⓜ");
+        api.set_info_string(101, 1, "Second Tablet");
+        api.set_info_string(200, 1, "Pressure Stylus");
+        let context = LOGCONTEXTW {
+            lcName: CString40W::from("Default System Ctx ✓"),
+            lcOptions: CXO::SYSTEM,
+            ..Default::default()
+        };
+        api.set_info(4, 0, &[context]);
+        // larger than any type we would read
+        api.set_info(5, 0, &[0u8; 1024]);
+        api
+    }
+
+    #[test]
+    fn test_info_strings() {
+        let api = fake_with_one_tablet();
+        assert_eq!(api.interface_id().as_deref(), Some("Wacom Tablet Wintab"));
+        assert_eq!(api.device_name(0).as_deref(), Some("Intuos Pro Ⓜ"));
+        assert_eq!(api.device_name(1).as_deref(), Some("Second Tablet"));
+        assert_eq!(api.device_name(2), None);
+        assert_eq!(api.cursor_name(0).as_deref(), Some("Pressure Stylus"));
+        assert_eq!(api.device_pnp_id(0), None);
+    }
+
+    #[test]
+    fn test_info_string_list() {
+        let api = fake_with_one_tablet();
+        api.set_info_string(200, 6, "Tip\0Lower Button\0Upper Button\0");
+        assert_eq!(
+            api.cursor_button_names(0).unwrap(),
+            ["Tip", "Lower Button", "Upper Button"]
+        );
+        // missing the final terminator
+        api.set_info_string(201, 6, "Tip\0Lower Button");
+        assert_eq!(api.cursor_button_names(1).unwrap(), ["Tip", "Lower Button"]);
+        api.set_info_string(202, 6, "");
+        assert_eq!(api.cursor_button_names(2).unwrap(), Vec::<String>::new());
+        assert_eq!(api.cursor_button_names(3), None);
+    }
+
+    #[test]
+    fn test_info_array() {
+        let api = fake_with_one_tablet();
+        api.set_info(200, 11, &[0u32, 100, 400, 1023]);
+        assert_eq!(api.info_array::<UINT>(200, 11).unwrap(), [0, 100, 400, 1023]);
+        api.set_info(200, 12, &[1u8, 0, 0, 0, 2, 0]);
+        assert_eq!(api.info_array::<UINT>(200, 12).unwrap(), [1]);
+        assert_eq!(api.info_array::<UINT>(200, 13), None);
+    }
+
+    #[test]
+    fn test_info_values() {
+        let api = fake_with_one_tablet();
+        let context = api.default_system_context().unwrap();
+        assert_eq!(context.lcName.to_string_lossy(), "Default System Ctx ✓");
+        assert_eq!(context.lcOptions, CXO::SYSTEM);
+        assert!(api.default_context().is_none());
+        // refuses to read into a buffer that is too small
+        assert_eq!(api.info_value::<UINT>(5, 0), None);
+    }
+
+    #[test]
+    fn test_info_value_bytes() {
+        let api = FakeWintab::new();
+        // units which are not in the spec are kept as the raw value
+        api.set_info(100, 1, &[0i32, 1000, 99, 0x10000]);
+        let axis = api.info_value::<AXIS>(100, 1).unwrap();
+        assert_eq!((axis.axMax, axis.axUnits, axis.units()), (1000, 99, None));
+        // a short item leaves the rest of the value zero
+        api.set_info(100, 2, &[7u16]);
+        assert_eq!(api.info_value::<[u16; 3]>(100, 2), Some([7, 0, 0]));
+        // a trailing partial element is dropped
+        api.set_info(100, 3, &[1u8, 0, 2, 0, 3]);
+        assert_eq!(api.info_array::<u16>(100, 3).unwrap(), [1, 2]);
+    }
+}
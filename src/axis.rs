@@ -1,5 +1,6 @@
 use crate::{
     LONG,
+    UINT,
     FIX32
 };
 
@@ -12,12 +13,19 @@ pub struct AXIS{
     pub axMin        : LONG,
     /// Maximum value of the data item in the tablet's native coordinates.
     pub axMax        : LONG,
-    /// Indicates the units used in calculating the resolution for the data item.
-    pub axUnits      : TU, //UINT,
+    /// Indicates the units used in calculating the resolution for the data item; see [AXIS::units].
+    pub axUnits      : UINT,
     /// Is a fixed-point number giving the number of data item increments per physical unit.
     pub axResolution : FIX32,
 }
 
+impl AXIS {
+    /// [AXIS::axUnits] as a [TU], or `None` if the driver reports a value which is not in the spec
+    pub fn units(&self) -> Option<TU> {
+        TU::try_from(self.axUnits).ok()
+    }
+}
+
 /// Physical Unit Specifiers
 #[repr(u32)] // UINT ≈ std::ffi::u_int ≈ u32
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
@@ -31,4 +39,34 @@ pub enum TU {
     CENTIMETERS = 2,
     /// Specifies that resolution is given with respect to one full revolution of arc. For example, if a data item returns degrees, the resolution would be 360 and the units would be TU_CIRCLE. If the item were in radians, the resolution would be 6.28318 (to FIX32’s precision) and the units would be TU_CIRCLE.
     CIRCLE      = 3,
-}
\ No newline at end of file
+}
+
+impl TryFrom<UINT> for TU {
+    /// The unknown value
+    type Error = UINT;
+
+    fn try_from(value: UINT) -> Result<Self, Self::Error> {
+        match value {
+            0 => Ok(TU::NONE),
+            1 => Ok(TU::INCHES),
+            2 => Ok(TU::CENTIMETERS),
+            3 => Ok(TU::CIRCLE),
+            other => Err(other),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_units() {
+        let mut axis = AXIS { axUnits: TU::CIRCLE as UINT, ..Default::default() };
+        assert_eq!(axis.units(), Some(TU::CIRCLE));
+        assert_eq!(AXIS::default().units(), Some(TU::NONE));
+        axis.axUnits = 4;
+        assert_eq!(axis.units(), None);
+        assert_eq!(TU::try_from(7), Err(7));
+    }
+}
@@ -37,4 +37,64 @@ impl<T:std::fmt::Binary> Deref for Bitmask<T> {
     fn deref(&self) -> &Self::Target {
         &self.0
     }
-}
\ No newline at end of file
+}
+
+macro_rules! impl_bitmask_bits {
+    ($($type:ty),*) => {$(
+        impl Bitmask<$type> {
+            /// The number of bits in the mask
+            pub const BITS: u32 = <$type>::BITS;
+
+            /// Returns true if bit `n` is set. Bits past [Self::BITS] are never set.
+            pub fn contains(&self, n: u32) -> bool {
+                n < Self::BITS && self.0 & (1 << n) != 0
+            }
+
+            /// Returns true if no bits are set
+            pub fn is_empty(&self) -> bool {
+                self.0 == 0
+            }
+
+            /// The numbers of the set bits, lowest first
+            pub fn iter(&self) -> impl Iterator<Item = u32> {
+                let bits = self.0;
+                (0..Self::BITS).filter(move |n| bits & (1 << n) != 0)
+            }
+
+            /// Compares against the `previous` state of the mask, returning the bits which have
+            /// been set (pressed) and the bits which have been cleared (released) since.
+            pub fn diff(&self, previous: Self) -> (Self, Self) {
+                (Bitmask(self.0 & !previous.0), Bitmask(previous.0 & !self.0))
+            }
+        }
+    )*};
+}
+impl_bitmask_bits!(u8, u16, u32, u64);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bits() {
+        let mask = Bitmask(0b1010_0001u32);
+        assert!(mask.contains(0));
+        assert!(!mask.contains(1));
+        assert!(mask.contains(7));
+        assert!(!mask.contains(32));
+        assert_eq!(mask.iter().collect::<Vec<_>>(), [0, 5, 7]);
+        assert!(Bitmask(0u8).is_empty());
+        assert_eq!(Bitmask(0x8000_0000u32).iter().collect::<Vec<_>>(), [31]);
+        assert_eq!(format!("{:?}", Bitmask(0b101u8)), "0b00000101");
+    }
+
+    #[test]
+    fn test_diff() {
+        let previous = Bitmask(0b0110u32);
+        let current = Bitmask(0b1100u32);
+        let (pressed, released) = current.diff(previous);
+        assert_eq!(pressed, Bitmask(0b1000));
+        assert_eq!(released, Bitmask(0b0010));
+        assert_eq!(current.diff(current), (Bitmask(0), Bitmask(0)));
+    }
+}
@@ -0,0 +1,289 @@
+use std::collections::HashMap;
+
+use crate::{
+    c_type_aliases::UINT,
+    information_categories::{CSR, WTI},
+    Bitmask, Packet, WintabApi,
+};
+
+/// How a cursor's buttons are laid out, read from its [WTI::CURSORS] category.
+///
+/// [Packet::pkButtons] reports logical button numbers. Several physical buttons can be mapped to
+/// the same logical button by [CSR::BUTTONMAP], and [CSR::BTNNAMES] names the physical buttons.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CursorButtons {
+    /// The names of the physical buttons ([CSR::BTNNAMES])
+    pub names: Vec<String>,
+    /// The logical button number of each physical button ([CSR::BUTTONMAP])
+    pub button_map: [u8; 32],
+    /// The system action code (`SBN_*` in `wintab.h`, e.g. `1` for a left click) of each logical
+    /// button ([CSR::SYSBTNMAP])
+    pub system_button_map: [u8; 32],
+}
+
+impl Default for CursorButtons {
+    /// No names, and each physical button mapped to the logical button with the same number
+    fn default() -> Self {
+        Self {
+            names: Vec::new(),
+            button_map: std::array::from_fn(|index| index as u8),
+            system_button_map: [0; 32],
+        }
+    }
+}
+
+impl CursorButtons {
+    /// Reads the button tables of `cursor`, the zero based cursor index as in
+    /// [Packet::pkCursor]. Tables the driver does not support are left as in
+    /// [CursorButtons::default].
+    pub fn load<A: WintabApi + ?Sized>(api: &A, cursor: UINT) -> Self {
+        let category = WTI::CURSORS as UINT + cursor;
+        let default = Self::default();
+        Self {
+            names: api.cursor_button_names(cursor).unwrap_or_default(),
+            button_map: api
+                .info_value(category, CSR::BUTTONMAP as UINT)
+                .unwrap_or(default.button_map),
+            system_button_map: api
+                .info_value(category, CSR::SYSBTNMAP as UINT)
+                .unwrap_or(default.system_button_map),
+        }
+    }
+
+    /// The first physical button mapped to the `logical` button
+    pub fn physical_button(&self, logical: u8) -> Option<u8> {
+        self.button_map
+            .iter()
+            .position(|mapped| *mapped == logical)
+            .map(|physical| physical as u8)
+    }
+
+    /// The name of the physical button mapped to the `logical` button
+    pub fn name(&self, logical: u8) -> Option<&str> {
+        let physical = self.physical_button(logical)?;
+        self.names.get(physical as usize).map(String::as_str)
+    }
+
+    /// The system action code of the `logical` button; see [CursorButtons::system_button_map]
+    pub fn system_action(&self, logical: u8) -> u8 {
+        self.system_button_map.get(logical as usize).copied().unwrap_or(0)
+    }
+}
+
+/// A button pressed or released, produced by [ButtonTracker]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ButtonEvent {
+    /// See [Packet::pkCursor]
+    pub cursor: UINT,
+    /// The logical button number, the bit in [Packet::pkButtons]
+    pub button: u8,
+    pub pressed: bool,
+    /// See [CursorButtons::name]
+    pub name: Option<String>,
+    /// See [CursorButtons::system_action]
+    pub system_action: u8,
+}
+
+impl std::fmt::Display for ButtonEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.name {
+            Some(name) => write!(f, "{name}")?,
+            None => write!(f, "Button {}", self.button)?,
+        }
+        write!(f, " {}", if self.pressed { "pressed" } else { "released" })
+    }
+}
+
+/// Turns changes in [Packet::pkButtons] into named [ButtonEvent]s.
+/// The button tables of each cursor are read from the driver the first time the cursor is seen.
+///
+/// ```ignore
+/// let mut buttons = ButtonTracker::new();
+/// let mut events = Vec::new();
+/// for packet in reader.drain(&context) {
+///     buttons.update(context.api(), packet, &mut events);
+/// }
+/// for event in events.drain(..) {
+///     println!("{event}"); // "Lower Button pressed"
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ButtonTracker {
+    cursors: HashMap<UINT, CursorButtons>,
+    /// The cursor and buttons of the previous packet
+    previous: Option<(UINT, Bitmask<u32>)>,
+}
+
+impl ButtonTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Uses `buttons` for `cursor` instead of reading the tables from the driver
+    pub fn insert_cursor(&mut self, cursor: UINT, buttons: CursorButtons) {
+        self.cursors.insert(cursor, buttons);
+    }
+
+    /// The button tables of `cursor`, reading them from the driver if they have not been yet
+    pub fn cursor<A: WintabApi + ?Sized>(&mut self, api: &A, cursor: UINT) -> &CursorButtons {
+        self.cursors
+            .entry(cursor)
+            .or_insert_with(|| CursorButtons::load(api, cursor))
+    }
+
+    /// Compares the buttons of `packet` with the previous packet and appends an event for each
+    /// change to `events`. When the cursor changes, the buttons held on the old cursor are
+    /// released first.
+    pub fn update<A: WintabApi + ?Sized>(
+        &mut self,
+        api: &A,
+        packet: &Packet,
+        events: &mut impl Extend<ButtonEvent>,
+    ) {
+        let cursor = packet.pkCursor;
+        let buttons = packet.pkButtons;
+        let previous = match self.previous {
+            Some((previous_cursor, previous)) if previous_cursor != cursor => {
+                self.release(api, previous_cursor, previous, events);
+                Bitmask(0)
+            }
+            Some((_, previous)) => previous,
+            None => Bitmask(0),
+        };
+        let (pressed, released) = buttons.diff(previous);
+        self.release(api, cursor, released, events);
+        let table = self.cursor(api, cursor);
+        events.extend(pressed.iter().map(|n| event(table, cursor, n as u8, true)));
+        self.previous = Some((cursor, buttons));
+    }
+
+    /// Releases every held button, e.g. when the pen leaves proximity or the context loses focus
+    pub fn release_all<A: WintabApi + ?Sized>(
+        &mut self,
+        api: &A,
+        events: &mut impl Extend<ButtonEvent>,
+    ) {
+        if let Some((cursor, held)) = self.previous.take() {
+            self.release(api, cursor, held, events);
+        }
+    }
+
+    fn release<A: WintabApi + ?Sized>(
+        &mut self,
+        api: &A,
+        cursor: UINT,
+        released: Bitmask<u32>,
+        events: &mut impl Extend<ButtonEvent>,
+    ) {
+        if released.is_empty() {
+            return;
+        }
+        let table = self.cursor(api, cursor);
+        events.extend(released.iter().map(|n| event(table, cursor, n as u8, false)));
+    }
+}
+
+fn event(table: &CursorButtons, cursor: UINT, button: u8, pressed: bool) -> ButtonEvent {
+    ButtonEvent {
+        cursor,
+        button,
+        pressed,
+        name: table.name(button).map(str::to_string),
+        system_action: table.system_action(button),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FakeWintab;
+
+    /// A pen with the tip and two barrel buttons, where the upper button is mapped to logical
+    /// button 1 like the lower button
+    fn fake_pen() -> FakeWintab {
+        let api = FakeWintab::new();
+        let category = WTI::CURSORS as UINT + 1;
+        api.set_info_string(category, CSR::BTNNAMES as UINT, "Tip\0Lower Button\0Upper Button\0");
+        let mut button_map = [0u8; 32];
+        button_map[..3].copy_from_slice(&[0, 1, 1]);
+        api.set_info(category, CSR::BUTTONMAP as UINT, &button_map);
+        let mut system_button_map = [0u8; 32];
+        system_button_map[..2].copy_from_slice(&[1, 4]);
+        api.set_info(category, CSR::SYSBTNMAP as UINT, &system_button_map);
+        api
+    }
+
+    fn packet(cursor: UINT, buttons: u32) -> Packet {
+        Packet { pkCursor: cursor, pkButtons: Bitmask(buttons), ..Default::default() }
+    }
+
+    #[test]
+    fn test_load_cursor_tables() {
+        let api = fake_pen();
+        let buttons = CursorButtons::load(&api, 1);
+        assert_eq!(buttons.name(0), Some("Tip"));
+        assert_eq!(buttons.name(1), Some("Lower Button"));
+        assert_eq!(buttons.name(2), None);
+        assert_eq!(buttons.physical_button(1), Some(1));
+        assert_eq!(buttons.system_action(1), 4);
+
+        // a cursor without tables
+        let buttons = CursorButtons::load(&api, 0);
+        assert_eq!(buttons, CursorButtons::default());
+        assert_eq!(buttons.physical_button(5), Some(5));
+        assert_eq!(buttons.name(0), None);
+    }
+
+    #[test]
+    fn test_named_events() {
+        let api = fake_pen();
+        let mut tracker = ButtonTracker::new();
+        let mut events = Vec::new();
+        for buttons in [0b00, 0b10, 0b11, 0b01, 0b00] {
+            tracker.update(&api, &packet(1, buttons), &mut events);
+        }
+        let text: Vec<_> = events.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            text,
+            [
+                "Lower Button pressed",
+                "Tip pressed",
+                "Lower Button released",
+                "Tip released",
+            ]
+        );
+        assert_eq!(events[1].system_action, 1);
+        assert_eq!(events[1].cursor, 1);
+    }
+
+    #[test]
+    fn test_cursor_change_releases_old_buttons() {
+        let api = fake_pen();
+        let mut tracker = ButtonTracker::new();
+        tracker.insert_cursor(2, CursorButtons { names: vec!["Eraser".into()], ..Default::default() });
+        let mut events = Vec::new();
+        tracker.update(&api, &packet(1, 0b10), &mut events);
+        tracker.update(&api, &packet(2, 0b01), &mut events);
+        tracker.release_all(&api, &mut events);
+        let text: Vec<_> = events.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            text,
+            [
+                "Lower Button pressed",
+                "Lower Button released",
+                "Eraser pressed",
+                "Eraser released",
+            ]
+        );
+        assert_eq!(events[2].cursor, 2);
+    }
+
+    #[test]
+    fn test_unnamed_buttons() {
+        let api = FakeWintab::new();
+        let mut tracker = ButtonTracker::new();
+        let mut events = Vec::new();
+        tracker.update(&api, &packet(0, 1 << 31), &mut events);
+        assert_eq!(events[0].to_string(), "Button 31 pressed");
+    }
+}
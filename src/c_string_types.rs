@@ -1,9 +1,17 @@
 use std::{
+    borrow::Cow,
     ffi::c_uchar,
     ops::Deref
 };
 
 
+/// A fixed size, zero-terminated `char[40]` string as used by the `A` (ANSI) variants of the wintab
+/// structs. e.g. [LOGCONTEXT::lcName](crate::LOGCONTEXT::lcName)
+///
+/// The bytes are in the system ANSI code page, which is not UTF-8. Use
+/// [CString40::to_string_windows_1252] (also used by the [Display](std::fmt::Display)
+/// implementation) to decode names containing non-ASCII characters. The [Deref] to [str]
+/// implementation only returns the leading portion of the string which happens to be valid UTF-8.
 #[repr(C)]
 #[derive(Copy, Clone, PartialEq, Eq)]
 pub struct CString40 {
@@ -15,6 +23,53 @@ pub struct CString40 {
     inner:[c_uchar; 40]
 }
 
+/// A fixed size, zero-terminated `WCHAR[40]` string as used by the `W` (Unicode) variants of the
+/// wintab structs. The content is UTF-16.
+#[repr(C)]
+#[derive(Copy, Clone, PartialEq, Eq)]
+pub struct CString40W {
+    inner:[u16; 40]
+}
+
+/// Describes what happened when a string was written into a [CString40] or [CString40W] buffer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct EncodeReport {
+    /// The number of code units (bytes or `u16`s) written, excluding the zero terminator
+    pub written: usize,
+    /// `true` if the input did not fit (or contained a zero character) and was cut short
+    pub truncated: bool,
+    /// The number of characters which could not be represented and were replaced with `?`.
+    /// Always zero for [CString40W]
+    pub replaced: usize,
+}
+
+/// Windows-1252 code points for bytes `0x80..=0x9F`.
+/// Bytes which are undefined in Windows-1252 map to the matching C1 control character, which is
+/// what `MultiByteToWideChar` does.
+const WINDOWS_1252_HIGH: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+fn windows_1252_decode(byte: u8) -> char {
+    match byte {
+        0x80..=0x9F => WINDOWS_1252_HIGH[(byte - 0x80) as usize],
+        _ => byte as char,
+    }
+}
+
+fn windows_1252_encode(character: char) -> Option<u8> {
+    match character as u32 {
+        code @ (0x00..=0x7F | 0xA0..=0xFF) => Some(code as u8),
+        _ => WINDOWS_1252_HIGH
+            .iter()
+            .position(|c| *c == character)
+            .map(|index| 0x80 + index as u8),
+    }
+}
+
 
 impl Default for CString40 {
     fn default() -> Self {
@@ -25,15 +80,13 @@ impl Default for CString40 {
 
 impl Deref for CString40 {
     type Target=str;
+    /// Returns the leading part of the string which is valid UTF-8.
+    /// For pure ASCII names (the common case) this is the whole string.
     fn deref(&self) -> &Self::Target {
-        let end_index = self.inner.iter().position(|char|*char == 0).unwrap_or(0);
-        if end_index == 0 {
-            return "";
-        }else{
-            let slice = &self.inner[..end_index];
-            unsafe {
-                std::str::from_utf8_unchecked(slice)
-            }
+        let bytes = self.as_bytes();
+        match std::str::from_utf8(bytes) {
+            Ok(string) => string,
+            Err(error) => std::str::from_utf8(&bytes[..error.valid_up_to()]).unwrap_or_default(),
         }
     }
 }
@@ -41,35 +94,139 @@ impl Deref for CString40 {
 
 impl std::fmt::Display for CString40 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.deref())
+        write!(f, "{}", self.to_string_windows_1252())
     }
 }
 
 
 impl std::fmt::Debug for CString40 {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "c40\"{}\"", self.deref())
+        write!(f, "c40\"{}\"", self.to_string_windows_1252())
+    }
+}
+
+
+impl From<&str> for CString40 {
+    /// See [CString40::write_str]
+    fn from(value: &str) -> Self {
+        let mut result = Self::default();
+        result.write_str(value);
+        result
     }
 }
 
 
 impl CString40 {
-    /// A utility function to write a string into the internal buffer.
-    /// This function will truncate the string if it is longer than the CString40 buffer.
+    /// The raw bytes of the string, up to (not including) the first zero byte.
+    pub fn as_bytes(&self) -> &[u8] {
+        let end_index = self.inner.iter().position(|char|*char == 0).unwrap_or(self.inner.len());
+        &self.inner[..end_index]
+    }
+
+    /// Decodes the string as UTF-8, replacing invalid sequences with `U+FFFD`.
+    pub fn to_string_lossy(&self) -> Cow<'_, str> {
+        String::from_utf8_lossy(self.as_bytes())
+    }
+
+    /// Decodes the string as Windows-1252 (the ANSI code page used by western locales).
+    /// Every byte maps to a character so this never fails.
+    pub fn to_string_windows_1252(&self) -> String {
+        self.as_bytes().iter().copied().map(windows_1252_decode).collect()
+    }
+
+    /// A utility function to write a string into the internal buffer, encoded as Windows-1252.
+    /// This function will truncate the string if it is longer than 39 bytes (the last byte is
+    /// always a zero terminator). Characters that can't be represented in Windows-1252 are
+    /// replaced with `?`.
     /// If the input string is less than the internal buffer length,
     /// then the remainder of the inner buffer will be filled with null.
-    pub fn write_str(&mut self, new_value:&str) {
-        for (i, c) in self.inner.iter_mut().enumerate() {
-            *c = match new_value.chars().nth(i) {
-                Some(c) => match (c as i8).try_into() {
-                    Ok(c) => c,
-                    Err(_) => 32,
-                },
-                None => 0,
+    pub fn write_str(&mut self, new_value:&str) -> EncodeReport {
+        let mut report = EncodeReport::default();
+        let capacity = self.inner.len() - 1;
+        for character in new_value.chars() {
+            if character == '\0' || report.written == capacity {
+                report.truncated = true;
+                break;
+            }
+            self.inner[report.written] = windows_1252_encode(character).unwrap_or_else(|| {
+                report.replaced += 1;
+                b'?'
+            });
+            report.written += 1;
+        }
+        self.inner[report.written..].fill(0);
+        report
+    }
+}
+
+
+impl Default for CString40W {
+    fn default() -> Self {
+        Self{inner: [0; 40]}
+    }
+}
+
+
+impl std::fmt::Display for CString40W {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.to_string_lossy())
+    }
+}
+
+
+impl std::fmt::Debug for CString40W {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "c40w\"{}\"", self.to_string_lossy())
+    }
+}
+
+
+impl From<&str> for CString40W {
+    /// See [CString40W::write_str]
+    fn from(value: &str) -> Self {
+        let mut result = Self::default();
+        result.write_str(value);
+        result
+    }
+}
+
+
+impl CString40W {
+    /// The raw UTF-16 code units of the string, up to (not including) the first zero.
+    pub fn as_wide(&self) -> &[u16] {
+        let end_index = self.inner.iter().position(|char|*char == 0).unwrap_or(self.inner.len());
+        &self.inner[..end_index]
+    }
+
+    /// Decodes the string, returning an error if it contains unpaired surrogates.
+    pub fn try_to_string(&self) -> Result<String, std::string::FromUtf16Error> {
+        String::from_utf16(self.as_wide())
+    }
+
+    /// Decodes the string, replacing unpaired surrogates with `U+FFFD`.
+    pub fn to_string_lossy(&self) -> String {
+        String::from_utf16_lossy(self.as_wide())
+    }
+
+    /// Write a string into the internal buffer as UTF-16.
+    /// This function will truncate the string if it is longer than 39 code units (the last one is
+    /// always a zero terminator). Surrogate pairs are never split by truncation.
+    /// The remainder of the inner buffer will be filled with null.
+    pub fn write_str(&mut self, new_value:&str) -> EncodeReport {
+        let mut report = EncodeReport::default();
+        let capacity = self.inner.len() - 1;
+        let mut units = [0u16; 2];
+        for character in new_value.chars() {
+            let encoded = character.encode_utf16(&mut units);
+            if character == '\0' || report.written + encoded.len() > capacity {
+                report.truncated = true;
+                break;
             }
+            self.inner[report.written..report.written + encoded.len()].copy_from_slice(encoded);
+            report.written += encoded.len();
         }
-        // set the last value to null to guarantee that this is always a valid CString 🙄
-        self.inner.last_mut().map(|c| *c = 0 );
+        self.inner[report.written..].fill(0);
+        report
     }
 }
 
@@ -99,4 +256,59 @@ mod tests {
         // println!("cast_string='{cast_string}'");
         assert_eq!(cstring.len(), 5);
     }
+
+    #[test]
+    fn test_cstring40_non_ascii(){
+        let mut buffer:[std::ffi::c_uchar; 40] = [0; 40];
+        // "Café ™" in Windows-1252
+        buffer[..6].copy_from_slice(&[b'C', b'a', b'f', 0xE9, b' ', 0x99]);
+        let cstring = CString40{inner: buffer};
+        assert_eq!(cstring.to_string_windows_1252(), "Café ™");
+        assert_eq!(cstring.to_string(), "Café ™");
+        assert_eq!(cstring.to_string_lossy(), "Caf\u{FFFD} \u{FFFD}");
+        // deref stops at the first byte which is not valid UTF-8
+        assert_eq!(&*cstring, "Caf");
+
+        // no zero terminator at all
+        let cstring = CString40{inner: [b'a'; 40]};
+        assert_eq!(cstring.as_bytes().len(), 40);
+    }
+
+    #[test]
+    fn test_cstring40_write_str(){
+        let mut cstring = CString40{inner: [b'x'; 40]};
+        let report = cstring.write_str("Café ™ 🖊");
+        assert_eq!(report, EncodeReport{written: 8, truncated: false, replaced: 1});
+        assert_eq!(cstring.as_bytes(), &[b'C', b'a', b'f', 0xE9, b' ', 0x99, b' ', b'?']);
+        assert!(cstring.inner[8..].iter().all(|c| *c == 0));
+
+        let report = cstring.write_str(&"a".repeat(50));
+        assert_eq!(report, EncodeReport{written: 39, truncated: true, replaced: 0});
+        assert_eq!(cstring.len(), 39);
+        assert_eq!(cstring.inner[39], 0);
+
+        let report = cstring.write_str("ab\0cd");
+        assert_eq!(report, EncodeReport{written: 2, truncated: true, replaced: 0});
+        assert_eq!(&*cstring, "ab");
+    }
+
+    #[test]
+    fn test_cstring40w(){
+        let mut cstring = CString40W::default();
+        assert_eq!(cstring.to_string_lossy(), "");
+        let report = cstring.write_str("Stylet 🖊 Ü");
+        assert_eq!(report, EncodeReport{written: 11, truncated: false, replaced: 0});
+        assert_eq!(cstring.try_to_string().unwrap(), "Stylet 🖊 Ü");
+        assert_eq!(format!("{cstring:?}"), "c40w\"Stylet 🖊 Ü\"");
+
+        // the surrogate pair would straddle the terminator so it is dropped entirely
+        let report = cstring.write_str(&format!("{}🖊", "a".repeat(38)));
+        assert_eq!(report, EncodeReport{written: 38, truncated: true, replaced: 0});
+        assert_eq!(cstring.as_wide().len(), 38);
+        assert_eq!(cstring.inner[38..], [0, 0]);
+
+        cstring.inner[0] = 0xD800; // unpaired surrogate
+        assert!(cstring.try_to_string().is_err());
+        assert!(cstring.to_string_lossy().starts_with('\u{FFFD}'));
+    }
 }
@@ -0,0 +1,261 @@
+use crate::{c_type_aliases::LONG, Packet, LOGCONTEXTW, XY};
+
+/// The scaling a context applies to one axis, from the tablet's native coordinates to context
+/// output coordinates
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AxisScaling {
+    pub in_org: f64,
+    pub in_ext: f64,
+    pub out_org: f64,
+    /// Negative when the axis is flipped, e.g. so that y points down the screen
+    pub out_ext: f64,
+}
+
+impl AxisScaling {
+    pub fn new(in_org: LONG, in_ext: LONG, out_org: LONG, out_ext: LONG) -> Self {
+        Self {
+            in_org: in_org as f64,
+            in_ext: in_ext as f64,
+            out_org: out_org as f64,
+            out_ext: out_ext as f64,
+        }
+    }
+
+    /// Applies the scaling formula of the wintab specification, without rounding
+    pub fn to_output(&self, input: f64) -> f64 {
+        let in_ext = self.in_ext.abs();
+        if in_ext == 0.0 {
+            return self.out_org;
+        }
+        let offset = input - self.in_org;
+        let offset = if self.out_ext < 0.0 { in_ext - offset } else { offset };
+        offset * self.out_ext.abs() / in_ext + self.out_org
+    }
+
+    /// The inverse of [AxisScaling::to_output]
+    pub fn to_input(&self, output: f64) -> f64 {
+        let out_ext = self.out_ext.abs();
+        if out_ext == 0.0 {
+            return self.in_org;
+        }
+        let offset = (output - self.out_org) * self.in_ext.abs() / out_ext;
+        let offset = if self.out_ext < 0.0 { self.in_ext.abs() - offset } else { offset };
+        offset + self.in_org
+    }
+}
+
+/// The x and y [AxisScaling] of a context
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ContextScaling {
+    pub x: AxisScaling,
+    pub y: AxisScaling,
+}
+
+impl ContextScaling {
+    /// Reads the input and output areas of a context. Use the context as returned by
+    /// [Context::get](crate::Context::get), since the driver may clip the input area on open.
+    pub fn from_log_context(log_context: &LOGCONTEXTW) -> Self {
+        let (in_org, in_ext) = (log_context.lcInOrgXYZ, log_context.lcInExtXYZ);
+        let (out_org, out_ext) = (log_context.lcOutOrgXYZ, log_context.lcOutExtXYZ);
+        Self {
+            x: AxisScaling::new(in_org.x, in_ext.x, out_org.x, out_ext.x),
+            y: AxisScaling::new(in_org.y, in_ext.y, out_org.y, out_ext.y),
+        }
+    }
+
+    pub fn to_output(&self, input: XY<f64>) -> XY<f64> {
+        XY { x: self.x.to_output(input.x), y: self.y.to_output(input.y) }
+    }
+
+    pub fn to_input(&self, output: XY<f64>) -> XY<f64> {
+        XY { x: self.x.to_input(output.x), y: self.y.to_input(output.y) }
+    }
+}
+
+/// Converts packets from a system context, whose output is in physical screen pixels with y
+/// pointing down, to logical coordinates within the client area of a window, like
+/// `ScreenToClient` followed by dividing by the window's scale factor.
+///
+/// A system context only reports whole pixels. For sub-pixel precision, open a second
+/// (digitizing) context over the same input area with
+/// [ClientMapper::with_digitizing_context] and pass its packets to [ClientMapper::map_precise];
+/// its tablet coordinates are scaled to the screen with the system context's own formula.
+///
+/// The process must be per-monitor DPI aware (`SetProcessDpiAwarenessContext`), otherwise
+/// Windows reports scaled window positions which do not match the packets.
+///
+/// ```ignore
+/// let mut mapper = ClientMapper::new(XY { x: 100.0, y: 50.0 }, window.scale_factor())
+///     .with_digitizing_context(&system.get().unwrap(), &digitizing.get().unwrap());
+/// let logical = mapper.map_precise(&system_packet, &digitizing_packet);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ClientMapper {
+    client_origin: XY<f64>,
+    scale_factor: f64,
+    /// The system and digitizing context scaling, when sub-pixel precision is available
+    precise: Option<(ContextScaling, ContextScaling)>,
+}
+
+impl Default for ClientMapper {
+    fn default() -> Self {
+        Self::new(XY::default(), 1.0)
+    }
+}
+
+impl ClientMapper {
+    /// `client_origin` is the top left of the client area in physical screen pixels, and
+    /// `scale_factor` is physical pixels per logical pixel
+    pub fn new(client_origin: XY<f64>, scale_factor: f64) -> Self {
+        Self { client_origin, scale_factor, precise: None }
+    }
+
+    /// Enables [ClientMapper::map_precise], given the system context and a digitizing context
+    /// opened over the same input area
+    pub fn with_digitizing_context(
+        mut self,
+        system: &LOGCONTEXTW,
+        digitizing: &LOGCONTEXTW,
+    ) -> Self {
+        self.precise = Some((
+            ContextScaling::from_log_context(system),
+            ContextScaling::from_log_context(digitizing),
+        ));
+        self
+    }
+
+    /// The top left of the client area in physical screen pixels
+    pub fn client_origin(&self) -> XY<f64> {
+        self.client_origin
+    }
+
+    /// Physical pixels per logical pixel
+    pub fn scale_factor(&self) -> f64 {
+        self.scale_factor
+    }
+
+    /// Call when the window moves or its scale factor changes
+    pub fn set_window(&mut self, client_origin: XY<f64>, scale_factor: f64) {
+        self.client_origin = client_origin;
+        self.scale_factor = scale_factor;
+    }
+
+    /// Converts physical screen pixels to logical client coordinates
+    pub fn screen_to_client(&self, screen: XY<f64>) -> XY<f64> {
+        XY {
+            x: (screen.x - self.client_origin.x) / self.scale_factor,
+            y: (screen.y - self.client_origin.y) / self.scale_factor,
+        }
+    }
+
+    /// Converts logical client coordinates to physical screen pixels
+    pub fn client_to_screen(&self, client: XY<f64>) -> XY<f64> {
+        XY {
+            x: client.x * self.scale_factor + self.client_origin.x,
+            y: client.y * self.scale_factor + self.client_origin.y,
+        }
+    }
+
+    /// The position of a system context packet, to the nearest physical pixel
+    pub fn map(&self, system: &Packet) -> XY<f64> {
+        let position = system.pkXYZ;
+        self.screen_to_client(XY { x: position.x as f64, y: position.y as f64 })
+    }
+
+    /// The position of the pen in physical screen pixels with sub-pixel precision, taken from a
+    /// packet of the digitizing context. Falls back on the whole pixel position of the system
+    /// packet if there is no digitizing context, or if the two packets are more than a pixel
+    /// apart (i.e. they were not reported at the same moment).
+    pub fn precise_screen(&self, system: &Packet, digitizing: &Packet) -> XY<f64> {
+        let position = system.pkXYZ;
+        let whole = XY { x: position.x as f64, y: position.y as f64 };
+        let Some((system_scaling, digitizing_scaling)) = self.precise else {
+            return whole;
+        };
+        let position = digitizing.pkXYZ;
+        let tablet = digitizing_scaling.to_input(XY { x: position.x as f64, y: position.y as f64 });
+        let precise = system_scaling.to_output(tablet);
+        if (precise.x - whole.x).abs() < 1.0 && (precise.y - whole.y).abs() < 1.0 {
+            precise
+        } else {
+            whole
+        }
+    }
+
+    /// Like [ClientMapper::map], with sub-pixel precision from [ClientMapper::precise_screen]
+    pub fn map_precise(&self, system: &Packet, digitizing: &Packet) -> XY<f64> {
+        self.screen_to_client(self.precise_screen(system, digitizing))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::XYZ;
+
+    fn close(a: XY<f64>, b: XY<f64>) -> bool {
+        (a.x - b.x).abs() < 1e-9 && (a.y - b.y).abs() < 1e-9
+    }
+
+    fn packet(x: LONG, y: LONG) -> Packet {
+        Packet { pkXYZ: XYZ { x, y, z: 0 }, ..Default::default() }
+    }
+
+    /// A tablet of 20000 x 10000 counts over a 2000 x 1000 pixel screen, and a digitizing context
+    /// reporting raw counts with y up
+    fn contexts() -> (LOGCONTEXTW, LOGCONTEXTW) {
+        let input = LOGCONTEXTW {
+            lcInOrgXYZ: XYZ { x: 0, y: 0, z: 0 },
+            lcInExtXYZ: XYZ { x: 20000, y: 10000, z: 0 },
+            ..Default::default()
+        };
+        let system = LOGCONTEXTW {
+            lcOutOrgXYZ: XYZ { x: 0, y: 0, z: 0 },
+            lcOutExtXYZ: XYZ { x: 2000, y: -1000, z: 0 },
+            ..input
+        };
+        let digitizing = LOGCONTEXTW { lcOutExtXYZ: input.lcInExtXYZ, ..input };
+        (system, digitizing)
+    }
+
+    #[test]
+    fn test_axis_scaling() {
+        let scaling = AxisScaling::new(100, 1000, 10, 200);
+        assert_eq!(scaling.to_output(100.0), 10.0);
+        assert_eq!(scaling.to_output(600.0), 110.0);
+        assert_eq!(scaling.to_input(110.0), 600.0);
+        // flipped: the end of the input maps to the origin of the output
+        let flipped = AxisScaling::new(100, 1000, 10, -200);
+        assert_eq!(flipped.to_output(1100.0), 10.0);
+        assert_eq!(flipped.to_output(100.0), 210.0);
+        assert_eq!(flipped.to_input(flipped.to_output(345.0)), 345.0);
+        assert_eq!(AxisScaling::new(0, 0, 5, 10).to_output(7.0), 5.0);
+    }
+
+    #[test]
+    fn test_screen_to_client() {
+        let mut mapper = ClientMapper::new(XY { x: 300.0, y: 200.0 }, 1.5);
+        assert_eq!(mapper.map(&packet(450, 350)), XY { x: 100.0, y: 100.0 });
+        assert_eq!(mapper.client_to_screen(XY { x: 100.0, y: 100.0 }), XY { x: 450.0, y: 350.0 });
+        // left of and above the window is negative
+        mapper.set_window(XY { x: 500.0, y: 400.0 }, 2.0);
+        assert_eq!(mapper.map(&packet(450, 350)), XY { x: -25.0, y: -25.0 });
+        // without a digitizing context there is no extra precision
+        assert_eq!(mapper.precise_screen(&packet(1, 2), &packet(9, 9)), XY { x: 1.0, y: 2.0 });
+    }
+
+    #[test]
+    fn test_sub_pixel_precision() {
+        let (system, digitizing) = contexts();
+        let mapper = ClientMapper::new(XY { x: 100.0, y: 50.0 }, 2.0)
+            .with_digitizing_context(&system, &digitizing);
+        // 10 counts per pixel; tablet y 7495 is screen y 1000 - 749.5
+        let precise = mapper.precise_screen(&packet(1234, 250), &packet(12345, 7495));
+        assert!(close(precise, XY { x: 1234.5, y: 250.5 }));
+        let client = mapper.map_precise(&packet(1234, 250), &packet(12345, 7495));
+        assert!(close(client, XY { x: 567.25, y: 100.25 }));
+        // packets from different moments do not agree, so the system packet wins
+        let stale = mapper.map_precise(&packet(1234, 250), &packet(0, 0));
+        assert_eq!(stale, XY { x: 567.0, y: 100.0 });
+    }
+}
@@ -0,0 +1,158 @@
+use windows::Win32::Foundation::HWND;
+
+use crate::{
+    c_type_aliases::{HCTX, UINT},
+    WintabApi, LOGCONTEXTW,
+};
+
+/// The ways opening or configuring a [Context] can fail
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContextError {
+    /// `WTOpenW` returned a null handle. This usually means the [LOGCONTEXTW] requested options or
+    /// packet data which are not supported by the driver, or that no tablet is connected.
+    OpenFailed,
+}
+
+impl std::fmt::Display for ContextError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContextError::OpenFailed => write!(f, "WTOpenW failed to open the wintab context"),
+        }
+    }
+}
+
+impl std::error::Error for ContextError {}
+
+/// An open wintab context. The context is closed with `WTClose` when this is dropped.
+///
+/// ```ignore
+/// let api = unsafe { LibloadingApi::load()? };
+/// let mut log_context = api.default_system_context().unwrap();
+/// let context = Context::open(&api, hwnd, &mut log_context, true)?;
+/// ```
+pub struct Context<A: WintabApi> {
+    api: A,
+    handle: *mut HCTX,
+}
+
+impl<A: WintabApi> Context<A> {
+    /// Opens a context owned by `window` using `WTOpenW`.
+    /// On success `log_context` is updated by the driver with the values actually in use
+    /// (e.g. the real [LOGCONTEXTW::lcPktRate]).
+    pub fn open(
+        api: A,
+        window: HWND,
+        log_context: &mut LOGCONTEXTW,
+        enable: bool,
+    ) -> Result<Self, ContextError> {
+        let handle = unsafe { api.open_w(window, log_context, enable.into()) };
+        if handle.is_null() {
+            Err(ContextError::OpenFailed)
+        } else {
+            Ok(Self { api, handle })
+        }
+    }
+
+    /// Takes ownership of a context opened some other way; it will be closed on drop.
+    ///
+    /// # Safety
+    /// `handle` must be an open context handle obtained from the same driver as `api`, and must
+    /// not be closed by anything else.
+    pub unsafe fn from_raw(api: A, handle: *mut HCTX) -> Self {
+        Self { api, handle }
+    }
+
+    /// The raw context handle, for use with functions not wrapped by this crate
+    pub fn handle(&self) -> *mut HCTX {
+        self.handle
+    }
+
+    /// The function table this context was opened with
+    pub fn api(&self) -> &A {
+        &self.api
+    }
+
+    /// The current attributes of the context (`WTGetW`)
+    pub fn get(&self) -> Option<LOGCONTEXTW> {
+        let mut log_context = LOGCONTEXTW::default();
+        let ok = unsafe { self.api.get_w(self.handle, &mut log_context) };
+        (ok != 0).then_some(log_context)
+    }
+
+    /// Changes the attributes of the context on the fly (`WTSetW`).
+    /// Returns `false` if the driver refused the change.
+    pub fn set(&self, log_context: &mut LOGCONTEXTW) -> bool {
+        unsafe { self.api.set_w(self.handle, log_context) != 0 }
+    }
+
+    /// The serial numbers of the oldest and newest packets in the queue (`WTQueuePacketsEx`), or
+    /// `None` if the queue is empty.
+    pub fn queue_extent(&self) -> Option<(UINT, UINT)> {
+        let mut oldest = 0;
+        let mut newest = 0;
+        let ok = unsafe { self.api.queue_packets_ex(self.handle, &mut oldest, &mut newest) };
+        (ok != 0).then_some((oldest, newest))
+    }
+
+    /// Enables or disables the context (`WTEnable`), e.g. when the owning window loses focus.
+    /// Returns `false` if the request was refused.
+    pub fn enable(&self, enable: bool) -> bool {
+        unsafe { self.api.enable(self.handle, enable.into()) != 0 }
+    }
+
+    /// Closes the context now. Returns `false` if `WTClose` reported a failure.
+    pub fn close(mut self) -> bool {
+        let handle = std::mem::replace(&mut self.handle, std::ptr::null_mut());
+        unsafe { self.api.close(handle) != 0 }
+    }
+}
+
+impl<A: WintabApi> Drop for Context<A> {
+    fn drop(&mut self) {
+        if !self.handle.is_null() {
+            unsafe {
+                self.api.close(self.handle);
+            }
+        }
+    }
+}
+
+impl<A: WintabApi> std::fmt::Debug for Context<A> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Context").field("handle", &self.handle).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FakeWintab, CXO};
+
+    #[test]
+    fn test_open_and_close() {
+        let api = FakeWintab::new();
+        let mut log_context = LOGCONTEXTW { lcOptions: CXO::SYSTEM, ..Default::default() };
+        let context = Context::open(&api, HWND(1), &mut log_context, true).unwrap();
+        let handle = context.handle() as usize;
+        assert_eq!(api.state().open, vec![handle]);
+        assert_eq!(context.get().unwrap().lcOptions, CXO::SYSTEM);
+        drop(context);
+        assert!(api.state().open.is_empty());
+        assert_eq!(api.state().closed, vec![handle]);
+
+        let context = Context::open(&api, HWND(1), &mut log_context, true).unwrap();
+        assert!(context.enable(false));
+        assert_eq!(api.state().enable_calls, [(context.handle() as usize, false)]);
+        assert!(context.close());
+        assert_eq!(api.state().closed.len(), 2);
+    }
+
+    #[test]
+    fn test_open_failure() {
+        let api = FakeWintab::new();
+        api.state().fail_open = true;
+        let result = Context::open(&api, HWND(1), &mut LOGCONTEXTW::default(), true);
+        assert_eq!(result.unwrap_err(), ContextError::OpenFailed);
+        assert!(api.state().closed.is_empty());
+    }
+}
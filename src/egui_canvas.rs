@@ -0,0 +1,282 @@
+use egui::{Color32, Pos2, Rect, Response, Sense, Stroke, Ui, Vec2};
+
+use crate::{ClientMapper, OrientationScale, Packet, PressureCurve, XY};
+
+/// A pen sample in egui coordinates
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PenSample {
+    /// In egui points, relative to the window
+    pub pos: Pos2,
+    /// From `0.0` to `1.0`
+    pub pressure: f32,
+    /// X and Y tilt in radians; see [Tilt](crate::Tilt)
+    pub tilt: Vec2,
+}
+
+/// Converts packets from a context whose output is in screen pixels with y pointing down (a
+/// system context with a negative `lcOutExtY`) into [PenSample]s for an [egui::Context].
+/// Positions are converted with a [ClientMapper].
+///
+/// ```ignore
+/// let mapper = EguiPenMapper::new(client_origin).with_pixels_per_point(ctx.pixels_per_point());
+/// let samples: Vec<_> = reader.drain(&context).map(|packet| mapper.sample(packet)).collect();
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct EguiPenMapper {
+    mapper: ClientMapper,
+    pressure: PressureCurve,
+    orientation: OrientationScale,
+}
+
+impl Default for EguiPenMapper {
+    fn default() -> Self {
+        Self::new(Pos2::ZERO)
+    }
+}
+
+impl EguiPenMapper {
+    /// `client_origin` is the top left of the client area of the window, in screen pixels
+    pub fn new(client_origin: Pos2) -> Self {
+        Self {
+            mapper: ClientMapper::new(to_xy(client_origin), 1.0),
+            pressure: PressureCurve::default(),
+            orientation: OrientationScale::default(),
+        }
+    }
+
+    /// Uses the scale of `ctx`, see [egui::Context::pixels_per_point]
+    pub fn for_context(ctx: &egui::Context, client_origin: Pos2) -> Self {
+        Self::new(client_origin).with_pixels_per_point(ctx.pixels_per_point())
+    }
+
+    pub fn with_pixels_per_point(mut self, pixels_per_point: f32) -> Self {
+        self.mapper.set_window(self.mapper.client_origin(), pixels_per_point as f64);
+        self
+    }
+
+    pub fn with_pressure_curve(mut self, pressure: PressureCurve) -> Self {
+        self.pressure = pressure;
+        self
+    }
+
+    pub fn with_orientation_scale(mut self, orientation: OrientationScale) -> Self {
+        self.orientation = orientation;
+        self
+    }
+
+    /// Call when the window moves
+    pub fn set_client_origin(&mut self, client_origin: Pos2) {
+        self.mapper.set_window(to_xy(client_origin), self.mapper.scale_factor());
+    }
+
+    /// The mapping from screen pixels to egui points
+    pub fn client_mapper(&self) -> &ClientMapper {
+        &self.mapper
+    }
+
+    /// Converts a point in screen pixels to egui points
+    pub fn to_points(&self, screen: Pos2) -> Pos2 {
+        let points = self.mapper.screen_to_client(to_xy(screen));
+        Pos2::new(points.x as f32, points.y as f32)
+    }
+
+    pub fn sample(&self, packet: &Packet) -> PenSample {
+        let tilt = self.orientation.to_angles(packet.pkOrientation).tilt();
+        PenSample {
+            pos: self.to_points(Pos2::new(packet.pkXYZ.x as f32, packet.pkXYZ.y as f32)),
+            pressure: self.pressure.map(packet.pkNormalPressure) as f32,
+            tilt: Vec2::new(tilt.x as f32, tilt.y as f32),
+        }
+    }
+}
+
+fn to_xy(pos: Pos2) -> XY<f64> {
+    XY { x: pos.x as f64, y: pos.y as f64 }
+}
+
+/// A stroke recorded by a [PressureCanvas]. Sample positions are relative to the top left of
+/// the canvas, so strokes stay put if the canvas moves.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CanvasStroke {
+    pub samples: Vec<PenSample>,
+}
+
+/// A widget which records what is drawn on it with the pen, with per sample pressure and tilt,
+/// and paints the strokes with a width proportional to pressure.
+///
+/// ```ignore
+/// egui::CentralPanel::default().show(ctx, |ui| {
+///     canvas.show(ui, samples.drain(..));
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct PressureCanvas {
+    strokes: Vec<CanvasStroke>,
+    current: Option<CanvasStroke>,
+    stroke_width: f32,
+    color: Color32,
+}
+
+impl Default for PressureCanvas {
+    fn default() -> Self {
+        Self {
+            strokes: Vec::new(),
+            current: None,
+            stroke_width: 8.0,
+            color: Color32::BLACK,
+        }
+    }
+}
+
+impl PressureCanvas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The width of a stroke at full pressure, in points
+    pub fn with_stroke_width(mut self, stroke_width: f32) -> Self {
+        self.stroke_width = stroke_width;
+        self
+    }
+
+    pub fn with_color(mut self, color: Color32) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// The finished strokes, oldest first
+    pub fn strokes(&self) -> &[CanvasStroke] {
+        &self.strokes
+    }
+
+    /// The stroke being drawn, if the pen is down
+    pub fn current(&self) -> Option<&CanvasStroke> {
+        self.current.as_ref()
+    }
+
+    pub fn clear(&mut self) {
+        self.strokes.clear();
+        self.current = None;
+    }
+
+    /// Records `sample` for a canvas occupying `rect`. A stroke starts when the pen presses
+    /// inside the canvas, continues while it is pressed even outside, and ends when it lifts.
+    /// Returns `true` if the sample was used.
+    pub fn push(&mut self, rect: Rect, sample: PenSample) -> bool {
+        let pressed = sample.pressure > 0.0;
+        let local = PenSample { pos: (sample.pos - rect.min).to_pos2(), ..sample };
+        match &mut self.current {
+            Some(stroke) if pressed => {
+                stroke.samples.push(local);
+                true
+            }
+            Some(_) => {
+                self.strokes.extend(self.current.take());
+                true
+            }
+            None if pressed && rect.contains(sample.pos) => {
+                self.current = Some(CanvasStroke { samples: vec![local] });
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Fills the available space, records `samples` and paints every stroke
+    pub fn show(&mut self, ui: &mut Ui, samples: impl IntoIterator<Item = PenSample>) -> Response {
+        let (mut response, painter) = ui.allocate_painter(ui.available_size(), Sense::hover());
+        let rect = response.rect;
+        let mut changed = false;
+        for sample in samples {
+            changed |= self.push(rect, sample);
+        }
+        if changed {
+            response.mark_changed();
+        }
+        for stroke in self.strokes.iter().chain(&self.current) {
+            for pair in stroke.samples.windows(2) {
+                let width = self.stroke_width * (pair[0].pressure + pair[1].pressure) / 2.0;
+                painter.line_segment(
+                    [rect.min + pair[0].pos.to_vec2(), rect.min + pair[1].pos.to_vec2()],
+                    Stroke::new(width, self.color),
+                );
+            }
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Orientation, XYZ};
+
+    fn sample(x: f32, y: f32, pressure: f32) -> PenSample {
+        PenSample { pos: Pos2::new(x, y), pressure, tilt: Vec2::ZERO }
+    }
+
+    #[test]
+    fn test_mapper() {
+        let mapper = EguiPenMapper::new(Pos2::new(100.0, 50.0)).with_pixels_per_point(2.0);
+        let packet = Packet {
+            pkXYZ: XYZ { x: 300, y: 251, z: 0 },
+            pkNormalPressure: 1023,
+            pkOrientation: Orientation { orAzimuth: 900, orAltitude: 450, orTwist: 0 },
+            ..Default::default()
+        };
+        let sample = mapper.sample(&packet);
+        assert_eq!(sample.pos, Pos2::new(100.0, 100.5));
+        assert_eq!(sample.pressure, 1.0);
+        assert!((sample.tilt.x - std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+
+        let ctx = egui::Context::default();
+        ctx.set_pixels_per_point(1.5);
+        // the new scale takes effect from the next frame
+        let _ = ctx.run(egui::RawInput::default(), |_| {});
+        let mapper = EguiPenMapper::for_context(&ctx, Pos2::ZERO);
+        assert_eq!(mapper.to_points(Pos2::new(30.0, 15.0)), Pos2::new(20.0, 10.0));
+    }
+
+    #[test]
+    fn test_hit_testing() {
+        let rect = Rect::from_min_size(Pos2::new(10.0, 10.0), Vec2::new(100.0, 100.0));
+        let mut canvas = PressureCanvas::new();
+        // hovering, and pressing outside, are ignored
+        assert!(!canvas.push(rect, sample(50.0, 50.0, 0.0)));
+        assert!(!canvas.push(rect, sample(5.0, 50.0, 0.5)));
+        assert!(canvas.current().is_none());
+        // a stroke started inside continues outside
+        assert!(canvas.push(rect, sample(20.0, 30.0, 0.5)));
+        assert!(canvas.push(rect, sample(200.0, 30.0, 0.75)));
+        assert_eq!(canvas.current().unwrap().samples.len(), 2);
+        assert!(canvas.push(rect, sample(200.0, 30.0, 0.0)));
+        assert!(canvas.current().is_none());
+
+        let stroke = &canvas.strokes()[0];
+        assert_eq!(stroke.samples[0], sample(10.0, 20.0, 0.5));
+        assert_eq!(stroke.samples[1], sample(190.0, 20.0, 0.75));
+        canvas.clear();
+        assert!(canvas.strokes().is_empty());
+    }
+
+    #[test]
+    fn test_show_without_gpu() {
+        let ctx = egui::Context::default();
+        let mut canvas = PressureCanvas::new().with_stroke_width(4.0);
+        let samples = [sample(20.0, 20.0, 1.0), sample(40.0, 20.0, 0.5), sample(40.0, 20.0, 0.0)];
+        let mut changed = false;
+        let output = ctx.run(egui::RawInput::default(), |ctx| {
+            egui::CentralPanel::default().show(ctx, |ui| {
+                changed = canvas.show(ui, samples).changed();
+            });
+        });
+        assert!(changed);
+        assert_eq!(canvas.strokes().len(), 1);
+        let segments = output
+            .shapes
+            .iter()
+            .filter(|shape| matches!(shape.shape, egui::Shape::LineSegment { .. }))
+            .count();
+        assert_eq!(segments, 1);
+    }
+}
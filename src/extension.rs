@@ -0,0 +1,96 @@
+#![allow(non_snake_case)]
+use crate::{
+    c_type_aliases::{DWORD, HCTX, UINT},
+    packet::TPS,
+};
+
+/// Extension tags, as returned by [EXT::TAG](crate::EXT::TAG).
+/// Find the [EXT::MASK](crate::EXT::MASK) for a tag to select the extension in
+/// [LOGCONTEXT::lcPktData](crate::LOGCONTEXT::lcPktData).
+#[repr(u32)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WTX {
+    /// Out of bounds tracking
+    OBT        = 0,
+    /// Function keys
+    FKEYS      = 1,
+    /// Raw Cartesian tilt; 1.1
+    TILT       = 2,
+    /// select input by cursor type
+    CSRMASK    = 3,
+    /// Extended button mask
+    XBTNMASK   = 4,
+    /// ExpressKeys; deprecated, use [WTX::EXPKEYS2]
+    EXPKEYS    = 5,
+    /// Touch strips
+    TOUCHSTRIP = 6,
+    /// Touch rings
+    TOUCHRING  = 7,
+    /// ExpressKeys
+    EXPKEYS2   = 8,
+}
+
+/// The common header of the extension packets delivered with [WT::PACKETEXT](crate::WT::PACKETEXT)
+#[repr(C, packed(4))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ExtensionBase {
+    /// Specifies the context that generated the event.
+    pub nContext: *mut HCTX,
+    /// Specifies various status and error conditions. See [Packet::pkStatus](crate::Packet::pkStatus)
+    pub nStatus: TPS,
+    /// The system time at which the event was posted.
+    pub nTime: DWORD,
+    /// A serial number assigned to the packet by the context.
+    pub nSerialNumber: UINT,
+}
+impl Default for ExtensionBase {
+    fn default() -> Self {
+        Self {
+            nContext: std::ptr::null_mut(),
+            nStatus: Default::default(),
+            nTime: Default::default(),
+            nSerialNumber: Default::default(),
+        }
+    }
+}
+
+/// The state of an ExpressKey; see [WTX::EXPKEYS2]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ExpKeysData {
+    /// The index of the tablet the key belongs to
+    pub nTablet: u8,
+    /// The index of the key
+    pub nControl: u8,
+    /// Which side of the tablet the key is on
+    pub nLocation: u8,
+    pub nReserved: u8,
+    /// Non-zero while the key is pressed
+    pub nState: DWORD,
+}
+
+/// The state of a touch strip or touch ring; see [WTX::TOUCHSTRIP] and [WTX::TOUCHRING]
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SliderData {
+    /// The index of the tablet the control belongs to
+    pub nTablet: u8,
+    /// The index of the strip or ring
+    pub nControl: u8,
+    /// The currently active mode of the control
+    pub nMode: u8,
+    pub nReserved: u8,
+    /// The position of the finger on the control. Zero when the finger is lifted.
+    pub nPosition: DWORD,
+}
+
+/// The full extension packet, for a context opened with the [WTX::EXPKEYS2], [WTX::TOUCHSTRIP]
+/// and [WTX::TOUCHRING] extension masks all selected.
+#[repr(C, packed(4))]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PacketExt {
+    pub pkBase: ExtensionBase,
+    pub pkExpKeys: ExpKeysData,
+    pub pkTouchStrip: SliderData,
+    pub pkTouchRing: SliderData,
+}
@@ -8,10 +8,11 @@
 //! using the `raw-dylib` feature instead, and use the functions defined in
 //! [crate::extern_functions]
 //! 
+#![allow(non_snake_case)]
 use windows::Win32::Foundation::HWND;
 use super::c_type_aliases::*;
 use super::information_categories::WTI;
-use super::LOGCONTEXT;
+use super::{LOGCONTEXT, LOGCONTEXTW};
 use libloading::Symbol;
 
 /// Returns information about the interface in an application-supplied buffer. 
@@ -24,12 +25,23 @@ use libloading::Symbol;
 /// The return value is the size of the returned information in bytes. If the information is not
 /// supported, the function returns zero. If a tablet is not physically present, this function
 /// always returns zero.
-pub type WTInfo<'a>  = Symbol<'a, unsafe extern fn (
+pub type WTInfo<'a>  = Symbol<'a, unsafe extern "system" fn (
     wCategory: WTI,
     nIndex: UINT,
     lpOutput: LPVOID
 ) -> UINT>;
 
+/// Unicode version of [WTInfo], load it with `lib.get(c"WTInfoW".to_bytes())`.
+/// Strings are returned as zero terminated UTF-16 and contexts as [LOGCONTEXTW].
+///
+/// Note: `wCategory` is a [UINT] rather than a [WTI] so that multiplexed categories can be
+/// requested (e.g. `WTI::DEVICES as UINT + 1` for the second device).
+pub type WTInfoW<'a>  = Symbol<'a, unsafe extern "system" fn (
+    wCategory: UINT,
+    nIndex: UINT,
+    lpOutput: LPVOID
+) -> UINT>;
+
 /// Opens a connection to the tablet using the provided context.
 /// If successful, the the specified window will receive tablet events via messages (if configured).
 /// The handle that is returned may also be used to poll the context, or to perform other functions.
@@ -40,12 +52,56 @@ pub type WTInfo<'a>  = Symbol<'a, unsafe extern fn (
 /// 
 /// The return value is the opened context handel. It will be a zero value if the context could not
 /// be opened.
-pub type WTOpen<'a>  = Symbol<'a, unsafe extern fn (
+pub type WTOpen<'a>  = Symbol<'a, unsafe extern "system" fn (
     hWnd: HWND,
     lpLogCtx: *mut LOGCONTEXT,
     fEnable: BOOL
 ) -> *mut HCTX>;
 
+/// Unicode version of [WTOpen], load it with `lib.get(c"WTOpenW".to_bytes())`.
+pub type WTOpenW<'a>  = Symbol<'a, unsafe extern "system" fn (
+    hWnd: HWND,
+    lpLogCtx: *mut LOGCONTEXTW,
+    fEnable: BOOL
+) -> *mut HCTX>;
+
+/// Fills the passed structure with the current context attributes for the passed handle.
+/// Load it with `lib.get(c"WTGetA".to_bytes())`.
+/// 
+/// - `hCtx` Identifies the context whose attributes are to be copied.
+/// - `lpLogCtx` Points to a [LOGCONTEXT] data structure to which the context attributes are to be
+///   copied.
+/// 
+/// The function returns a non-zero value if successful, zero otherwise.
+pub type WTGet<'a>  = Symbol<'a, unsafe extern "system" fn (
+    hCtx: *mut HCTX,
+    lpLogCtx: *mut LOGCONTEXT
+) -> BOOL>;
+
+/// Unicode version of [WTGet], load it with `lib.get(c"WTGetW".to_bytes())`.
+pub type WTGetW<'a>  = Symbol<'a, unsafe extern "system" fn (
+    hCtx: *mut HCTX,
+    lpLogCtx: *mut LOGCONTEXTW
+) -> BOOL>;
+
+/// Allows some of the context's attributes to be changed on the fly.
+/// Load it with `lib.get(c"WTSetA".to_bytes())`.
+/// 
+/// - `hCtx` Identifies the context whose attributes are being changed.
+/// - `lpLogCtx` Points to a [LOGCONTEXT] data structure containing the new context attributes.
+/// 
+/// The function returns a non-zero value if successful, zero otherwise.
+pub type WTSet<'a>  = Symbol<'a, unsafe extern "system" fn (
+    hCtx: *mut HCTX,
+    lpLogCtx: *mut LOGCONTEXT
+) -> BOOL>;
+
+/// Unicode version of [WTSet], load it with `lib.get(c"WTSetW".to_bytes())`.
+pub type WTSetW<'a>  = Symbol<'a, unsafe extern "system" fn (
+    hCtx: *mut HCTX,
+    lpLogCtx: *mut LOGCONTEXTW
+) -> BOOL>;
+
 /// Closes and destroys the tablet context object.
 /// After a calling the passed handle is invalid. The owning window (and all manager windows)
 /// will receive a [WT::CTXCLOSE](crate::WT) message when the context has been closed.
@@ -53,10 +109,22 @@ pub type WTOpen<'a>  = Symbol<'a, unsafe extern fn (
 /// - `hCtx` Identifies the context to be closed.
 /// 
 /// The function returns a non-zero value if the context was valid and was destroyed.
-pub type WTClose<'a>  = Symbol<'a, unsafe extern fn (
+pub type WTClose<'a>  = Symbol<'a, unsafe extern "system" fn (
     hCtx: *mut HCTX
 ) -> BOOL>;
 
+/// Enables or disables a context, e.g. when the owning window loses focus. A disabled context
+/// does not generate packets or messages. Load it with `lib.get(c"WTEnable".to_bytes())`.
+/// 
+/// - `hCtx` Identifies the context to be enabled or disabled.
+/// - `fEnable` Specifies enabling if non-zero, disabling if zero.
+/// 
+/// The function returns a non-zero value if the enable or disable request was satisfied.
+pub type WTEnable<'a>  = Symbol<'a, unsafe extern "system" fn (
+    hCtx: *mut HCTX,
+    fEnable: BOOL
+) -> BOOL>;
+
 /// Fills in the passed buffer with the event packet having the specified serial number.
 /// The returned packet and any older packets are removed from the context's internal queue.
 /// 
@@ -66,7 +134,7 @@ pub type WTClose<'a>  = Symbol<'a, unsafe extern fn (
 /// 
 /// The return value is non-zero if the specified packet was found and returned.
 /// It is zero if the specified packet was not found in the queue.
-pub type WTPacket<'a> = Symbol<'a, unsafe extern fn (
+pub type WTPacket<'a> = Symbol<'a, unsafe extern "system" fn (
     hCtx:*mut HCTX,
     wSerial:UINT,
     lpPkts:LPVOID
@@ -81,7 +149,7 @@ pub type WTPacket<'a> = Symbol<'a, unsafe extern fn (
 /// - `lpNew` Points to an unsigned integer to receive the newest packet's serial number.
 /// 
 /// The function returns non-zero if successful, zero otherwise.
-pub type WTQueuePacketsEx<'a> = Symbol<'a, unsafe extern fn (
+pub type WTQueuePacketsEx<'a> = Symbol<'a, unsafe extern "system" fn (
     hCtx: *mut HCTX,
     lpOld:*mut UINT,
     lpNew: *mut UINT
@@ -101,7 +169,7 @@ pub type WTQueuePacketsEx<'a> = Symbol<'a, unsafe extern fn (
 /// 
 /// The return value is the total number of packets found in the queue between wBegin and wEnd.
 /// 
-pub type WTDataGet<'a> = Symbol<'a, unsafe extern fn (
+pub type WTDataGet<'a> = Symbol<'a, unsafe extern "system" fn (
     hCtx: *mut HCTX,
     wBegin: UINT,
     wEnd: UINT,
@@ -129,8 +197,42 @@ pub type WTDataGet<'a> = Symbol<'a, unsafe extern fn (
 /// - Applications may flush packets from the queue by calling this function with a
 ///   NULL lpPktargument.
 /// 
-pub type WTPacketsGet = Symbol<'static, unsafe extern fn (
+pub type WTPacketsGet = Symbol<'static, unsafe extern "system" fn (
     hCtx: *mut HCTX,
     cMaxPkts: INT,
     lpPkts: LPVOID
 ) -> INT>;
+
+/// Opens a tablet manager handle for use by tablet manager and configuration applications.
+/// 
+/// - `hWnd` Identifies the window which owns the manager handle.
+/// - `wMsgBase` Specifies the message base number to use when notifying the manager window.
+/// 
+/// The function returns a manager handle if successful, otherwise it returns null.
+pub type WTMgrOpen<'a> = Symbol<'a, unsafe extern "system" fn (
+    hWnd: HWND,
+    wMsgBase: UINT
+) -> *mut HMGR>;
+
+/// Closes a tablet manager handle.
+/// 
+/// - `hMgr` Identifies the manager handle to be closed.
+/// 
+/// The function returns a non-zero value if successful, zero otherwise.
+pub type WTMgrClose<'a> = Symbol<'a, unsafe extern "system" fn (
+    hMgr: *mut HMGR
+) -> BOOL>;
+
+/// Retrieves a context handle for either the default system context or the default digitizing
+/// context for the specified device. The context is read-only.
+/// 
+/// - `hMgr` Identifies the manager handle.
+/// - `wDevice` Specifies the device for which a default context handle will be returned.
+/// - `fSystem` Non-zero to get the default system context, zero for the default digitizing context.
+/// 
+/// The function returns the context handle if successful, null otherwise.
+pub type WTMgrDefContextEx<'a> = Symbol<'a, unsafe extern "system" fn (
+    hMgr: *mut HMGR,
+    wDevice: UINT,
+    fSystem: BOOL
+) -> *mut HCTX>;
@@ -1,13 +1,21 @@
 //! Functions linked using `#[link(kind="raw-dylib")]`
+#![allow(non_snake_case)]
 
 use crate::{
-    c_type_aliases::{BOOL, HCTX, LPVOID, UINT, INT},
+    c_type_aliases::{BOOL, HCTX, HMGR, LPVOID, UINT, INT},
     information_categories::WTI,
     LOGCONTEXT,
+    LOGCONTEXTW,
 };
+use windows::Win32::Foundation::HWND;
 
-#[link(name = "Wintab32", kind = "raw-dylib")]
-extern "C" {
+// Wintab32.dll exports undecorated names, even for the stdcall functions on 32 bit windows
+#[cfg_attr(not(target_arch = "x86"), link(name = "Wintab32", kind = "raw-dylib"))]
+#[cfg_attr(
+    target_arch = "x86",
+    link(name = "Wintab32", kind = "raw-dylib", import_name_type = "undecorated")
+)]
+extern "system" {
     /// Returns information about the interface in an application-supplied buffer.
     ///
     /// - `wCategory` Identifies the category from which information is being requested
@@ -21,6 +29,14 @@ extern "C" {
     #[link_name = "WTInfoA"]
     pub fn info(wCategory: WTI, nIndex: UINT, lpOutput: LPVOID) -> UINT;
 
+    /// Unicode version of [info]. Strings are returned as zero terminated UTF-16 and contexts as
+    /// [LOGCONTEXTW](crate::LOGCONTEXTW).
+    ///
+    /// Note: `wCategory` is a [UINT] rather than a [WTI] so that multiplexed categories can be
+    /// requested (e.g. `WTI::DEVICES as UINT + 1` for the second device).
+    #[link_name = "WTInfoW"]
+    pub fn info_w(wCategory: UINT, nIndex: UINT, lpOutput: LPVOID) -> UINT;
+
     /// Opens a connection to the tablet using the provided context.
     /// If successful, the the specified window will receive tablet events via messages
     /// (if configured). The handle that is returned may also be used to poll the context,
@@ -34,7 +50,43 @@ extern "C" {
     /// The return value is the opened context handel. It will be a zero value if the context could
     /// not be opened.
     #[link_name = "WTOpenA"]
-    pub fn open(hWnd: isize, lpLogCtx: *mut LOGCONTEXT, fEnable: BOOL) -> *mut HCTX;
+    pub fn open(hWnd: HWND, lpLogCtx: *mut LOGCONTEXT, fEnable: BOOL) -> *mut HCTX;
+
+    /// Unicode version of [open] which takes a [LOGCONTEXTW](crate::LOGCONTEXTW)
+    #[link_name = "WTOpenW"]
+    pub fn open_w(hWnd: HWND, lpLogCtx: *mut LOGCONTEXTW, fEnable: BOOL) -> *mut HCTX;
+
+    /// Fills the passed structure with the current context attributes for the passed handle.
+    ///
+    /// - `hCtx` Identifies the context whose attributes are to be copied.
+    /// - `lpLogCtx` Points to a [LOGCONTEXT](crate::LOGCONTEXT) data structure to which the
+    ///   context attributes are to be copied.
+    ///
+    /// The function returns a non-zero value if successful, zero otherwise.
+    #[link_name = "WTGetA"]
+    #[must_use]
+    pub fn get(hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXT) -> BOOL;
+
+    /// Unicode version of [get] which takes a [LOGCONTEXTW](crate::LOGCONTEXTW)
+    #[link_name = "WTGetW"]
+    #[must_use]
+    pub fn get_w(hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL;
+
+    /// Allows some of the context's attributes to be changed on the fly.
+    ///
+    /// - `hCtx` Identifies the context whose attributes are being changed.
+    /// - `lpLogCtx` Points to a [LOGCONTEXT](crate::LOGCONTEXT) data structure containing the new
+    ///   context attributes.
+    ///
+    /// The function returns a non-zero value if successful, zero otherwise.
+    #[link_name = "WTSetA"]
+    #[must_use]
+    pub fn set(hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXT) -> BOOL;
+
+    /// Unicode version of [set] which takes a [LOGCONTEXTW](crate::LOGCONTEXTW)
+    #[link_name = "WTSetW"]
+    #[must_use]
+    pub fn set_w(hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL;
 
     /// Closes and destroys the tablet context object.
     /// After a calling the passed handle is invalid. The owning window (and all manager windows)
@@ -47,6 +99,17 @@ extern "C" {
     #[must_use]
     pub fn close(hctx: *mut HCTX) -> BOOL;
 
+    /// Enables or disables a context, e.g. when the owning window loses focus. A disabled
+    /// context does not generate packets or messages.
+    ///
+    /// - `hCtx` Identifies the context to be enabled or disabled.
+    /// - `fEnable` Specifies enabling if non-zero, disabling if zero.
+    ///
+    /// The function returns a non-zero value if the enable or disable request was satisfied.
+    #[link_name = "WTEnable"]
+    #[must_use]
+    pub fn enable(hCtx: *mut HCTX, fEnable: BOOL) -> BOOL;
+
     /// Fills in the passed buffer with the event packet having the specified serial number.
     /// The returned packet and any older packets are removed from the context's internal queue.
     ///
@@ -112,7 +175,37 @@ extern "C" {
     /// - `lpNew` Points to an unsigned integer to receive the newest packet's serial number.
     ///
     /// The function returns non-zero if successful, zero otherwise.
-    #[link_name = "WTQueuePacketsGet"]
+    #[link_name = "WTQueuePacketsEx"]
     #[must_use]
     pub fn queue_packets_extent(hCtx: *mut HCTX, lpOld: *mut UINT, lpNew: *mut UINT) -> BOOL;
+
+    /// Opens a tablet manager handle for use by tablet manager and configuration applications.
+    ///
+    /// - `hWnd` Identifies the window which owns the manager handle.
+    /// - `wMsgBase` Specifies the message base number to use when notifying the manager window.
+    ///
+    /// The function returns a manager handle if successful, otherwise it returns null.
+    #[link_name = "WTMgrOpen"]
+    pub fn mgr_open(hWnd: HWND, wMsgBase: UINT) -> *mut HMGR;
+
+    /// Closes a tablet manager handle.
+    ///
+    /// - `hMgr` Identifies the manager handle to be closed.
+    ///
+    /// The function returns a non-zero value if successful, zero otherwise.
+    #[link_name = "WTMgrClose"]
+    #[must_use]
+    pub fn mgr_close(hMgr: *mut HMGR) -> BOOL;
+
+    /// Retrieves a context handle for either the default system context or the default digitizing
+    /// context for the specified device. The context is read-only.
+    ///
+    /// - `hMgr` Identifies the manager handle.
+    /// - `wDevice` Specifies the device for which a default context handle will be returned.
+    /// - `fSystem` Non-zero to get the default system context, zero for the default digitizing
+    ///   context.
+    ///
+    /// The function returns the context handle if successful, null otherwise.
+    #[link_name = "WTMgrDefContextEx"]
+    pub fn mgr_def_context_ex(hMgr: *mut HMGR, wDevice: UINT, fSystem: BOOL) -> *mut HCTX;
 }
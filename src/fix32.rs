@@ -0,0 +1,490 @@
+use std::{
+    cmp::Ordering,
+    ffi::c_uint,
+    ops::{Add, Div, Mul, Sub},
+};
+
+use crate::DWORD;
+
+/// A 32-bit fixed-point arithmetic type, with the radix point between the two words.
+/// Thus, the type contains 16 bits to the left of the radix point and 16 bits to the right of it.
+///
+/// In `wintab.h` this is `typedef DWORD FIX32;` so the value is unsigned; the representable range
+/// is `0.0 ..= 65535.99998474121`. It shows up in [AXIS::axResolution](crate::AXIS::axResolution),
+/// [LOGCONTEXT::lcSensXYZ](crate::LOGCONTEXT::lcSensXYZ) and
+/// [LOGCONTEXT::lcSysSensXY](crate::LOGCONTEXT::lcSysSensXY).
+///
+/// Arithmetic is done exactly on the raw bits. The operators (`+`, `-`, `*`, `/`) panic on
+/// overflow just like the primitive integer types do in debug builds; use the `checked_*` or
+/// `saturating_*` methods if you need to handle that case. Multiplication and division round the
+/// result to the nearest representable value (ties away from zero).
+#[repr(C)]
+#[derive(Default, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct FIX32(DWORD);
+
+/// How to round when a value can not be represented exactly by a [FIX32].
+/// See [FIX32::from_f64_rounded]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Rounding {
+    /// Round to the nearest representable value, ties away from zero.
+    /// This matches the behaviour of the `From<f64>` implementation.
+    #[default]
+    Nearest,
+    /// Round towards negative infinity
+    Floor,
+    /// Round towards positive infinity
+    Ceil,
+    /// Discard the bits that don't fit. The same as [Rounding::Floor] for positive values, but
+    /// tiny negative values round up to zero instead of failing with [FIX32Error::Negative].
+    TowardZero,
+}
+
+/// The reason a conversion into [FIX32] failed.
+/// See [FIX32::from_f64_rounded]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FIX32Error {
+    /// The value was negative (after rounding). [FIX32] can only hold unsigned values.
+    Negative,
+    /// The value was larger than [FIX32::MAX] (after rounding).
+    Overflow,
+    /// The value was `NaN`
+    NaN,
+}
+
+impl std::fmt::Display for FIX32Error {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FIX32Error::Negative => write!(f, "value is negative and can not be stored in a FIX32"),
+            FIX32Error::Overflow => write!(f, "value is too large to be stored in a FIX32"),
+            FIX32Error::NaN      => write!(f, "NaN can not be stored in a FIX32"),
+        }
+    }
+}
+
+impl std::error::Error for FIX32Error {}
+
+impl FIX32 {
+    /// The number of bits to the right of the radix point
+    pub const FRAC_BITS: u32 = 16;
+    /// The raw value which represents `1.0`
+    const ONE_RAW: u64 = 1 << Self::FRAC_BITS;
+
+    /// `0.0`
+    pub const ZERO: FIX32 = FIX32(0);
+    /// `1.0`
+    pub const ONE: FIX32 = FIX32(1 << 16);
+    /// The smallest representable value, `0.0`
+    pub const MIN: FIX32 = FIX32(0);
+    /// The largest representable value, `65535 + 65535/65536`
+    pub const MAX: FIX32 = FIX32(DWORD::MAX);
+    /// The smallest non-zero value, `1/65536`
+    pub const EPSILON: FIX32 = FIX32(1);
+
+    /// Create a [FIX32] from a [DWORD]
+    pub const fn new(value: DWORD) -> Self {
+        FIX32(value)
+    }
+
+    /// Create a [FIX32] from the integer part (high word) and the fractional part (low word).
+    /// The fractional part is in units of `1/65536`.
+    ///
+    /// `FIX32::from_parts(1, 0x8000)` is `1.5`
+    pub const fn from_parts(int: u16, frac: u16) -> Self {
+        FIX32(((int as DWORD) << 16) | frac as DWORD)
+    }
+
+    /// Returns the raw bits as they are stored by wintab
+    pub const fn to_bits(self) -> DWORD {
+        self.0
+    }
+
+    /// Extracts the integer part of the fixed-point number. (high word)
+    pub const fn int_part(self) -> c_uint {
+        self.0 >> 16
+    }
+
+    /// Extracts the fractional part of the fixed-point number. (low word)
+    pub const fn frac_part(self) -> c_uint {
+        self.0 & 0xFFFF
+    }
+
+    /// The exact value of this number as a fraction `(numerator, denominator)` in lowest terms.
+    /// The denominator is always a power of two no larger than `65536`.
+    ///
+    /// `FIX32::from_parts(2, 0x4000).to_ratio()` is `(9, 4)`
+    pub const fn to_ratio(self) -> (u32, u32) {
+        if self.0 == 0 {
+            return (0, 1);
+        }
+        let shift = {
+            let trailing = self.0.trailing_zeros();
+            if trailing > Self::FRAC_BITS { Self::FRAC_BITS } else { trailing }
+        };
+        (self.0 >> shift, 1 << (Self::FRAC_BITS - shift))
+    }
+
+    /// Converts a float into a [FIX32] using the specified rounding mode.
+    ///
+    /// Returns an error if the value is `NaN` or falls outside the range `FIX32::MIN..=FIX32::MAX`
+    /// once rounded. Note that a small negative value like `-0.000001` rounds to zero with
+    /// [Rounding::Nearest] and is therefore accepted.
+    pub fn from_f64_rounded(value: f64, rounding: Rounding) -> Result<Self, FIX32Error> {
+        if value.is_nan() {
+            return Err(FIX32Error::NaN);
+        }
+        let scaled = value * Self::ONE_RAW as f64;
+        let rounded = match rounding {
+            Rounding::Nearest => scaled.round(),
+            Rounding::Floor => scaled.floor(),
+            Rounding::TowardZero => scaled.trunc(),
+            Rounding::Ceil => scaled.ceil(),
+        };
+        if rounded < 0.0 {
+            Err(FIX32Error::Negative)
+        } else if rounded > DWORD::MAX as f64 {
+            Err(FIX32Error::Overflow)
+        } else {
+            Ok(FIX32(rounded as DWORD))
+        }
+    }
+
+    /// Converts a float into a [FIX32] rounding to nearest, clamping out of range values to
+    /// [FIX32::MIN] or [FIX32::MAX]. `NaN` becomes [FIX32::ZERO].
+    pub fn saturating_from_f64(value: f64) -> Self {
+        match Self::from_f64_rounded(value, Rounding::Nearest) {
+            Ok(result) => result,
+            Err(FIX32Error::Negative | FIX32Error::NaN) => Self::MIN,
+            Err(FIX32Error::Overflow) => Self::MAX,
+        }
+    }
+
+    /// Converts to a float. This is always exact since an [f64] has more than 32 bits of mantissa.
+    pub fn to_f64(self) -> f64 {
+        self.0 as f64 / Self::ONE_RAW as f64
+    }
+
+    /// Checked addition. Returns `None` if the result would overflow.
+    pub const fn checked_add(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_add(rhs.0) {
+            Some(result) => Some(FIX32(result)),
+            None => None,
+        }
+    }
+
+    /// Checked subtraction. Returns `None` if the result would be negative.
+    pub const fn checked_sub(self, rhs: Self) -> Option<Self> {
+        match self.0.checked_sub(rhs.0) {
+            Some(result) => Some(FIX32(result)),
+            None => None,
+        }
+    }
+
+    /// Checked multiplication, rounded to nearest. Returns `None` if the result would overflow.
+    pub const fn checked_mul(self, rhs: Self) -> Option<Self> {
+        let product = self.0 as u64 * rhs.0 as u64;
+        let result = (product + (Self::ONE_RAW >> 1)) >> Self::FRAC_BITS;
+        if result > DWORD::MAX as u64 {
+            None
+        } else {
+            Some(FIX32(result as DWORD))
+        }
+    }
+
+    /// Checked division, rounded to nearest.
+    /// Returns `None` if `rhs` is zero or if the result would overflow.
+    pub const fn checked_div(self, rhs: Self) -> Option<Self> {
+        if rhs.0 == 0 {
+            return None;
+        }
+        let numerator = (self.0 as u64) << Self::FRAC_BITS;
+        let divisor = rhs.0 as u64;
+        let result = (numerator + divisor / 2) / divisor;
+        if result > DWORD::MAX as u64 {
+            None
+        } else {
+            Some(FIX32(result as DWORD))
+        }
+    }
+
+    /// Saturating addition. Clamps to [FIX32::MAX] on overflow.
+    pub const fn saturating_add(self, rhs: Self) -> Self {
+        FIX32(self.0.saturating_add(rhs.0))
+    }
+
+    /// Saturating subtraction. Clamps to [FIX32::MIN] (zero) on underflow.
+    pub const fn saturating_sub(self, rhs: Self) -> Self {
+        FIX32(self.0.saturating_sub(rhs.0))
+    }
+
+    /// Saturating multiplication. Clamps to [FIX32::MAX] on overflow.
+    pub const fn saturating_mul(self, rhs: Self) -> Self {
+        match self.checked_mul(rhs) {
+            Some(result) => result,
+            None => Self::MAX,
+        }
+    }
+
+    /// Saturating division. Clamps to [FIX32::MAX] on overflow.
+    ///
+    /// # Panics
+    /// Panics if `rhs` is zero.
+    pub const fn saturating_div(self, rhs: Self) -> Self {
+        if rhs.0 == 0 {
+            panic!("attempt to divide by zero");
+        }
+        match self.checked_div(rhs) {
+            Some(result) => result,
+            None => Self::MAX,
+        }
+    }
+
+    /// Multiplies an integer by this factor, rounding to the nearest integer.
+    /// The result is computed exactly with 128-bit intermediates.
+    ///
+    /// Useful for applying sensitivity factors (e.g. [LOGCONTEXT::lcSensXYZ](crate::LOGCONTEXT))
+    /// to counts without going through floating point.
+    ///
+    /// # Panics
+    /// Panics if the result does not fit in an [i64]; see [FIX32::checked_mul_int].
+    pub const fn mul_int(self, value: i64) -> i64 {
+        match self.checked_mul_int(value) {
+            Some(result) => result,
+            None => panic!("attempt to multiply with overflow"),
+        }
+    }
+
+    /// Like [FIX32::mul_int], returning `None` if the result does not fit in an [i64]
+    pub const fn checked_mul_int(self, value: i64) -> Option<i64> {
+        let rounded = self.mul_int_wide(value);
+        if rounded > i64::MAX as i128 || rounded < i64::MIN as i128 {
+            None
+        } else {
+            Some(rounded as i64)
+        }
+    }
+
+    /// Like [FIX32::mul_int], clamping to [i64::MIN] or [i64::MAX] on overflow
+    pub const fn saturating_mul_int(self, value: i64) -> i64 {
+        let rounded = self.mul_int_wide(value);
+        if rounded > i64::MAX as i128 {
+            i64::MAX
+        } else if rounded < i64::MIN as i128 {
+            i64::MIN
+        } else {
+            rounded as i64
+        }
+    }
+
+    const fn mul_int_wide(self, value: i64) -> i128 {
+        let product = value as i128 * self.0 as i128;
+        let half = (Self::ONE_RAW >> 1) as i128;
+        if product < 0 {
+            -((-product + half) >> Self::FRAC_BITS)
+        } else {
+            (product + half) >> Self::FRAC_BITS
+        }
+    }
+}
+
+impl std::fmt::Debug for FIX32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{:}fix32", self)
+    }
+}
+impl std::fmt::Display for FIX32 {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        write!(f, "{:}", self.to_f64())
+    }
+}
+
+/// Rounds to nearest. Values outside the representable range (including negative values) are
+/// clamped, see [FIX32::saturating_from_f64]. Use [FIX32::from_f64_rounded] to detect this.
+impl From<f64> for FIX32 {
+    fn from(value: f64) -> Self {
+        FIX32::saturating_from_f64(value)
+    }
+}
+
+impl From<FIX32> for f64 {
+    fn from(value: FIX32) -> Self {
+        value.to_f64()
+    }
+}
+
+impl From<u16> for FIX32 {
+    fn from(value: u16) -> Self {
+        FIX32::from_parts(value, 0)
+    }
+}
+
+impl PartialEq<f64> for FIX32 {
+    fn eq(&self, other: &f64) -> bool {
+        self.to_f64() == *other
+    }
+}
+
+impl PartialOrd<f64> for FIX32 {
+    fn partial_cmp(&self, other: &f64) -> Option<Ordering> {
+        self.to_f64().partial_cmp(other)
+    }
+}
+
+impl Add for FIX32 {
+    type Output = FIX32;
+    fn add(self, rhs: Self) -> Self::Output {
+        self.checked_add(rhs).expect("attempt to add with overflow")
+    }
+}
+
+impl Sub for FIX32 {
+    type Output = FIX32;
+    fn sub(self, rhs: Self) -> Self::Output {
+        self.checked_sub(rhs).expect("attempt to subtract with overflow")
+    }
+}
+
+impl Mul for FIX32 {
+    type Output = FIX32;
+    fn mul(self, rhs: Self) -> Self::Output {
+        self.checked_mul(rhs).expect("attempt to multiply with overflow")
+    }
+}
+
+impl Div for FIX32 {
+    type Output = FIX32;
+    fn div(self, rhs: Self) -> Self::Output {
+        if rhs.0 == 0 {
+            panic!("attempt to divide by zero");
+        }
+        self.checked_div(rhs).expect("attempt to divide with overflow")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parts_and_conversion() {
+        let value = FIX32::from_parts(1, 0x8000);
+        assert_eq!(value.int_part(), 1);
+        assert_eq!(value.frac_part(), 0x8000);
+        assert_eq!(f64::from(value), 1.5);
+        assert_eq!(FIX32::from(1.5), value);
+        assert_eq!(FIX32::from(2u16), FIX32::new(0x0002_0000));
+        // TU::CIRCLE resolution in radians, as given in the spec
+        let two_pi = FIX32::from(std::f64::consts::TAU);
+        assert_eq!(two_pi.to_bits(), 411775);
+        assert!((f64::from(two_pi) - std::f64::consts::TAU).abs() < 1.0 / 65536.0);
+    }
+
+    #[test]
+    fn test_out_of_range_conversion() {
+        assert_eq!(FIX32::from(-1.0), FIX32::ZERO);
+        assert_eq!(FIX32::from(70000.0), FIX32::MAX);
+        assert_eq!(FIX32::from(f64::NAN), FIX32::ZERO);
+        assert_eq!(FIX32::from_f64_rounded(-1.0, Rounding::Nearest), Err(FIX32Error::Negative));
+        assert_eq!(FIX32::from_f64_rounded(65536.0, Rounding::Nearest), Err(FIX32Error::Overflow));
+        assert_eq!(FIX32::from_f64_rounded(f64::NAN, Rounding::Floor), Err(FIX32Error::NaN));
+        // tiny negative values round to zero
+        assert_eq!(FIX32::from_f64_rounded(-1e-9, Rounding::Nearest), Ok(FIX32::ZERO));
+        assert_eq!(FIX32::from_f64_rounded(-1e-9, Rounding::Ceil), Ok(FIX32::ZERO));
+        assert_eq!(FIX32::from_f64_rounded(-1e-9, Rounding::Floor), Err(FIX32Error::Negative));
+        assert_eq!(f64::from(FIX32::MAX), 65535.0 + 65535.0 / 65536.0);
+        assert_eq!(FIX32::from_f64_rounded(f64::from(FIX32::MAX), Rounding::Nearest), Ok(FIX32::MAX));
+    }
+
+    #[test]
+    fn test_rounding_modes() {
+        let third = 1.0 / 3.0; // 21845.33.. / 65536
+        assert_eq!(FIX32::from_f64_rounded(third, Rounding::Nearest).unwrap().to_bits(), 21845);
+        assert_eq!(FIX32::from_f64_rounded(third, Rounding::Floor).unwrap().to_bits(), 21845);
+        assert_eq!(FIX32::from_f64_rounded(third, Rounding::TowardZero).unwrap().to_bits(), 21845);
+        assert_eq!(FIX32::from_f64_rounded(third, Rounding::Ceil).unwrap().to_bits(), 21846);
+        let two_thirds = 2.0 / 3.0; // 43690.66.. / 65536
+        assert_eq!(FIX32::from_f64_rounded(two_thirds, Rounding::Nearest).unwrap().to_bits(), 43691);
+        assert_eq!(FIX32::from_f64_rounded(two_thirds, Rounding::Floor).unwrap().to_bits(), 43690);
+        assert_eq!(FIX32::from_f64_rounded(-1e-9, Rounding::TowardZero), Ok(FIX32::ZERO));
+        assert_eq!(FIX32::from_f64_rounded(-1.0, Rounding::TowardZero), Err(FIX32Error::Negative));
+    }
+
+    #[test]
+    fn test_arithmetic() {
+        let a = FIX32::from(2.5);
+        let b = FIX32::from(0.25);
+        assert_eq!(a + b, FIX32::from(2.75));
+        assert_eq!(a - b, FIX32::from(2.25));
+        assert_eq!(a * b, FIX32::from(0.625));
+        assert_eq!(a / b, FIX32::from(10.0));
+        assert_eq!(FIX32::ONE / FIX32::from(3u16), FIX32::new(21845));
+        assert_eq!(FIX32::from(2u16) / FIX32::from(3u16), FIX32::new(43691));
+        assert_eq!(FIX32::EPSILON * FIX32::from(0.5), FIX32::EPSILON);
+    }
+
+    #[test]
+    fn test_checked_and_saturating() {
+        assert_eq!(FIX32::MAX.checked_add(FIX32::EPSILON), None);
+        assert_eq!(FIX32::ZERO.checked_sub(FIX32::EPSILON), None);
+        assert_eq!(FIX32::from(300u16).checked_mul(FIX32::from(300u16)), None);
+        assert_eq!(FIX32::ONE.checked_div(FIX32::ZERO), None);
+        assert_eq!(FIX32::MAX.checked_div(FIX32::from(0.5)), None);
+        assert_eq!(FIX32::MAX.saturating_add(FIX32::ONE), FIX32::MAX);
+        assert_eq!(FIX32::ONE.saturating_sub(FIX32::MAX), FIX32::ZERO);
+        assert_eq!(FIX32::from(300u16).saturating_mul(FIX32::from(300u16)), FIX32::MAX);
+        assert_eq!(FIX32::MAX.saturating_div(FIX32::from(0.5)), FIX32::MAX);
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to subtract with overflow")]
+    fn test_sub_overflow_panics() {
+        let _ = FIX32::ZERO - FIX32::ONE;
+    }
+
+    #[test]
+    #[should_panic(expected = "attempt to divide by zero")]
+    fn test_div_by_zero_panics() {
+        let _ = FIX32::ONE / FIX32::ZERO;
+    }
+
+    #[test]
+    fn test_ordering_and_ratio() {
+        assert!(FIX32::from(0.5) < FIX32::ONE);
+        assert!(FIX32::MAX > FIX32::from(65535u16));
+        assert!(FIX32::from(0.5) < 0.75);
+        assert_eq!(FIX32::ZERO.to_ratio(), (0, 1));
+        assert_eq!(FIX32::from(3u16).to_ratio(), (3, 1));
+        assert_eq!(FIX32::from_parts(2, 0x4000).to_ratio(), (9, 4));
+        assert_eq!(FIX32::EPSILON.to_ratio(), (1, 65536));
+    }
+
+    #[test]
+    fn test_mul_int() {
+        let sensitivity = FIX32::from(1.5);
+        assert_eq!(sensitivity.mul_int(10), 15);
+        assert_eq!(sensitivity.mul_int(-10), -15);
+        assert_eq!(FIX32::from(0.5).mul_int(3), 2);
+        assert_eq!(FIX32::from(0.5).mul_int(-3), -2);
+        assert_eq!(FIX32::MAX.mul_int(i32::MAX as i64), 140737488257024);
+    }
+
+    #[test]
+    fn test_mul_int_overflow() {
+        // 0x10000 raw is exactly one, so the boundary is exact
+        assert_eq!(FIX32::from(1.0).checked_mul_int(i64::MAX), Some(i64::MAX));
+        assert_eq!(FIX32::from(1.0).checked_mul_int(i64::MIN), Some(i64::MIN));
+        assert_eq!(FIX32::from(2.0).checked_mul_int(i64::MAX / 2), Some(i64::MAX - 1));
+        assert_eq!(FIX32::from(2.0).checked_mul_int(i64::MAX / 2 + 1), None);
+        assert_eq!(FIX32::from(2.0).checked_mul_int(i64::MIN / 2), Some(i64::MIN));
+        assert_eq!(FIX32::from(2.0).checked_mul_int(i64::MIN / 2 - 1), None);
+        assert_eq!(FIX32::MAX.checked_mul_int(i64::MAX), None);
+        assert_eq!(FIX32::MAX.saturating_mul_int(i64::MAX), i64::MAX);
+        assert_eq!(FIX32::MAX.saturating_mul_int(i64::MIN), i64::MIN);
+        assert_eq!(FIX32::from(1.5).saturating_mul_int(10), 15);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_mul_int_panics_on_overflow() {
+        FIX32::MAX.mul_int(i64::MAX);
+    }
+}
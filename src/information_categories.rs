@@ -324,12 +324,14 @@ pub enum CSR {
     MINBUTTONS   = 18,
     /// `[CRC]` Returns flags indicating cursor capabilities, as defined by the values and their meanings, below:
     CAPABILITIES = 19,
-    /// TODO: UNDOCUMENTED?
+    /// `UINT` Wacom specific; returns the tool type id of the cursor.
+    /// The general kind of tool is found by masking with `0x0F06`; see [PenTool](crate::PenTool).
     TYPE     = 20,
 }
 
 bitflags! {
     /// See [CSR::CAPABILITIES] cursor capabilities
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
     pub struct CRC:u32 {
         ///  Indicates this cursor type describes one of several modes of a single physical cursor.
         /// Consecutive cursor type categories describe the modes;
@@ -0,0 +1,120 @@
+//! Compile time checks that the `#[repr(C)]` structs in this crate have the same size and field
+//! offsets as the structs in `wintab.h` on 32 and 64 bit windows.
+//!
+//! Since the [c_type_aliases](crate::DWORD) are pinned to their windows widths, the layouts only
+//! depend on the pointer width and hold on non-windows targets too. These are evaluated by
+//! `cargo check`, so both windows targets can be verified from any host with
+//!
+//! ```bash
+//! cargo check --target i686-pc-windows-msvc
+//! cargo check --target x86_64-pc-windows-msvc
+//! ```
+//!
+//! Only [Packet] and [ExtensionBase] contain a pointer sized field (the context handle), so only
+//! they differ between the two targets. Packets are written by the driver with no padding between
+//! fields, hence the `packed(4)` representation of those structs.
+use std::mem::{offset_of, size_of};
+
+use crate::{
+    extension::{ExpKeysData, ExtensionBase, PacketExt, SliderData},
+    packet::{ButtonChange, Orientation, Packet, Rotation},
+    AXIS, LOGCONTEXT, LOGCONTEXTW,
+};
+
+/// Asserts the size of a struct and the offsets of the listed fields at compile time
+macro_rules! assert_layout {
+    ($type:ty, size = $size:expr, { $($field:ident : $offset:expr),* $(,)? }) => {
+        const _: () = {
+            assert!(size_of::<$type>() == $size, concat!("wrong size for ", stringify!($type)));
+            $(
+                assert!(
+                    offset_of!($type, $field) == $offset,
+                    concat!("wrong offset for ", stringify!($type), "::", stringify!($field))
+                );
+            )*
+        };
+    };
+}
+
+/// Layouts which are the same on every target
+mod common {
+    use super::*;
+
+    assert_layout!(AXIS, size = 16, {
+        axMin: 0, axMax: 4, axUnits: 8, axResolution: 12,
+    });
+
+    assert_layout!(Orientation, size = 12, {
+        orAzimuth: 0, orAltitude: 4, orTwist: 8,
+    });
+
+    assert_layout!(Rotation, size = 12, {
+        roPitch: 0, roRoll: 4, roYaw: 8,
+    });
+
+    assert_layout!(ButtonChange, size = 4, {
+        button_number: 0, change_type: 2,
+    });
+
+    assert_layout!(LOGCONTEXT, size = 172, {
+        lcName: 0, lcOptions: 40, lcStatus: 44, lcLocks: 48, lcMsgBase: 52, lcDevice: 56,
+        lcPktRate: 60, lcPktData: 64, lcPktMode: 68, lcMoveMask: 72, lcBtnDnMask: 76,
+        lcBtnUpMask: 80, lcInOrgXYZ: 84, lcInExtXYZ: 96, lcOutOrgXYZ: 108, lcOutExtXYZ: 120,
+        lcSensXYZ: 132, lcSysMode: 144, lcSysOrgXY: 148, lcSysExtXY: 156, lcSysSensXY: 164,
+    });
+
+    assert_layout!(LOGCONTEXTW, size = 212, {
+        lcName: 0, lcOptions: 80, lcStatus: 84, lcLocks: 88, lcMsgBase: 92, lcDevice: 96,
+        lcPktRate: 100, lcPktData: 104, lcPktMode: 108, lcMoveMask: 112, lcBtnDnMask: 116,
+        lcBtnUpMask: 120, lcInOrgXYZ: 124, lcInExtXYZ: 136, lcOutOrgXYZ: 148, lcOutExtXYZ: 160,
+        lcSensXYZ: 172, lcSysMode: 184, lcSysOrgXY: 188, lcSysExtXY: 196, lcSysSensXY: 204,
+    });
+
+    assert_layout!(ExpKeysData, size = 8, {
+        nTablet: 0, nControl: 1, nLocation: 2, nReserved: 3, nState: 4,
+    });
+
+    assert_layout!(SliderData, size = 8, {
+        nTablet: 0, nControl: 1, nMode: 2, nReserved: 3, nPosition: 4,
+    });
+}
+
+/// 32 bit targets, `HCTX` is 4 bytes
+#[cfg(target_pointer_width = "32")]
+mod pointer_32 {
+    use super::*;
+
+    assert_layout!(Packet, size = 72, {
+        pkContext: 0, pkStatus: 4, pkTime: 8, pkChanged: 12, pkSerialNumber: 16, pkCursor: 20,
+        pkButtons: 24, pkXYZ: 28, pkNormalPressure: 40, pkTangentPressure: 44,
+        pkOrientation: 48, pkRotation: 60,
+    });
+
+    assert_layout!(ExtensionBase, size = 16, {
+        nContext: 0, nStatus: 4, nTime: 8, nSerialNumber: 12,
+    });
+
+    assert_layout!(PacketExt, size = 40, {
+        pkBase: 0, pkExpKeys: 16, pkTouchStrip: 24, pkTouchRing: 32,
+    });
+}
+
+/// 64 bit targets, `HCTX` is 8 bytes
+#[cfg(target_pointer_width = "64")]
+mod pointer_64 {
+    use super::*;
+
+    assert_layout!(Packet, size = 76, {
+        pkContext: 0, pkStatus: 8, pkTime: 12, pkChanged: 16, pkSerialNumber: 20, pkCursor: 24,
+        pkButtons: 28, pkXYZ: 32, pkNormalPressure: 44, pkTangentPressure: 48,
+        pkOrientation: 52, pkRotation: 64,
+    });
+
+    assert_layout!(ExtensionBase, size = 20, {
+        nContext: 0, nStatus: 8, nTime: 12, nSerialNumber: 16,
+    });
+
+    assert_layout!(PacketExt, size = 44, {
+        pkBase: 0, pkExpKeys: 20, pkTouchStrip: 28, pkTouchRing: 36,
+    });
+}
@@ -10,8 +10,9 @@
 //! However the example code provided by Wacom is MIT licensed
 //! [here](https://github.com/Wacom-Developer/wacom-device-kit-windows/blob/881d8e8303e858e53584e70235fe32e3c9ef06f2/Wintab%20Pressure%20Test/SampleCode/MIT-license.txt)
 //! 
-//! For example usage please read / run the example `cargo run --example wininit_libloading`
+//! For example usage please read / run the example `cargo run --example winit_libloading --features libloading,raw-window-handle`
 mod c_type_aliases;
+mod fix32;
 mod log_context;
 mod axis;
 mod wtpkt;
@@ -21,12 +22,74 @@ mod information_categories;
 mod c_string_types;
 mod bitmask;
 mod window_message;
+mod api;
+mod extension;
+mod layout_assertions;
+mod context;
+mod packet_reader;
+mod serial_tracker;
+mod time_tracker;
+mod pen_event;
+mod stroke;
+mod buttons;
+mod pen_tool;
+mod tilt;
+mod pressure;
+mod pump;
+#[cfg(feature="async")]
+mod packet_stream;
+#[cfg(feature="raw-window-handle")]
+mod window_handle;
+mod message;
+#[cfg(all(windows, feature="subclass"))]
+mod subclass;
+mod message_window;
+#[cfg(feature="egui")]
+mod egui_canvas;
+pub mod octotablet;
+mod client_mapping;
+mod monitor_layout;
+mod proportions;
+#[cfg(test)]
+mod test_support;
 
 pub use c_type_aliases::*;
-pub use c_string_types::CString40;
+pub use fix32::{FIX32, FIX32Error, Rounding};
+pub use c_string_types::{CString40, CString40W, EncodeReport};
 pub use bitmask::Bitmask;
 pub use coordinate::{XY, XYZ};
-pub use axis::AXIS;
+pub use axis::{AXIS, TU};
+pub use api::{InfoValue, WintabApi};
+pub use context::{Context, ContextError};
+pub use packet_reader::PacketReader;
+pub use serial_tracker::{SerialEvent, SerialTracker, Tracked, TrackedPackets};
+pub use time_tracker::{TimeMode, TimeTracker};
+pub use pen_event::{PenEvent, PenEventStream, PenEvents, PenState};
+pub use stroke::{Stroke, StrokeBuilder, StrokeEnd, StrokeKind, StrokeSample};
+pub use buttons::{ButtonEvent, ButtonTracker, CursorButtons};
+pub use pen_tool::{CursorInfo, PenTool, ToolTable};
+pub use tilt::{OrientationScale, PenAngles, Tilt};
+pub use pressure::{CurveShape, PressureCurve};
+pub use pump::{PacketPump, PumpNotifier, PumpSender};
+#[cfg(feature="async")]
+pub use packet_stream::PacketStream;
+#[cfg(feature="raw-window-handle")]
+pub use window_handle::{window_hwnd, WindowHandleError};
+pub use message::{MessageRouter, WintabMessage};
+#[cfg(all(windows, feature="subclass"))]
+pub use subclass::WindowSubclass;
+pub use message_window::MessageForwarder;
+#[cfg(all(windows, feature="message-window"))]
+pub use message_window::{MessageWindow, MessageWindowError};
+#[cfg(feature="egui")]
+pub use egui_canvas::{CanvasStroke, EguiPenMapper, PenSample, PressureCanvas};
+pub use client_mapping::{AxisScaling, ClientMapper, ContextScaling};
+pub use monitor_layout::{Monitor, MonitorLayout, OutputArea, OutputPreset, ScreenRect};
+pub use proportions::{ProportionMode, ProportionalMapping, TabletArea};
+#[cfg(feature="raw-dylib")]
+pub use api::RawDylibApi;
+#[cfg(feature="libloading")]
+pub use api::LibloadingApi;
 
 #[cfg(feature="libloading")]
 mod extern_function_types;
@@ -40,6 +103,7 @@ pub use extern_functions::*;
 
 pub use log_context::{
     LOGCONTEXT,
+    LOGCONTEXTW,
     CXO,
     CXL,
     CXS,
@@ -48,7 +112,17 @@ pub use wtpkt::WTPKT;
 pub use packet::{
     Packet,
     ButtonChange,
-    ButtonChangeType
+    ButtonChangeType,
+    Orientation,
+    Rotation,
+    TPS,
+};
+pub use extension::{
+    WTX,
+    ExtensionBase,
+    ExpKeysData,
+    SliderData,
+    PacketExt,
 };
 pub use information_categories::{
     WTI,
@@ -1,6 +1,6 @@
 #![allow(non_snake_case)]
 use bitflags::bitflags;
-use crate::{Bitmask, CString40};
+use crate::{Bitmask, CString40, CString40W, FIX32};
 use super::c_type_aliases::*;
 use super::wtpkt::WTPKT;
 use super::coordinate::{
@@ -146,6 +146,119 @@ impl Default for LOGCONTEXT{
 }
 
 
+/// The Unicode version of [LOGCONTEXT], used with `WTInfoW`, `WTOpenW`, `WTGetW` and `WTSetW`.
+/// It is identical except that [LOGCONTEXTW::lcName] is a UTF-16 [CString40W].
+/// See [LOGCONTEXT] for the meaning of each field.
+#[repr(C)]
+#[derive(Debug, Copy, Clone)]
+pub struct LOGCONTEXTW {
+    /// Contains a zero-terminated UTF-16 context name string.
+    pub lcName: CString40W,
+    /// See [LOGCONTEXT::lcOptions]
+    pub lcOptions: CXO,
+    /// See [LOGCONTEXT::lcStatus]
+    pub lcStatus: CXS,
+    /// See [LOGCONTEXT::lcLocks]
+    pub lcLocks: CXL,
+    /// See [LOGCONTEXT::lcMsgBase]
+    pub lcMsgBase: UINT,
+    /// See [LOGCONTEXT::lcDevice]
+    pub lcDevice: UINT,
+    /// See [LOGCONTEXT::lcPktRate]
+    pub lcPktRate: UINT,
+    /// See [LOGCONTEXT::lcPktData]
+    pub lcPktData: WTPKT,
+    /// See [LOGCONTEXT::lcPktMode]
+    pub lcPktMode: WTPKT,
+    /// See [LOGCONTEXT::lcMoveMask]
+    pub lcMoveMask: WTPKT,
+    /// See [LOGCONTEXT::lcBtnDnMask]
+    pub lcBtnDnMask: Bitmask<DWORD>,
+    /// See [LOGCONTEXT::lcBtnUpMask]
+    pub lcBtnUpMask: Bitmask<DWORD>,
+    /// See [LOGCONTEXT::lcInOrgXYZ]
+    pub lcInOrgXYZ: XYZ<LONG>,
+    /// See [LOGCONTEXT::lcInExtXYZ]
+    pub lcInExtXYZ: XYZ<LONG>,
+    /// See [LOGCONTEXT::lcOutOrgXYZ]
+    pub lcOutOrgXYZ: XYZ<LONG>,
+    /// See [LOGCONTEXT::lcOutExtXYZ]
+    pub lcOutExtXYZ: XYZ<LONG>,
+    /// See [LOGCONTEXT::lcSensXYZ]
+    pub lcSensXYZ: XYZ<FIX32>,
+    /// See [LOGCONTEXT::lcSysMode]
+    pub lcSysMode: BOOL,
+    /// See [LOGCONTEXT::lcSysOrgXY]
+    pub lcSysOrgXY: XY<INT>,
+    /// See [LOGCONTEXT::lcSysExtXY]
+    pub lcSysExtXY: XY<INT>,
+    /// See [LOGCONTEXT::lcSysSensXY]
+    pub lcSysSensXY: XY<FIX32>,
+}
+impl Default for LOGCONTEXTW{
+    fn default() -> Self {
+        LOGCONTEXT::default().into()
+    }
+}
+
+/// Converts the name from Windows-1252, everything else is copied as is.
+impl From<LOGCONTEXT> for LOGCONTEXTW {
+    fn from(value: LOGCONTEXT) -> Self {
+        Self{
+            lcName: CString40W::from(value.lcName.to_string_windows_1252().as_str()),
+            lcOptions: value.lcOptions,
+            lcStatus: value.lcStatus,
+            lcLocks: value.lcLocks,
+            lcMsgBase: value.lcMsgBase,
+            lcDevice: value.lcDevice,
+            lcPktRate: value.lcPktRate,
+            lcPktData: value.lcPktData,
+            lcPktMode: value.lcPktMode,
+            lcMoveMask: value.lcMoveMask,
+            lcBtnDnMask: value.lcBtnDnMask,
+            lcBtnUpMask: value.lcBtnUpMask,
+            lcInOrgXYZ: value.lcInOrgXYZ,
+            lcInExtXYZ: value.lcInExtXYZ,
+            lcOutOrgXYZ: value.lcOutOrgXYZ,
+            lcOutExtXYZ: value.lcOutExtXYZ,
+            lcSensXYZ: value.lcSensXYZ,
+            lcSysMode: value.lcSysMode,
+            lcSysOrgXY: value.lcSysOrgXY,
+            lcSysExtXY: value.lcSysExtXY,
+            lcSysSensXY: value.lcSysSensXY,
+        }
+    }
+}
+
+/// Converts the name to Windows-1252 (see [CString40::write_str]), everything else is copied as is.
+impl From<LOGCONTEXTW> for LOGCONTEXT {
+    fn from(value: LOGCONTEXTW) -> Self {
+        Self{
+            lcName: CString40::from(value.lcName.to_string_lossy().as_str()),
+            lcOptions: value.lcOptions,
+            lcStatus: value.lcStatus,
+            lcLocks: value.lcLocks,
+            lcMsgBase: value.lcMsgBase,
+            lcDevice: value.lcDevice,
+            lcPktRate: value.lcPktRate,
+            lcPktData: value.lcPktData,
+            lcPktMode: value.lcPktMode,
+            lcMoveMask: value.lcMoveMask,
+            lcBtnDnMask: value.lcBtnDnMask,
+            lcBtnUpMask: value.lcBtnUpMask,
+            lcInOrgXYZ: value.lcInOrgXYZ,
+            lcInExtXYZ: value.lcInExtXYZ,
+            lcOutOrgXYZ: value.lcOutOrgXYZ,
+            lcOutExtXYZ: value.lcOutExtXYZ,
+            lcSensXYZ: value.lcSensXYZ,
+            lcSysMode: value.lcSysMode,
+            lcSysOrgXY: value.lcSysOrgXY,
+            lcSysExtXY: value.lcSysExtXY,
+            lcSysSensXY: value.lcSysSensXY,
+        }
+    }
+}
+
 bitflags! {
     /// See [LOGCONTEXT::lcOptions]
     #[repr(C)]
@@ -213,6 +326,23 @@ bitflags! {
 
 
 
+#[cfg(test)]
+mod conversion_tests {
+    use super::*;
+
+    #[test]
+    fn test_logcontext_conversion(){
+        let mut context = LOGCONTEXT::default();
+        context.lcName.write_str("Café");
+        context.lcOutExtXYZ.y = -1080;
+        let wide = LOGCONTEXTW::from(context);
+        assert_eq!(wide.lcName.to_string_lossy(), "Café");
+        assert_eq!(wide.lcOutExtXYZ.y, -1080);
+        let narrow = LOGCONTEXT::from(wide);
+        assert_eq!(narrow.lcName, context.lcName);
+    }
+}
+
 #[cfg(feature="libloading")]
 #[cfg(test)]
 mod tests {
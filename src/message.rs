@@ -0,0 +1,233 @@
+use windows::Win32::Foundation::{LPARAM, WPARAM};
+
+use crate::{
+    c_type_aliases::{HCTX, HMGR, UINT},
+    window_message as wt, PumpSender, CXS, LOGCONTEXTW,
+};
+
+/// A wintab window message with its parameters decoded. See the [WT](crate::WT) constants for
+/// the meaning of each message.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WintabMessage {
+    /// [WT::PACKET](crate::WT::PACKET); read the packet with `WTPacket`
+    Packet { context: *mut HCTX, serial: UINT },
+    /// [WT::CTXOPEN](crate::WT::CTXOPEN)
+    ContextOpen { context: *mut HCTX, status: CXS },
+    /// [WT::CTXCLOSE](crate::WT::CTXCLOSE)
+    ContextClose { context: *mut HCTX, status: CXS },
+    /// [WT::CTXUPDATE](crate::WT::CTXUPDATE)
+    ContextUpdate { context: *mut HCTX, status: CXS },
+    /// [WT::CTXOVERLAP](crate::WT::CTXOVERLAP)
+    ContextOverlap { context: *mut HCTX, status: CXS },
+    /// [WT::PROXIMITY](crate::WT::PROXIMITY). `hardware` is set when the cursor entered or left
+    /// the range of the tablet itself, not just the context.
+    Proximity { context: *mut HCTX, entering: bool, hardware: bool },
+    /// [WT::INFOCHANGE](crate::WT::INFOCHANGE). `manager` is null when the change was reported
+    /// by the hardware.
+    InfoChange { manager: *mut HMGR, category: UINT, index: UINT },
+    /// [WT::CSRCHANGE](crate::WT::CSRCHANGE)
+    CursorChange { context: *mut HCTX, serial: UINT },
+    /// [WT::PACKETEXT](crate::WT::PACKETEXT); read the extension packet with `WTPacket`
+    PacketExt { context: *mut HCTX, serial: UINT },
+    /// A message within the wintab range which this crate does not know about
+    Other { offset: UINT, wparam: usize, lparam: isize },
+}
+
+// The handles are only identifying values; they are never dereferenced.
+unsafe impl Send for WintabMessage {}
+unsafe impl Sync for WintabMessage {}
+
+impl WintabMessage {
+    /// Decodes `msg` if it is one of the wintab messages of a context opened with `msg_base` as
+    /// its [LOGCONTEXTW::lcMsgBase]
+    pub fn decode(msg_base: UINT, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> Option<Self> {
+        let offset = msg.checked_sub(msg_base).filter(|offset| *offset <= wt::MAXOFFSET)?;
+        let WPARAM(wparam) = wparam;
+        let LPARAM(lparam) = lparam;
+        let handle = lparam as *mut HCTX;
+        let wparam_handle = wparam as *mut HCTX;
+        let status = CXS::from_bits_retain(lparam as UINT);
+        let low = (lparam & 0xFFFF) as UINT;
+        let high = ((lparam >> 16) & 0xFFFF) as UINT;
+        let serial = wparam as UINT;
+        Some(match offset + wt::DEFBASE {
+            wt::PACKET => WintabMessage::Packet { context: handle, serial },
+            wt::CTXOPEN => WintabMessage::ContextOpen { context: wparam_handle, status },
+            wt::CTXCLOSE => WintabMessage::ContextClose { context: wparam_handle, status },
+            wt::CTXUPDATE => WintabMessage::ContextUpdate { context: wparam_handle, status },
+            wt::CTXOVERLAP => WintabMessage::ContextOverlap { context: wparam_handle, status },
+            wt::PROXIMITY => WintabMessage::Proximity {
+                context: wparam_handle,
+                entering: low != 0,
+                hardware: high != 0,
+            },
+            wt::INFOCHANGE => WintabMessage::InfoChange {
+                manager: wparam as *mut HMGR,
+                category: low,
+                index: high,
+            },
+            wt::CSRCHANGE => WintabMessage::CursorChange { context: handle, serial },
+            wt::PACKETEXT => WintabMessage::PacketExt { context: handle, serial },
+            _ => WintabMessage::Other { offset, wparam, lparam },
+        })
+    }
+
+    /// The context the message is about, if any
+    pub fn context(&self) -> Option<*mut HCTX> {
+        match *self {
+            WintabMessage::Packet { context, .. }
+            | WintabMessage::ContextOpen { context, .. }
+            | WintabMessage::ContextClose { context, .. }
+            | WintabMessage::ContextUpdate { context, .. }
+            | WintabMessage::ContextOverlap { context, .. }
+            | WintabMessage::Proximity { context, .. }
+            | WintabMessage::CursorChange { context, .. }
+            | WintabMessage::PacketExt { context, .. } => Some(context),
+            WintabMessage::InfoChange { .. } | WintabMessage::Other { .. } => None,
+        }
+    }
+}
+
+/// Decodes the wintab messages sent to a window and forwards them to a callback or channel.
+///
+/// This holds no Win32 state, so it can be driven from any window procedure; `WindowSubclass`
+/// (with the `subclass` feature) installs one on an existing window.
+///
+/// ```ignore
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// let mut router = MessageRouter::with_sender(log_context.lcMsgBase, sender);
+/// // in the window procedure
+/// if router.route(msg, wparam, lparam) {
+///     return LRESULT(0);
+/// }
+/// ```
+pub struct MessageRouter {
+    msg_base: UINT,
+    handler: Box<dyn FnMut(WintabMessage)>,
+}
+
+impl MessageRouter {
+    /// Calls `handler` with each message in the range of a context opened with `msg_base` as
+    /// its [LOGCONTEXTW::lcMsgBase]
+    pub fn new(msg_base: UINT, handler: impl FnMut(WintabMessage) + 'static) -> Self {
+        Self { msg_base, handler: Box::new(handler) }
+    }
+
+    /// Sends each message over a channel. Messages are dropped once the receiver is gone, or
+    /// while a bounded channel is full, since the window procedure must not block.
+    pub fn with_sender(msg_base: UINT, sender: impl PumpSender<WintabMessage> + 'static) -> Self {
+        Self::new(msg_base, move |message| {
+            let _ = sender.try_send(message);
+        })
+    }
+
+    /// Uses the message base of an open context, as written back by `WTOpenW`
+    pub fn for_context(
+        log_context: &LOGCONTEXTW,
+        handler: impl FnMut(WintabMessage) + 'static,
+    ) -> Self {
+        Self::new(log_context.lcMsgBase, handler)
+    }
+
+    pub fn msg_base(&self) -> UINT {
+        self.msg_base
+    }
+
+    /// Forwards `msg` if it is a wintab message, returning `true` if it was handled
+    pub fn route(&mut self, msg: UINT, wparam: WPARAM, lparam: LPARAM) -> bool {
+        match WintabMessage::decode(self.msg_base, msg, wparam, lparam) {
+            Some(message) => {
+                (self.handler)(message);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+impl std::fmt::Debug for MessageRouter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageRouter").field("msg_base", &self.msg_base).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WT;
+    use std::{cell::RefCell, rc::Rc, sync::mpsc};
+
+    fn decode(msg_base: UINT, msg: UINT, wparam: usize, lparam: isize) -> Option<WintabMessage> {
+        WintabMessage::decode(msg_base, msg, WPARAM(wparam), LPARAM(lparam))
+    }
+
+    #[test]
+    fn test_decode() {
+        let context = 0x1000 as *mut HCTX;
+        assert_eq!(
+            decode(WT::DEFBASE, WT::PACKET, 42, 0x1000),
+            Some(WintabMessage::Packet { context, serial: 42 })
+        );
+        assert_eq!(
+            decode(WT::DEFBASE, WT::CTXOPEN, 0x1000, 0b10),
+            Some(WintabMessage::ContextOpen { context, status: CXS::from_bits_retain(0b10) })
+        );
+        assert_eq!(
+            decode(WT::DEFBASE, WT::PROXIMITY, 0x1000, 0x0001_0000),
+            Some(WintabMessage::Proximity { context, entering: false, hardware: true })
+        );
+        assert_eq!(
+            decode(WT::DEFBASE, WT::INFOCHANGE, 0, 0x0003_0064),
+            Some(WintabMessage::InfoChange {
+                manager: std::ptr::null_mut(),
+                category: 100,
+                index: 3,
+            })
+        );
+        assert_eq!(
+            decode(WT::DEFBASE, WT::DEFBASE + 0xF, 1, 2),
+            Some(WintabMessage::Other { offset: 0xF, wparam: 1, lparam: 2 })
+        );
+        assert_eq!(decode(WT::DEFBASE, WT::DEFBASE + 0x10, 0, 0), None);
+        assert_eq!(decode(WT::DEFBASE, WT::DEFBASE - 1, 0, 0), None);
+    }
+
+    #[test]
+    fn test_custom_msg_base() {
+        let base = 0x8000;
+        assert_eq!(
+            decode(base, base + 7, 5, 0x1000),
+            Some(WintabMessage::CursorChange { context: 0x1000 as *mut HCTX, serial: 5 })
+        );
+        // the default messages mean nothing to a context using another base
+        assert_eq!(decode(base, WT::PACKET, 5, 0x1000), None);
+    }
+
+    #[test]
+    fn test_route_to_callback() {
+        let received = Rc::new(RefCell::new(Vec::new()));
+        let log_context = LOGCONTEXTW { lcMsgBase: 0x0400, ..Default::default() };
+        let mut router = MessageRouter::for_context(&log_context, {
+            let received = received.clone();
+            move |message| received.borrow_mut().push(message)
+        });
+        assert!(router.route(0x0400, WPARAM(1), LPARAM(0x1000)));
+        assert!(!router.route(0x0001, WPARAM(0), LPARAM(0)));
+        assert!(router.route(0x0408, WPARAM(2), LPARAM(0x1000)));
+        let received = received.borrow();
+        assert_eq!(received.len(), 2);
+        assert!(matches!(received[1], WintabMessage::PacketExt { serial: 2, .. }));
+        assert_eq!(received[0].context(), Some(0x1000 as *mut HCTX));
+    }
+
+    #[test]
+    fn test_route_to_channel() {
+        let (sender, receiver) = mpsc::channel();
+        let mut router = MessageRouter::with_sender(WT::DEFBASE, sender);
+        assert!(router.route(WT::CTXCLOSE, WPARAM(0x1000), LPARAM(0)));
+        assert!(matches!(receiver.try_recv(), Ok(WintabMessage::ContextClose { .. })));
+        drop(receiver);
+        // still consumed, even though nobody is listening
+        assert!(router.route(WT::PACKET, WPARAM(0), LPARAM(0)));
+    }
+}
@@ -0,0 +1,487 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+};
+
+use crate::{
+    pump::{send_or_stop, FULL_RETRY},
+    Context, Packet, PacketReader, PumpSender, WintabApi, WintabMessage,
+};
+
+type Decode<T> = Box<dyn FnMut(&Packet, &mut Vec<T>)>;
+
+/// Reads packets when a context with [CXO::MESSAGES](crate::CXO::MESSAGES) reports
+/// [WintabMessage::Packet], decodes them and sends the results over a channel.
+///
+/// This is the part of `MessageWindow` (with the `message-window` feature) which does not
+/// depend on Win32, so it can also be used from any window procedure, e.g. with a
+/// [MessageRouter](crate::MessageRouter).
+///
+/// ```ignore
+/// let mut forwarder = MessageForwarder::new(context, sender, |packet, items| {
+///     items.push(packet.clone())
+/// });
+/// if let Some(message) = WintabMessage::decode(msg_base, msg, wparam, lparam) {
+///     forwarder.forward(&message);
+/// }
+/// ```
+pub struct MessageForwarder<A: WintabApi, T> {
+    context: Context<A>,
+    reader: PacketReader,
+    sender: Box<dyn PumpSender<T>>,
+    decode: Decode<T>,
+    items: Vec<T>,
+    stop: Arc<AtomicBool>,
+}
+
+impl<A: WintabApi, T> MessageForwarder<A, T> {
+    pub fn new(
+        context: Context<A>,
+        sender: impl PumpSender<T> + 'static,
+        decode: impl FnMut(&Packet, &mut Vec<T>) + 'static,
+    ) -> Self {
+        Self {
+            context,
+            reader: PacketReader::new(),
+            sender: Box::new(sender),
+            decode: Box::new(decode),
+            items: Vec::new(),
+            stop: Arc::new(AtomicBool::new(false)),
+        }
+    }
+
+    /// While a bounded channel is full, [MessageForwarder::forward] waits for space until
+    /// `stop` is set. Set it before closing the window, since the window procedure cannot see
+    /// the close message while it is waiting.
+    pub fn with_stop(mut self, stop: Arc<AtomicBool>) -> Self {
+        self.stop = stop;
+        self
+    }
+
+    pub fn context(&self) -> &Context<A> {
+        &self.context
+    }
+
+    /// Reads and forwards every queued packet if `message` says the context has packets
+    /// waiting; messages about other contexts are ignored. Packets are read in bulk so that
+    /// none are missed if messages are coalesced or the queue overflows.
+    ///
+    /// Returns `false` once the receiver has gone away, or if the stop flag given to
+    /// [MessageForwarder::with_stop] is set while waiting for space in a bounded channel.
+    pub fn forward(&mut self, message: &WintabMessage) -> bool {
+        match *message {
+            WintabMessage::Packet { context, .. } if context == self.context.handle() => {}
+            _ => return true,
+        }
+        for packet in self.reader.drain(&self.context) {
+            (self.decode)(packet, &mut self.items);
+        }
+        let should_stop = || self.stop.load(Ordering::Acquire);
+        for item in self.items.drain(..) {
+            if !send_or_stop(&*self.sender, item, should_stop, || thread::sleep(FULL_RETRY)) {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Gives back the context, e.g. to close it
+    pub fn into_context(self) -> Context<A> {
+        self.context
+    }
+}
+
+impl<A: WintabApi, T> std::fmt::Debug for MessageForwarder<A, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageForwarder")
+            .field("context", &self.context.handle())
+            .finish()
+    }
+}
+
+#[cfg(all(windows, feature = "message-window"))]
+pub use window::{MessageWindow, MessageWindowError};
+
+#[cfg(all(windows, feature = "message-window"))]
+mod window {
+    use std::{
+        sync::{
+            atomic::{AtomicBool, Ordering},
+            mpsc, Arc,
+        },
+        thread::{self, JoinHandle},
+    };
+
+    use windows::{
+        core::w,
+        Win32::{
+            Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+            System::LibraryLoader::GetModuleHandleW,
+            UI::WindowsAndMessaging::{
+                CreateWindowExW, DefWindowProcW, DestroyWindow, DispatchMessageW, GetMessageW,
+                PostMessageW, RegisterClassExW, HWND_MESSAGE, MSG, WINDOW_EX_STYLE, WINDOW_STYLE,
+                WM_CLOSE, WNDCLASSEXW,
+            },
+        },
+    };
+
+    use super::MessageForwarder;
+    use crate::{
+        Context, ContextError, Packet, PenEvent, PenEventStream, PumpSender, WintabApi,
+        WintabMessage, CXO, LOGCONTEXTW,
+    };
+
+    /// The ways starting a [MessageWindow] can fail
+    #[derive(Debug, Clone)]
+    pub enum MessageWindowError {
+        /// The message-only window could not be created
+        Window(windows::core::Error),
+        /// The window was created, but the context could not be opened
+        Open(ContextError),
+    }
+
+    impl std::fmt::Display for MessageWindowError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                MessageWindowError::Window(error) => {
+                    write!(f, "failed to create the message window: {error}")
+                }
+                MessageWindowError::Open(error) => error.fmt(f),
+            }
+        }
+    }
+
+    impl std::error::Error for MessageWindowError {
+        fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+            match self {
+                MessageWindowError::Window(error) => Some(error),
+                MessageWindowError::Open(error) => Some(error),
+            }
+        }
+    }
+
+    /// A hidden message-only (`HWND_MESSAGE`) window on its own thread, which owns a context
+    /// opened with [CXO::MESSAGES] and sends what it reads over a channel. This is for programs
+    /// with no window of their own, since `WTOpen` needs one.
+    ///
+    /// The context is closed and the window destroyed when this is shut down or dropped, or
+    /// when the receiver is dropped.
+    ///
+    /// ```ignore
+    /// let api = unsafe { LibloadingApi::load()? };
+    /// let log_context = api.default_system_context().unwrap();
+    /// let (sender, receiver) = std::sync::mpsc::channel();
+    /// let _window = MessageWindow::spawn_pen_events(api, log_context, sender)?;
+    /// for event in receiver {
+    ///     println!("{:?}", event);
+    /// }
+    /// ```
+    #[derive(Debug)]
+    pub struct MessageWindow {
+        window: HWND,
+        thread: Option<JoinHandle<()>>,
+        stop: Arc<AtomicBool>,
+    }
+
+    impl MessageWindow {
+        /// Sends every [Packet] read from the context
+        pub fn spawn<A, S>(
+            api: A,
+            log_context: LOGCONTEXTW,
+            sender: S,
+        ) -> Result<Self, MessageWindowError>
+        where
+            A: WintabApi + Send + 'static,
+            S: PumpSender<Packet> + 'static,
+        {
+            Self::spawn_with(api, log_context, sender, |packet, items| {
+                items.push(packet.clone())
+            })
+        }
+
+        /// Like [MessageWindow::spawn], but sends the [PenEvent]s produced by a
+        /// [PenEventStream]
+        pub fn spawn_pen_events<A, S>(
+            api: A,
+            log_context: LOGCONTEXTW,
+            sender: S,
+        ) -> Result<Self, MessageWindowError>
+        where
+            A: WintabApi + Send + 'static,
+            S: PumpSender<PenEvent> + 'static,
+        {
+            let mut stream = PenEventStream::new();
+            Self::spawn_with(api, log_context, sender, move |packet, items| {
+                stream.process(packet, items)
+            })
+        }
+
+        /// Opens a context described by `log_context`, with [CXO::MESSAGES] added, and sends
+        /// whatever `decode` appends to the `Vec` it is given for each packet
+        pub fn spawn_with<A, T, S, F>(
+            api: A,
+            mut log_context: LOGCONTEXTW,
+            sender: S,
+            decode: F,
+        ) -> Result<Self, MessageWindowError>
+        where
+            A: WintabApi + Send + 'static,
+            T: 'static,
+            S: PumpSender<T> + 'static,
+            F: FnMut(&Packet, &mut Vec<T>) + Send + 'static,
+        {
+            let (ready_sender, ready) = mpsc::sync_channel(1);
+            let stop = Arc::new(AtomicBool::new(false));
+            let thread_stop = stop.clone();
+            let thread = thread::Builder::new()
+                .name("wintab message window".to_string())
+                .spawn(move || {
+                    let window = match unsafe { create_window() } {
+                        Ok(window) => window,
+                        Err(error) => {
+                            let _ = ready_sender.send(Err(MessageWindowError::Window(error)));
+                            return;
+                        }
+                    };
+                    log_context.lcOptions |= CXO::MESSAGES;
+                    let context = match Context::open(api, window, &mut log_context, true) {
+                        Ok(context) => context,
+                        Err(error) => {
+                            let _ = unsafe { DestroyWindow(window) };
+                            let _ = ready_sender.send(Err(MessageWindowError::Open(error)));
+                            return;
+                        }
+                    };
+                    let _ = ready_sender.send(Ok(window));
+                    let mut forwarder =
+                        MessageForwarder::new(context, sender, decode).with_stop(thread_stop);
+                    unsafe { run(window, log_context.lcMsgBase, &mut forwarder) };
+                    drop(forwarder);
+                    let _ = unsafe { DestroyWindow(window) };
+                })
+                .expect("failed to spawn the message window thread");
+            match ready.recv() {
+                Ok(Ok(window)) => Ok(Self { window, thread: Some(thread), stop }),
+                Ok(Err(error)) => {
+                    let _ = thread.join();
+                    Err(error)
+                }
+                Err(_) => match thread.join() {
+                    Err(panic) => std::panic::resume_unwind(panic),
+                    Ok(()) => unreachable!("the message window thread exited without reporting"),
+                },
+            }
+        }
+
+        /// The message-only window which owns the context
+        pub fn window(&self) -> HWND {
+            self.window
+        }
+
+        /// `false` once the window thread has stopped, e.g. because the receiver was dropped
+        pub fn is_running(&self) -> bool {
+            self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+        }
+
+        /// Closes the context and the window, and waits for the thread to finish
+        pub fn shutdown(mut self) -> thread::Result<()> {
+            self.stop_and_join()
+        }
+
+        fn stop_and_join(&mut self) -> thread::Result<()> {
+            match self.thread.take() {
+                Some(thread) => {
+                    // gives up waiting for a full channel, so the close message is seen
+                    self.stop.store(true, Ordering::Release);
+                    // fails if the thread has already stopped and destroyed the window
+                    let _ = unsafe { PostMessageW(self.window, WM_CLOSE, WPARAM(0), LPARAM(0)) };
+                    thread.join()
+                }
+                None => Ok(()),
+            }
+        }
+    }
+
+    impl Drop for MessageWindow {
+        fn drop(&mut self) {
+            let _ = self.stop_and_join();
+        }
+    }
+
+    unsafe extern "system" fn window_proc(
+        window: HWND,
+        msg: u32,
+        wparam: WPARAM,
+        lparam: LPARAM,
+    ) -> LRESULT {
+        DefWindowProcW(window, msg, wparam, lparam)
+    }
+
+    unsafe fn create_window() -> Result<HWND, windows::core::Error> {
+        let instance = GetModuleHandleW(None)?;
+        let class_name = w!("wintab_lite message window");
+        let class = WNDCLASSEXW {
+            cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+            lpfnWndProc: Some(window_proc),
+            hInstance: instance.into(),
+            lpszClassName: class_name,
+            ..Default::default()
+        };
+        // fails harmlessly if an earlier MessageWindow already registered the class
+        RegisterClassExW(&class);
+        let window = CreateWindowExW(
+            WINDOW_EX_STYLE::default(),
+            class_name,
+            w!(""),
+            WINDOW_STYLE::default(),
+            0,
+            0,
+            0,
+            0,
+            HWND_MESSAGE,
+            None,
+            instance,
+            None,
+        );
+        if window.0 == 0 {
+            Err(windows::core::Error::from_win32())
+        } else {
+            Ok(window)
+        }
+    }
+
+    /// Pumps messages until the window is asked to close or the receiver goes away
+    unsafe fn run<A: WintabApi, T>(
+        window: HWND,
+        msg_base: u32,
+        forwarder: &mut MessageForwarder<A, T>,
+    ) {
+        let mut msg = MSG::default();
+        while GetMessageW(&mut msg, None, 0, 0).0 > 0 {
+            if msg.hwnd == window && msg.message == WM_CLOSE {
+                return;
+            }
+            match WintabMessage::decode(msg_base, msg.message, msg.wParam, msg.lParam) {
+                Some(message) if msg.hwnd == window => {
+                    if !forwarder.forward(&message) {
+                        return;
+                    }
+                }
+                _ => {
+                    DispatchMessageW(&msg);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{c_type_aliases::HCTX, test_support::FakeWintab, PenEvent, PenEventStream, TPS};
+    use crate::{ContextError, LOGCONTEXTW};
+    use std::{sync::mpsc, time::Duration};
+    use windows::Win32::Foundation::HWND;
+
+    fn open(api: &FakeWintab) -> Result<Context<&FakeWintab>, ContextError> {
+        Context::open(api, HWND(1), &mut LOGCONTEXTW::default(), true)
+    }
+
+    fn packet_message(context: *mut HCTX, serial: u32) -> WintabMessage {
+        WintabMessage::Packet { context, serial }
+    }
+
+    #[test]
+    fn test_forward_packets() {
+        let api = FakeWintab::new();
+        let (sender, receiver) = mpsc::channel();
+        let context = open(&api).unwrap();
+        let handle = context.handle();
+        let mut forwarder =
+            MessageForwarder::new(context, sender, |packet, items| items.push(packet.clone()));
+        for _ in 0..3 {
+            api.push(Packet::default());
+        }
+        // one message is enough to read everything queued so far
+        assert!(forwarder.forward(&packet_message(handle, 0)));
+        let serials: Vec<_> = receiver.try_iter().map(|packet| packet.pkSerialNumber).collect();
+        assert_eq!(serials, [0, 1, 2]);
+        // the later messages for packets already read find an empty queue
+        assert!(forwarder.forward(&packet_message(handle, 1)));
+        assert!(receiver.try_recv().is_err());
+    }
+
+    #[test]
+    fn test_other_messages_are_ignored() {
+        let api = FakeWintab::new();
+        let (sender, receiver) = mpsc::channel();
+        let context = open(&api).unwrap();
+        let handle = context.handle();
+        let mut forwarder =
+            MessageForwarder::new(context, sender, |packet, items| items.push(packet.clone()));
+        api.push(Packet::default());
+        let other = 0x2000 as *mut HCTX;
+        assert!(forwarder.forward(&packet_message(other, 0)));
+        let proximity = WintabMessage::Proximity { context: handle, entering: true, hardware: true };
+        assert!(forwarder.forward(&proximity));
+        assert!(receiver.try_recv().is_err());
+        assert!(api.state().data_get_calls.is_empty());
+    }
+
+    #[test]
+    fn test_pen_events_and_dropped_receiver() {
+        let api = FakeWintab::new();
+        let (sender, receiver) = mpsc::channel();
+        let context = open(&api).unwrap();
+        let handle = context.handle();
+        let mut stream = PenEventStream::new();
+        let mut forwarder = MessageForwarder::new(context, sender, move |packet, items| {
+            stream.process(packet, items)
+        });
+        api.push(Packet::default());
+        api.push(Packet { pkStatus: TPS::PROXIMITY, ..Default::default() });
+        assert!(forwarder.forward(&packet_message(handle, 1)));
+        let events: Vec<_> = receiver.try_iter().collect();
+        assert!(matches!(events[..], [
+            PenEvent::ProximityEnter(_),
+            PenEvent::Hover(_),
+            PenEvent::ProximityLeave(_)
+        ]));
+
+        drop(receiver);
+        api.push(Packet::default());
+        assert!(!forwarder.forward(&packet_message(handle, 2)));
+        drop(forwarder);
+        assert_eq!(api.state().closed.len(), 1);
+    }
+
+    #[test]
+    fn test_stop_while_channel_is_full() {
+        let api = FakeWintab::new();
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let context = open(&api).unwrap();
+        let handle = context.handle();
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut forwarder =
+            MessageForwarder::new(context, sender, |packet, items| items.push(packet.clone()))
+                .with_stop(stop.clone());
+        for _ in 0..3 {
+            api.push(Packet::default());
+        }
+        thread::scope(|scope| {
+            scope.spawn(|| {
+                // once every packet has been read the forwarder is waiting for space, since
+                // the receiver is alive but never drained
+                while !api.state().queue.is_empty() {
+                    thread::sleep(Duration::from_millis(1));
+                }
+                stop.store(true, Ordering::Release);
+            });
+            assert!(!forwarder.forward(&packet_message(handle, 0)));
+        });
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+}
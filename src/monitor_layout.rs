@@ -0,0 +1,340 @@
+use crate::{
+    c_type_aliases::{INT, LONG},
+    LOGCONTEXT, LOGCONTEXTW, XY, XYZ,
+};
+
+/// A context whose output area can be set by [ScreenRect::apply_output] and
+/// [MonitorLayout::apply]; implemented for both [LOGCONTEXT] and [LOGCONTEXTW]
+pub trait OutputArea {
+    /// The output origin and extent, as in [LOGCONTEXTW::lcOutOrgXYZ] and
+    /// [LOGCONTEXTW::lcOutExtXYZ]
+    fn output_area_mut(&mut self) -> (&mut XYZ<LONG>, &mut XYZ<LONG>);
+    /// The screen area the system cursor follows, as in [LOGCONTEXTW::lcSysOrgXY] and
+    /// [LOGCONTEXTW::lcSysExtXY]
+    fn system_area_mut(&mut self) -> (&mut XY<INT>, &mut XY<INT>);
+}
+
+impl OutputArea for LOGCONTEXT {
+    fn output_area_mut(&mut self) -> (&mut XYZ<LONG>, &mut XYZ<LONG>) {
+        (&mut self.lcOutOrgXYZ, &mut self.lcOutExtXYZ)
+    }
+    fn system_area_mut(&mut self) -> (&mut XY<INT>, &mut XY<INT>) {
+        (&mut self.lcSysOrgXY, &mut self.lcSysExtXY)
+    }
+}
+
+impl OutputArea for LOGCONTEXTW {
+    fn output_area_mut(&mut self) -> (&mut XYZ<LONG>, &mut XYZ<LONG>) {
+        (&mut self.lcOutOrgXYZ, &mut self.lcOutExtXYZ)
+    }
+    fn system_area_mut(&mut self) -> (&mut XY<INT>, &mut XY<INT>) {
+        (&mut self.lcSysOrgXY, &mut self.lcSysExtXY)
+    }
+}
+
+/// A rectangle in physical screen pixels
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ScreenRect {
+    pub left: LONG,
+    pub top: LONG,
+    pub width: LONG,
+    pub height: LONG,
+}
+
+impl ScreenRect {
+    pub fn new(left: LONG, top: LONG, width: LONG, height: LONG) -> Self {
+        Self { left, top, width, height }
+    }
+
+    /// From the edges, as in a win32 `RECT`
+    pub fn from_edges(left: LONG, top: LONG, right: LONG, bottom: LONG) -> Self {
+        Self { left, top, width: right - left, height: bottom - top }
+    }
+
+    pub fn right(&self) -> LONG {
+        self.left + self.width
+    }
+
+    pub fn bottom(&self) -> LONG {
+        self.top + self.height
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.width <= 0 || self.height <= 0
+    }
+
+    /// Width over height
+    pub fn aspect(&self) -> f64 {
+        self.width as f64 / self.height as f64
+    }
+
+    pub fn contains(&self, x: LONG, y: LONG) -> bool {
+        (self.left..self.right()).contains(&x) && (self.top..self.bottom()).contains(&y)
+    }
+
+    /// The smallest rect containing both
+    pub fn union(&self, other: &ScreenRect) -> ScreenRect {
+        Self::from_edges(
+            self.left.min(other.left),
+            self.top.min(other.top),
+            self.right().max(other.right()),
+            self.bottom().max(other.bottom()),
+        )
+    }
+
+    /// The largest rect with the given width over height which fits inside this one, centred.
+    /// The spare space is left as bars either side (pillarbox) or above and below (letterbox).
+    pub fn fit_aspect(&self, aspect: f64) -> ScreenRect {
+        if !(aspect > 0.0 && aspect.is_finite()) || self.is_empty() {
+            return *self;
+        }
+        let (width, height) = if self.aspect() > aspect {
+            ((self.height as f64 * aspect).round() as LONG, self.height)
+        } else {
+            (self.width, (self.width as f64 / aspect).round() as LONG)
+        };
+        Self {
+            left: self.left + (self.width - width) / 2,
+            top: self.top + (self.height - height) / 2,
+            width,
+            height,
+        }
+    }
+
+    /// The [LOGCONTEXTW::lcOutOrgXYZ] and [LOGCONTEXTW::lcOutExtXYZ] which map the tablet onto
+    /// this rect, with y pointing down the screen. z is left at zero.
+    pub fn output_extents(&self) -> (XYZ<LONG>, XYZ<LONG>) {
+        (
+            XYZ { x: self.left, y: self.top, z: 0 },
+            // wintab's y axis points up; a negative extent flips it
+            XYZ { x: self.width, y: -self.height, z: 0 },
+        )
+    }
+
+    /// Sets the x and y of [LOGCONTEXTW::lcOutOrgXYZ] and [LOGCONTEXTW::lcOutExtXYZ] from
+    /// [ScreenRect::output_extents], leaving z as it is. Also sets the system cursor area with
+    /// [ScreenRect::apply_system], so that a [CXO::SYSTEM](crate::CXO::SYSTEM) context moves the
+    /// cursor over the same rect.
+    pub fn apply_output(&self, log_context: &mut impl OutputArea) {
+        let (org, ext) = self.output_extents();
+        let (out_org, out_ext) = log_context.output_area_mut();
+        *out_org = XYZ { z: out_org.z, ..org };
+        *out_ext = XYZ { z: out_ext.z, ..ext };
+        self.apply_system(log_context);
+    }
+
+    /// Sets [LOGCONTEXTW::lcSysOrgXY] and [LOGCONTEXTW::lcSysExtXY] to this rect. Unlike the
+    /// output extent, the system extent is not flipped; it is plain screen coordinates.
+    pub fn apply_system(&self, log_context: &mut impl OutputArea) {
+        let (sys_org, sys_ext) = log_context.system_area_mut();
+        *sys_org = XY { x: self.left, y: self.top };
+        *sys_ext = XY { x: self.width, y: self.height };
+    }
+}
+
+/// A display, in physical screen pixels
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Monitor {
+    pub rect: ScreenRect,
+    /// Physical pixels per logical pixel, e.g. `1.5` at 144 DPI
+    pub scale_factor: f64,
+}
+
+impl Monitor {
+    pub fn new(rect: ScreenRect, scale_factor: f64) -> Self {
+        Self { rect, scale_factor }
+    }
+}
+
+/// Where a context's output should go; see [MonitorLayout::output_rect]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum OutputPreset {
+    /// Every monitor, as one rect
+    VirtualDesktop,
+    /// The monitor with its top left corner at the screen origin
+    PrimaryMonitor,
+    /// The monitor with this index in the layout
+    Monitor(usize),
+    /// The client area of a window, in physical screen pixels
+    Window(ScreenRect),
+    /// The largest part of `rect` with the tablet's aspect ratio (width over height), so that
+    /// circles stay round. The rest of `rect` is not reachable with the pen.
+    Region { rect: ScreenRect, tablet_aspect: f64 },
+}
+
+/// The arrangement of monitors, as reported by the platform (e.g. `EnumDisplayMonitors` with
+/// `GetDpiForMonitor` on Windows, or `winit`'s `available_monitors`). Used to pick the output
+/// area of a context without calling `GetSystemMetrics`.
+///
+/// ```ignore
+/// let layout = MonitorLayout::new()
+///     .with_monitor(Monitor::new(ScreenRect::new(0, 0, 2560, 1440), 1.5))
+///     .with_monitor(Monitor::new(ScreenRect::new(2560, 0, 1920, 1080), 1.0));
+/// layout.apply(OutputPreset::Monitor(1), &mut log_context);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MonitorLayout {
+    monitors: Vec<Monitor>,
+}
+
+impl MonitorLayout {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_monitor(mut self, monitor: Monitor) -> Self {
+        self.monitors.push(monitor);
+        self
+    }
+
+    pub fn push(&mut self, monitor: Monitor) {
+        self.monitors.push(monitor);
+    }
+
+    pub fn monitors(&self) -> &[Monitor] {
+        &self.monitors
+    }
+
+    /// The bounding rect of every monitor, like `SM_XVIRTUALSCREEN` to `SM_CYVIRTUALSCREEN`
+    pub fn virtual_desktop(&self) -> Option<ScreenRect> {
+        self.monitors.iter().map(|monitor| monitor.rect).reduce(|a, b| a.union(&b))
+    }
+
+    /// The monitor with its top left corner at `(0, 0)`, which is the primary monitor on
+    /// Windows. Falls back on the first monitor.
+    pub fn primary(&self) -> Option<&Monitor> {
+        let primary = self.monitors.iter().find(|monitor| {
+            let rect = monitor.rect;
+            (rect.left, rect.top) == (0, 0)
+        });
+        primary.or(self.monitors.first())
+    }
+
+    /// The monitor containing the screen pixel `(x, y)`
+    pub fn monitor_at(&self, x: LONG, y: LONG) -> Option<&Monitor> {
+        self.monitors.iter().find(|monitor| monitor.rect.contains(x, y))
+    }
+
+    /// The scale factor at `(x, y)`, e.g. at the client origin of a window for
+    /// [ClientMapper](crate::ClientMapper)
+    pub fn scale_factor_at(&self, x: LONG, y: LONG) -> Option<f64> {
+        self.monitor_at(x, y).map(|monitor| monitor.scale_factor)
+    }
+
+    /// The output area for `preset`, or `None` if it refers to a monitor which is not in the
+    /// layout or the area is empty
+    pub fn output_rect(&self, preset: OutputPreset) -> Option<ScreenRect> {
+        let rect = match preset {
+            OutputPreset::VirtualDesktop => self.virtual_desktop()?,
+            OutputPreset::PrimaryMonitor => self.primary()?.rect,
+            OutputPreset::Monitor(index) => self.monitors.get(index)?.rect,
+            OutputPreset::Window(rect) => rect,
+            OutputPreset::Region { rect, tablet_aspect } => rect.fit_aspect(tablet_aspect),
+        };
+        Some(rect).filter(|rect| !rect.is_empty())
+    }
+
+    /// Sets the output extents and system cursor area of `log_context` for `preset`. Returns `false`, leaving the
+    /// context unchanged, if there is no such output area.
+    pub fn apply(&self, preset: OutputPreset, log_context: &mut impl OutputArea) -> bool {
+        match self.output_rect(preset) {
+            Some(rect) => {
+                rect.apply_output(log_context);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 1.5x laptop screen with a 1x monitor to its left, lower down
+    fn layout() -> MonitorLayout {
+        MonitorLayout::new()
+            .with_monitor(Monitor::new(ScreenRect::new(-1920, 200, 1920, 1080), 1.0))
+            .with_monitor(Monitor::new(ScreenRect::new(0, 0, 2880, 1800), 1.5))
+    }
+
+    fn extents(layout: &MonitorLayout, preset: OutputPreset) -> Option<(XYZ<LONG>, XYZ<LONG>)> {
+        let mut log_context = LOGCONTEXTW::default();
+        log_context.lcOutOrgXYZ.z = 7;
+        log_context.lcOutExtXYZ.z = 1023;
+        layout.apply(preset, &mut log_context).then(|| {
+            assert_eq!((log_context.lcOutOrgXYZ.z, log_context.lcOutExtXYZ.z), (7, 1023));
+            let (org, ext) = (log_context.lcOutOrgXYZ, log_context.lcOutExtXYZ);
+            assert_eq!(log_context.lcSysOrgXY, XY { x: org.x, y: org.y });
+            assert_eq!(log_context.lcSysExtXY, XY { x: ext.x, y: -ext.y });
+            (log_context.lcOutOrgXYZ, log_context.lcOutExtXYZ)
+        })
+    }
+
+    fn xyz(x: LONG, y: LONG, z: LONG) -> XYZ<LONG> {
+        XYZ { x, y, z }
+    }
+
+    #[test]
+    fn test_presets() {
+        let layout = layout();
+        assert_eq!(
+            extents(&layout, OutputPreset::VirtualDesktop),
+            Some((xyz(-1920, 0, 7), xyz(4800, -1800, 1023)))
+        );
+        assert_eq!(
+            extents(&layout, OutputPreset::PrimaryMonitor),
+            Some((xyz(0, 0, 7), xyz(2880, -1800, 1023)))
+        );
+        assert_eq!(
+            extents(&layout, OutputPreset::Monitor(0)),
+            Some((xyz(-1920, 200, 7), xyz(1920, -1080, 1023)))
+        );
+        assert_eq!(extents(&layout, OutputPreset::Monitor(2)), None);
+        let window = ScreenRect::from_edges(100, 150, 900, 750);
+        assert_eq!(
+            extents(&layout, OutputPreset::Window(window)),
+            Some((xyz(100, 150, 7), xyz(800, -600, 1023)))
+        );
+        assert_eq!(extents(&MonitorLayout::new(), OutputPreset::VirtualDesktop), None);
+        assert_eq!(extents(&MonitorLayout::new(), OutputPreset::PrimaryMonitor), None);
+    }
+
+    #[test]
+    fn test_ansi_context() {
+        let mut log_context = LOGCONTEXT::from(LOGCONTEXTW::default());
+        log_context.lcOutExtXYZ.z = 1023;
+        assert!(layout().apply(OutputPreset::Monitor(0), &mut log_context));
+        assert_eq!(log_context.lcOutOrgXYZ, xyz(-1920, 200, 0));
+        assert_eq!(log_context.lcOutExtXYZ, xyz(1920, -1080, 1023));
+        assert_eq!(log_context.lcSysOrgXY, XY { x: -1920, y: 200 });
+        assert_eq!(log_context.lcSysExtXY, XY { x: 1920, y: 1080 });
+    }
+
+    #[test]
+    fn test_letterboxed_region() {
+        let layout = layout();
+        // a 16:10 tablet on a 16:9 monitor leaves bars at the sides
+        let rect = layout.monitors()[0].rect;
+        let region = OutputPreset::Region { rect, tablet_aspect: 1.6 };
+        assert_eq!(layout.output_rect(region), Some(ScreenRect::new(-1824, 200, 1728, 1080)));
+        // a 16:9 tablet on a 16:10 monitor leaves bars above and below
+        let rect = layout.monitors()[1].rect;
+        let region = OutputPreset::Region { rect, tablet_aspect: 16.0 / 9.0 };
+        assert_eq!(layout.output_rect(region), Some(ScreenRect::new(0, 90, 2880, 1620)));
+        // a nonsense aspect is ignored
+        let region = OutputPreset::Region { rect, tablet_aspect: 0.0 };
+        assert_eq!(layout.output_rect(region), Some(rect));
+    }
+
+    #[test]
+    fn test_monitor_lookup() {
+        let layout = layout();
+        assert_eq!(layout.scale_factor_at(10, 10), Some(1.5));
+        assert_eq!(layout.scale_factor_at(-1, 1279), Some(1.0));
+        // above the left monitor, which starts lower down
+        assert_eq!(layout.scale_factor_at(-1, 10), None);
+        let offset = MonitorLayout::new().with_monitor(layout.monitors()[0]);
+        assert_eq!(offset.primary(), Some(&layout.monitors()[0]));
+    }
+}
@@ -0,0 +1,955 @@
+//! Tablets, tools, pads and events in the shape used by the
+//! [octotablet](https://crates.io/crates/octotablet) crate, so that a wintab backend for it is a
+//! thin shim.
+//!
+//! Everything is discovered with `WTInfo` queries through [Adapter::load], then packets from a
+//! context are turned into [Event]s with [Adapter::process] and [Adapter::process_ext].
+//!
+//! ```ignore
+//! let mut adapter = octotablet::Adapter::load(&api);
+//! let mut events = adapter.initial_events();
+//! for packet in reader.drain(&context) {
+//!     adapter.process(packet, &mut events);
+//! }
+//! ```
+
+use std::{
+    collections::{HashMap, HashSet},
+    f32::consts::{FRAC_PI_2, TAU},
+    time::Duration,
+};
+
+use crate::{
+    c_type_aliases::{DWORD, UINT},
+    information_categories::{CSR, DVC, EXT, IFC, WTI},
+    OrientationScale, Packet, PacketExt, PenEvent, PenEventStream, PenState, PenTool,
+    PressureCurve, ToolTable, WintabApi, AXIS, WTPKT, WTX,
+};
+
+/// A USB vendor and product id
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct UsbId {
+    pub vid: u16,
+    pub pid: u16,
+}
+
+impl UsbId {
+    /// Finds the `VID_xxxx` and `PID_xxxx` parts of a Plug and Play id such as
+    /// `USB\VID_056A&PID_0357&MI_00`. Returns `None` for ids which are not USB ids.
+    pub fn from_pnp_id(pnp_id: &str) -> Option<Self> {
+        let upper = pnp_id.to_ascii_uppercase();
+        let hex = |key: &str| {
+            let start = upper.find(key)? + key.len();
+            u16::from_str_radix(upper.get(start..start + 4)?, 16).ok()
+        };
+        Some(Self { vid: hex("VID_")?, pid: hex("PID_")? })
+    }
+}
+
+/// A device, as listed under [WTI::DEVICES]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Tablet {
+    /// The zero based device index
+    pub device: UINT,
+    /// [DVC::NAME]
+    pub name: Option<String>,
+    /// Parsed from [DVC::PNPID]
+    pub usb_id: Option<UsbId>,
+}
+
+/// The kind of a [Tool]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ToolType {
+    Pen,
+    Pencil,
+    Brush,
+    Airbrush,
+    Eraser,
+    Mouse,
+    Lens,
+}
+
+impl ToolType {
+    /// Uses the general tool kind of [CSR::TYPE] to tell a lens cursor (`0x0006`) from a mouse.
+    /// An art pen is a [ToolType::Pen] with [Axes::roll].
+    pub fn from_tool(tool: PenTool, cursor_type: Option<UINT>) -> Option<Self> {
+        Some(match tool {
+            PenTool::Tip | PenTool::ArtPen => ToolType::Pen,
+            PenTool::Eraser => ToolType::Eraser,
+            PenTool::Airbrush => ToolType::Airbrush,
+            PenTool::Puck if cursor_type.map(|t| t & 0x0F06) == Some(0x0006) => ToolType::Lens,
+            PenTool::Puck => ToolType::Mouse,
+            PenTool::Unknown => return None,
+        })
+    }
+}
+
+/// The range of a tool axis, as reported in [Pose]
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct AxisInfo {
+    /// The smallest and largest values reported, if fixed
+    pub limits: Option<(f32, f32)>,
+    /// The number of distinct raw values, if known
+    pub granularity: Option<u32>,
+}
+
+/// The axes a [Tool] reports; `None` when the axis is not available
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Axes {
+    /// Normal pressure from `0.0` to `1.0`
+    pub pressure: Option<AxisInfo>,
+    /// Tilt in radians; see [Tilt](crate::Tilt)
+    pub tilt: Option<AxisInfo>,
+    /// Height above the tablet in raw [DVC::Z] units
+    pub distance: Option<AxisInfo>,
+    /// Rotation of the pen about its own axis in radians, e.g. from an art pen
+    pub roll: Option<AxisInfo>,
+    /// The finger wheel of an airbrush, from `-1.0` to `1.0`
+    pub slider: Option<AxisInfo>,
+}
+
+/// A cursor, as listed under [WTI::CURSORS]
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tool {
+    /// The zero based cursor index, as in [Packet::pkCursor]
+    pub cursor: UINT,
+    /// The device this cursor belongs to, from [DVC::FIRSTCSR] and [DVC::NCSRTYPES]
+    pub tablet: UINT,
+    /// [CSR::NAME]
+    pub name: Option<String>,
+    /// [CSR::PHYSID], if non zero
+    pub hardware_serial: Option<u64>,
+    /// [CSR::TYPE]
+    pub wacom_id: Option<u64>,
+    pub tool_type: Option<ToolType>,
+    pub axes: Axes,
+}
+
+/// A touch ring or touch strip of a [PadGroup]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PadControl {
+    /// The number of positions, from the extension's [EXT::AXES]
+    pub granularity: Option<u32>,
+}
+
+/// The controls of a [Pad] which share a mode
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PadGroup {
+    /// Indices of the buttons of the pad in this group
+    pub buttons: Vec<u32>,
+    pub rings: Vec<PadControl>,
+    pub strips: Vec<PadControl>,
+    /// Wintab does not report how many modes there are
+    pub mode_count: Option<u32>,
+}
+
+/// The ExpressKeys, touch rings and touch strips of one tablet.
+///
+/// Wintab does not report how many controls a tablet has, so the pad grows as controls are used.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Pad {
+    /// [ExpKeysData::nTablet](crate::ExpKeysData::nTablet)
+    pub tablet: u8,
+    pub total_buttons: u32,
+    /// Always exactly one group
+    pub groups: Vec<PadGroup>,
+}
+
+/// An extension found under [WTI::EXTENSIONS]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ExtensionInfo {
+    /// The zero based extension index
+    pub index: UINT,
+    /// [EXT::MASK], to be added to [LOGCONTEXTW::lcPktData](crate::LOGCONTEXTW::lcPktData)
+    pub mask: WTPKT,
+    /// [EXT::AXES]
+    pub axes: Vec<AXIS>,
+}
+
+/// The pad extensions supported by the driver
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Extensions {
+    /// [WTX::EXPKEYS2]
+    pub express_keys: Option<ExtensionInfo>,
+    /// [WTX::TOUCHRING]
+    pub touch_ring: Option<ExtensionInfo>,
+    /// [WTX::TOUCHSTRIP]
+    pub touch_strip: Option<ExtensionInfo>,
+}
+
+impl Extensions {
+    /// Searches the [IFC::NEXTENSIONS] extensions by [EXT::TAG]
+    pub fn load<A: WintabApi + ?Sized>(api: &A) -> Self {
+        let count: UINT =
+            api.info_value(WTI::INTERFACE as UINT, IFC::NEXTENSIONS as UINT).unwrap_or(0);
+        let mut extensions = Self::default();
+        for index in 0..count {
+            let category = WTI::EXTENSIONS as UINT + index;
+            let Some(tag) = api.info_value::<UINT>(category, EXT::TAG as UINT) else {
+                continue;
+            };
+            let slot = match tag {
+                tag if tag == WTX::EXPKEYS2 as UINT => &mut extensions.express_keys,
+                tag if tag == WTX::TOUCHRING as UINT => &mut extensions.touch_ring,
+                tag if tag == WTX::TOUCHSTRIP as UINT => &mut extensions.touch_strip,
+                _ => continue,
+            };
+            *slot = Some(ExtensionInfo {
+                index,
+                mask: WTPKT::from_bits_retain(
+                    api.info_value(category, EXT::MASK as UINT).unwrap_or(0),
+                ),
+                axes: api.info_array(category, EXT::AXES as UINT).unwrap_or_default(),
+            });
+        }
+        extensions
+    }
+
+    /// The bits to add to [LOGCONTEXTW::lcPktData](crate::LOGCONTEXTW::lcPktData) to receive
+    /// every supported extension in [WT::PACKETEXT](crate::WT::PACKETEXT) messages
+    pub fn packet_mask(&self) -> WTPKT {
+        [&self.express_keys, &self.touch_ring, &self.touch_strip]
+            .into_iter()
+            .flatten()
+            .fold(WTPKT::empty(), |mask, extension| mask | extension.mask)
+    }
+}
+
+/// The state of a tool as of a [ToolEvent::Pose]. Axes which the tool does not have are `None`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Pose {
+    /// [Packet::pkXYZ] in context output coordinates
+    pub position: [f32; 2],
+    pub distance: Option<f32>,
+    pub pressure: Option<f32>,
+    pub tilt: Option<[f32; 2]>,
+    pub roll: Option<f32>,
+    pub slider: Option<f32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TabletEvent {
+    Added,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToolEvent {
+    /// The tool was seen for the first time
+    Added,
+    /// The tool came into proximity of `tablet`
+    In {
+        tablet: UINT,
+    },
+    Down,
+    /// A wintab logical button changed; the tip is usually button 0
+    Button {
+        button_id: u32,
+        pressed: bool,
+    },
+    Pose(Pose),
+    /// Ends the events of one packet, with [Packet::pkTime]. `None` for the events of
+    /// [Adapter::leave], which have no packet.
+    Frame(Option<Duration>),
+    Up,
+    Out,
+}
+
+/// An event of a touch ring or touch strip
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SliderEvent {
+    /// For a ring, the angle in radians clockwise from the top. For a strip, from `0.0` to `1.0`.
+    Pose(f32),
+    /// The finger was lifted
+    Up,
+    Frame(Option<Duration>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PadGroupEvent {
+    Ring {
+        ring: u32,
+        event: SliderEvent,
+    },
+    Strip {
+        strip: u32,
+        event: SliderEvent,
+    },
+    /// The mode of the group changed, e.g. with the button in the middle of a touch ring
+    Mode(u32),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PadEvent {
+    /// The pad was used for the first time
+    Added,
+    Button {
+        button_idx: u32,
+        pressed: bool,
+        group: u32,
+    },
+    Group {
+        group: u32,
+        event: PadGroupEvent,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Event {
+    Tablet {
+        tablet: UINT,
+        event: TabletEvent,
+    },
+    /// `tool` is the cursor index of a [Tool]
+    Tool {
+        tool: UINT,
+        event: ToolEvent,
+    },
+    /// `pad` is the tablet number of a [Pad]
+    Pad {
+        pad: u8,
+        event: PadEvent,
+    },
+}
+
+/// Ring positions assumed when the driver reports no [EXT::AXES], as on Intuos tablets
+const DEFAULT_RING_POSITIONS: u32 = 72;
+/// Strip positions assumed when the driver reports no [EXT::AXES]
+const DEFAULT_STRIP_POSITIONS: u32 = 8;
+
+/// How to scale the packets of one device
+#[derive(Clone, Debug, Default)]
+struct DeviceScale {
+    orientation: OrientationScale,
+    pressure: PressureCurve,
+    tangent: PressureCurve,
+}
+
+/// Discovers the tablets, tools and pads of a driver and translates packets into [Event]s. See
+/// the [module documentation](self).
+#[derive(Clone, Debug, Default)]
+pub struct Adapter {
+    tablets: Vec<Tablet>,
+    tools: Vec<Tool>,
+    pads: Vec<Pad>,
+    extensions: Extensions,
+    tool_table: ToolTable,
+    scales: Vec<DeviceScale>,
+    pen: PenEventStream,
+    /// The tool in proximity and whether it is down
+    current: Option<(UINT, bool)>,
+    added_tools: HashSet<UINT>,
+    keys: HashMap<(u8, u8), bool>,
+    /// The last position of each `(tablet, control)` ring and strip
+    rings: HashMap<(u8, u8), DWORD>,
+    strips: HashMap<(u8, u8), DWORD>,
+    /// The last mode of each `(tablet, is_ring)`
+    modes: HashMap<(u8, bool), u8>,
+}
+
+impl Adapter {
+    /// Reads every device ([IFC::NDEVICES]), cursor ([IFC::NCURSORS]) and pad extension
+    pub fn load<A: WintabApi + ?Sized>(api: &A) -> Self {
+        let devices: UINT =
+            api.info_value(WTI::INTERFACE as UINT, IFC::NDEVICES as UINT).unwrap_or(0);
+        let tool_table = ToolTable::load(api);
+        let mut adapter = Self {
+            tablets: (0..devices)
+                .map(|device| Tablet {
+                    device,
+                    name: api.device_name(device),
+                    usb_id: api.device_pnp_id(device).as_deref().and_then(UsbId::from_pnp_id),
+                })
+                .collect(),
+            scales: (0..devices)
+                .map(|device| DeviceScale {
+                    orientation: OrientationScale::load(api, device).unwrap_or_default(),
+                    pressure: PressureCurve::load_normal(api, device).unwrap_or_default(),
+                    tangent: PressureCurve::load_tangent(api, device).unwrap_or_default(),
+                })
+                .collect(),
+            extensions: Extensions::load(api),
+            ..Self::default()
+        };
+        let cursors: UINT =
+            api.info_value(WTI::INTERFACE as UINT, IFC::NCURSORS as UINT).unwrap_or(0);
+        for cursor in 0..cursors {
+            let tablet = (0..devices)
+                .find(|&device| {
+                    let category = WTI::DEVICES as UINT + device;
+                    let first: UINT = api.info_value(category, DVC::FIRSTCSR as UINT).unwrap_or(0);
+                    let count: UINT = api.info_value(category, DVC::NCSRTYPES as UINT).unwrap_or(0);
+                    (first..first + count).contains(&cursor)
+                })
+                .unwrap_or(0);
+            let info = tool_table.cursor(cursor).copied().unwrap_or_default();
+            let packet_data = api
+                .info_value(WTI::CURSORS as UINT + cursor, CSR::PKTDATA as UINT)
+                .map(WTPKT::from_bits_retain);
+            adapter.tools.push(Tool {
+                cursor,
+                tablet,
+                name: api.cursor_name(cursor),
+                hardware_serial: Some(info.physical_id as u64).filter(|id| *id != 0),
+                wacom_id: info.cursor_type.map(u64::from),
+                tool_type: ToolType::from_tool(tool_table.cursor_tool(cursor), info.cursor_type),
+                axes: load_axes(api, tablet, packet_data),
+            });
+        }
+        adapter.tool_table = tool_table;
+        adapter
+    }
+
+    pub fn tablets(&self) -> &[Tablet] {
+        &self.tablets
+    }
+
+    pub fn tools(&self) -> &[Tool] {
+        &self.tools
+    }
+
+    /// The pads used so far
+    pub fn pads(&self) -> &[Pad] {
+        &self.pads
+    }
+
+    pub fn extensions(&self) -> &Extensions {
+        &self.extensions
+    }
+
+    pub fn tool(&self, cursor: UINT) -> Option<&Tool> {
+        self.tools.iter().find(|tool| tool.cursor == cursor)
+    }
+
+    /// [TabletEvent::Added] for every tablet
+    pub fn initial_events(&self) -> Vec<Event> {
+        self.tablets
+            .iter()
+            .map(|tablet| Event::Tablet { tablet: tablet.device, event: TabletEvent::Added })
+            .collect()
+    }
+
+    /// Appends the events caused by `packet`, ending with a [ToolEvent::Frame] if anything
+    /// changed
+    pub fn process(&mut self, packet: &Packet, events: &mut impl Extend<Event>) {
+        let mut pen_events = Vec::new();
+        self.pen.process(packet, &mut pen_events);
+        let time = Duration::from_millis({ packet.pkTime } as u64);
+        self.translate(pen_events, Some(time), events);
+    }
+
+    /// Ends proximity without a packet, e.g. when the context loses focus
+    pub fn leave(&mut self, events: &mut impl Extend<Event>) {
+        let mut pen_events = Vec::new();
+        self.pen.leave(&mut pen_events);
+        self.translate(pen_events, None, events);
+    }
+
+    fn translate(
+        &mut self,
+        pen_events: Vec<PenEvent>,
+        time: Option<Duration>,
+        events: &mut impl Extend<Event>,
+    ) {
+        // a proximity leave repeats the previous state, so the time comes from the packet
+        let Some(last) = pen_events.last().map(|event| *event.state()) else {
+            return;
+        };
+        for pen_event in pen_events {
+            let state = *pen_event.state();
+            match pen_event {
+                PenEvent::ProximityEnter(_) | PenEvent::CursorChanged(_) => {
+                    self.tool_out(events);
+                    self.tool_in(state.cursor, events);
+                }
+                PenEvent::ButtonPressed(button, _) | PenEvent::ButtonReleased(button, _) => {
+                    let pressed = matches!(pen_event, PenEvent::ButtonPressed(..));
+                    let event = ToolEvent::Button { button_id: button as u32, pressed };
+                    events.extend([Event::Tool { tool: state.cursor, event }]);
+                }
+                PenEvent::Down(_) | PenEvent::Move(_) | PenEvent::Up(_) | PenEvent::Hover(_) => {
+                    self.set_down(state.in_contact, events);
+                    let event = ToolEvent::Pose(self.pose(&state));
+                    events.extend([Event::Tool { tool: state.cursor, event }]);
+                }
+                PenEvent::ProximityLeave(_) => self.tool_out(events),
+            }
+        }
+        let event = ToolEvent::Frame(time);
+        events.extend([Event::Tool { tool: last.cursor, event }]);
+    }
+
+    fn tool_in(&mut self, cursor: UINT, events: &mut impl Extend<Event>) {
+        if self.tool(cursor).is_none() {
+            // a cursor the driver did not list; assume it belongs to the first tablet
+            self.tools.push(Tool {
+                cursor,
+                tool_type: ToolType::from_tool(self.tool_table.cursor_tool(cursor), None),
+                ..Tool::default()
+            });
+        }
+        if self.added_tools.insert(cursor) {
+            events.extend([Event::Tool { tool: cursor, event: ToolEvent::Added }]);
+        }
+        let tablet = self.tool(cursor).map_or(0, |tool| tool.tablet);
+        events.extend([Event::Tool { tool: cursor, event: ToolEvent::In { tablet } }]);
+        self.current = Some((cursor, false));
+    }
+
+    fn tool_out(&mut self, events: &mut impl Extend<Event>) {
+        self.set_down(false, events);
+        if let Some((cursor, _)) = self.current.take() {
+            events.extend([Event::Tool { tool: cursor, event: ToolEvent::Out }]);
+        }
+    }
+
+    fn set_down(&mut self, down: bool, events: &mut impl Extend<Event>) {
+        if let Some((cursor, was_down)) = &mut self.current {
+            if *was_down != down {
+                *was_down = down;
+                let event = if down { ToolEvent::Down } else { ToolEvent::Up };
+                events.extend([Event::Tool { tool: *cursor, event }]);
+            }
+        }
+    }
+
+    fn pose(&self, state: &PenState) -> Pose {
+        let tool = self.tool(state.cursor);
+        let axes = tool.map(|tool| tool.axes).unwrap_or_default();
+        let scale = tool.and_then(|tool| self.scales.get(tool.tablet as usize));
+        let scale = scale.cloned().unwrap_or_default();
+        let angles = scale.orientation.to_angles(state.orientation);
+        let tilt = angles.tilt();
+        Pose {
+            position: [state.position.x as f32, state.position.y as f32],
+            distance: axes.distance.map(|_| state.position.z as f32),
+            pressure: axes.pressure.map(|_| scale.pressure.normalise(state.pressure) as f32),
+            tilt: axes.tilt.map(|_| [tilt.x as f32, tilt.y as f32]),
+            roll: axes.roll.map(|_| angles.twist as f32),
+            slider: axes
+                .slider
+                .map(|_| scale.tangent.normalise(state.tangent_pressure) as f32 * 2.0 - 1.0),
+        }
+    }
+
+    /// Appends the events caused by an extension packet from a
+    /// [WT::PACKETEXT](crate::WT::PACKETEXT) message. Only the extensions found by
+    /// [Extensions::load] are considered, and only changes produce events.
+    pub fn process_ext(&mut self, packet: &PacketExt, events: &mut impl Extend<Event>) {
+        let time = Some(Duration::from_millis({ packet.pkBase }.nTime as u64));
+        let keys = packet.pkExpKeys;
+        let ring = packet.pkTouchRing;
+        let strip = packet.pkTouchStrip;
+        let mut changed = Vec::new();
+
+        if self.extensions.express_keys.is_some() {
+            let pressed = keys.nState != 0;
+            let previous = self.keys.insert((keys.nTablet, keys.nControl), pressed);
+            if previous.unwrap_or(false) != pressed {
+                let pad = self.pad(keys.nTablet, events);
+                let group = &mut pad.groups[0];
+                while group.buttons.len() <= keys.nControl as usize {
+                    group.buttons.push(group.buttons.len() as u32);
+                }
+                pad.total_buttons = pad.total_buttons.max(group.buttons.len() as u32);
+                let event =
+                    PadEvent::Button { button_idx: keys.nControl as u32, pressed, group: 0 };
+                changed.push((keys.nTablet, event));
+            }
+        }
+
+        for (is_ring, data) in [(true, ring), (false, strip)] {
+            let extension = match is_ring {
+                true => &self.extensions.touch_ring,
+                false => &self.extensions.touch_strip,
+            };
+            let Some(extension) = extension else {
+                continue;
+            };
+            let granularity = extension.axes.first().map(|axis| axis.axMax.max(1) as u32);
+            let positions = granularity.unwrap_or(match is_ring {
+                true => DEFAULT_RING_POSITIONS,
+                false => DEFAULT_STRIP_POSITIONS,
+            });
+
+            let previous_mode = self.modes.insert((data.nTablet, is_ring), data.nMode);
+            if previous_mode.is_some_and(|mode| mode != data.nMode) {
+                self.pad(data.nTablet, events);
+                let event = PadGroupEvent::Mode(data.nMode as u32);
+                changed.push((data.nTablet, PadEvent::Group { group: 0, event }));
+            }
+
+            let positions_seen = if is_ring { &mut self.rings } else { &mut self.strips };
+            let previous = positions_seen.insert((data.nTablet, data.nControl), data.nPosition);
+            if previous.unwrap_or(0) == data.nPosition {
+                continue;
+            }
+            let pad = self.pad(data.nTablet, events);
+            let controls =
+                if is_ring { &mut pad.groups[0].rings } else { &mut pad.groups[0].strips };
+            while controls.len() <= data.nControl as usize {
+                controls.push(PadControl { granularity });
+            }
+            // position 0 means the finger is up; touched positions count from 1
+            let slider_events = if data.nPosition == 0 {
+                [SliderEvent::Up, SliderEvent::Frame(time)]
+            } else {
+                let fraction = (data.nPosition - 1) as f32;
+                let pose = match is_ring {
+                    true => fraction / positions as f32 * TAU,
+                    false => (fraction / (positions.max(2) - 1) as f32).min(1.0),
+                };
+                [SliderEvent::Pose(pose), SliderEvent::Frame(time)]
+            };
+            let control = data.nControl as u32;
+            changed.extend(slider_events.map(|event| {
+                let event = match is_ring {
+                    true => PadGroupEvent::Ring { ring: control, event },
+                    false => PadGroupEvent::Strip { strip: control, event },
+                };
+                (data.nTablet, PadEvent::Group { group: 0, event })
+            }));
+        }
+
+        events.extend(changed.into_iter().map(|(pad, event)| Event::Pad { pad, event }));
+    }
+
+    /// The pad of `tablet`, emitting [PadEvent::Added] if it is new
+    fn pad(&mut self, tablet: u8, events: &mut impl Extend<Event>) -> &mut Pad {
+        let index = match self.pads.iter().position(|pad| pad.tablet == tablet) {
+            Some(index) => index,
+            None => {
+                self.pads.push(Pad { tablet, total_buttons: 0, groups: vec![PadGroup::default()] });
+                events.extend([Event::Pad { pad: tablet, event: PadEvent::Added }]);
+                self.pads.len() - 1
+            }
+        };
+        &mut self.pads[index]
+    }
+}
+
+/// The axes of a cursor on `device`. An axis is available when the device reports a range for
+/// it and, if the cursor reports [CSR::PKTDATA], the cursor supports it.
+fn load_axes<A: WintabApi + ?Sized>(api: &A, device: UINT, packet_data: Option<WTPKT>) -> Axes {
+    let category = WTI::DEVICES as UINT + device;
+    let supports = |item: WTPKT| packet_data.is_none_or(|data| data.contains(item));
+    let range = |axis: &AXIS| (axis.axMax > axis.axMin).then(|| (axis.axMax - axis.axMin) as u32);
+    let axis = |index: DVC, item: WTPKT| {
+        api.info_value::<AXIS>(category, index as UINT).filter(|_| supports(item))
+    };
+    let orientation = api
+        .info_value::<[AXIS; 3]>(category, DVC::ORIENTATION as UINT)
+        .filter(|_| supports(WTPKT::ORIENTATION));
+    let info = |limits: (f32, f32), granularity: Option<u32>| {
+        granularity
+            .map(|granularity| AxisInfo { limits: Some(limits), granularity: Some(granularity) })
+    };
+    Axes {
+        pressure: axis(DVC::NPRESSURE, WTPKT::NORMAL_PRESSURE)
+            .and_then(|axis| info((0.0, 1.0), range(&axis))),
+        tilt: orientation.and_then(|axes| info((-FRAC_PI_2, FRAC_PI_2), range(&axes[1]))),
+        distance: axis(DVC::Z, WTPKT::Z)
+            .and_then(|axis| info((axis.axMin as f32, axis.axMax as f32), range(&axis))),
+        roll: orientation.and_then(|axes| info((0.0, TAU), range(&axes[2]))),
+        slider: axis(DVC::TPRESSURE, WTPKT::TANGENT_PRESSURE)
+            .and_then(|axis| info((-1.0, 1.0), range(&axis))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        test_support::FakeWintab, ExpKeysData, ExtensionBase, Orientation, SliderData, LOGCONTEXTW,
+        TPS, XYZ,
+    };
+    use windows::Win32::Foundation::HWND;
+
+    fn axis(min: i32, max: i32) -> AXIS {
+        AXIS { axMin: min, axMax: max, ..Default::default() }
+    }
+
+    /// One Intuos style tablet with a lens cursor, a pen and its eraser, ExpressKeys and a touch
+    /// ring with 72 positions
+    fn fake_driver() -> FakeWintab {
+        let api = FakeWintab::new();
+        let interface = WTI::INTERFACE as UINT;
+        api.set_info_string(interface, IFC::WINTABID as UINT, "Wacom Tablet");
+        api.set_info(interface, IFC::NDEVICES as UINT, &[1 as UINT]);
+        api.set_info(interface, IFC::NCURSORS as UINT, &[3 as UINT]);
+        api.set_info(interface, IFC::NEXTENSIONS as UINT, &[3 as UINT]);
+
+        let device = WTI::DEVICES as UINT;
+        api.set_info_string(device, DVC::NAME as UINT, "Intuos Pro M");
+        api.set_info_string(device, DVC::PNPID as UINT, "USB\\VID_056A&PID_0357&MI_00");
+        api.set_info(device, DVC::FIRSTCSR as UINT, &[0 as UINT]);
+        api.set_info(device, DVC::NCSRTYPES as UINT, &[3 as UINT]);
+        api.set_info(device, DVC::NPRESSURE as UINT, &[axis(0, 8191)]);
+        api.set_info(device, DVC::Z as UINT, &[axis(-1023, 1023)]);
+        api.set_info(
+            device,
+            DVC::ORIENTATION as UINT,
+            &[axis(0, 3599), axis(-900, 900), axis(0, 0)],
+        );
+
+        let cursors: [(&str, UINT, DWORD, WTPKT); 3] = [
+            ("Lens", 0x0006, 0, WTPKT::X | WTPKT::Y | WTPKT::Z),
+            ("Pressure Stylus", 0x0802, 0x1234, WTPKT::all()),
+            ("Eraser", 0x080A, 0x1234, WTPKT::NORMAL_PRESSURE | WTPKT::ORIENTATION),
+        ];
+        for (index, (name, cursor_type, physical_id, packet_data)) in
+            cursors.into_iter().enumerate()
+        {
+            let category = WTI::CURSORS as UINT + index as UINT;
+            api.set_info_string(category, CSR::NAME as UINT, name);
+            api.set_info(category, CSR::TYPE as UINT, &[cursor_type]);
+            api.set_info(category, CSR::PHYSID as UINT, &[physical_id]);
+            api.set_info(category, CSR::PKTDATA as UINT, &[packet_data.bits()]);
+        }
+
+        let extensions: [(WTX, DWORD, Option<AXIS>); 3] = [
+            (WTX::OBT, 0, None),
+            (WTX::EXPKEYS2, 0x0002_0000, None),
+            (WTX::TOUCHRING, 0x0004_0000, Some(axis(0, 72))),
+        ];
+        for (index, (tag, mask, axes)) in extensions.into_iter().enumerate() {
+            let category = WTI::EXTENSIONS as UINT + index as UINT;
+            api.set_info(category, EXT::TAG as UINT, &[tag as UINT]);
+            api.set_info(category, EXT::MASK as UINT, &[mask]);
+            if let Some(axes) = axes {
+                api.set_info(category, EXT::AXES as UINT, &[axes]);
+            }
+        }
+        api
+    }
+
+    fn packet(cursor: UINT, x: i32, pressure: UINT, buttons: u32, time: DWORD) -> Packet {
+        Packet {
+            pkCursor: cursor,
+            pkXYZ: XYZ { x, y: 10, z: 0 },
+            pkNormalPressure: pressure,
+            pkButtons: crate::Bitmask(buttons),
+            pkOrientation: Orientation { orAzimuth: 0, orAltitude: 900, orTwist: 0 },
+            pkTime: time,
+            ..Default::default()
+        }
+    }
+
+    fn tool(tool: UINT, event: ToolEvent) -> Event {
+        Event::Tool { tool, event }
+    }
+
+    fn frame(tool: UINT, time: u64) -> Event {
+        Event::Tool { tool, event: ToolEvent::Frame(Some(Duration::from_millis(time))) }
+    }
+
+    fn pose(x: f32, pressure: f32) -> ToolEvent {
+        ToolEvent::Pose(Pose {
+            position: [x, 10.0],
+            distance: Some(0.0),
+            pressure: Some(pressure),
+            tilt: Some([0.0, 0.0]),
+            ..Default::default()
+        })
+    }
+
+    /// Rounds tilt to hide the error of an upright pen, which is `cos(π/2)` rather than zero
+    fn rounded(mut events: Vec<Event>) -> Vec<Event> {
+        for event in &mut events {
+            if let Event::Tool { event: ToolEvent::Pose(Pose { tilt: Some(tilt), .. }), .. } = event
+            {
+                *tilt = tilt.map(|angle| (angle * 1e6).round() / 1e6);
+            }
+        }
+        events
+    }
+
+    #[test]
+    fn test_discovery() {
+        let adapter = Adapter::load(&fake_driver());
+        assert_eq!(
+            adapter.tablets(),
+            [Tablet {
+                device: 0,
+                name: Some("Intuos Pro M".into()),
+                usb_id: Some(UsbId { vid: 0x056A, pid: 0x0357 }),
+            }]
+        );
+        assert_eq!(
+            adapter.initial_events(),
+            [Event::Tablet { tablet: 0, event: TabletEvent::Added }]
+        );
+
+        let types: Vec<_> = adapter.tools().iter().map(|tool| tool.tool_type).collect();
+        assert_eq!(types, [Some(ToolType::Lens), Some(ToolType::Pen), Some(ToolType::Eraser)]);
+        let pen = adapter.tool(1).unwrap();
+        assert_eq!(pen.name.as_deref(), Some("Pressure Stylus"));
+        assert_eq!(pen.hardware_serial, Some(0x1234));
+        assert_eq!(pen.wacom_id, Some(0x0802));
+        assert_eq!(
+            pen.axes.pressure,
+            Some(AxisInfo { limits: Some((0.0, 1.0)), granularity: Some(8191) })
+        );
+        assert_eq!(pen.axes.tilt.unwrap().granularity, Some(1800));
+        assert_eq!(pen.axes.distance.unwrap().limits, Some((-1023.0, 1023.0)));
+        // the device reports no twist range or tangent pressure
+        assert_eq!(pen.axes.roll, None);
+        assert_eq!(pen.axes.slider, None);
+        // limited by the cursors' packet data
+        let lens = adapter.tool(0).unwrap().axes;
+        assert!(lens.pressure.is_none() && lens.tilt.is_none() && lens.distance.is_some());
+        assert!(adapter.tool(2).unwrap().axes.distance.is_none());
+
+        let extensions = adapter.extensions();
+        assert_eq!(extensions.express_keys.as_ref().unwrap().index, 1);
+        assert_eq!(extensions.touch_ring.as_ref().unwrap().axes, [axis(0, 72)]);
+        assert!(extensions.touch_strip.is_none());
+        assert_eq!(extensions.packet_mask(), WTPKT::from_bits_retain(0x0006_0000));
+        assert!(adapter.pads().is_empty());
+    }
+
+    #[test]
+    fn test_usb_id() {
+        assert_eq!(
+            UsbId::from_pnp_id("hid\\vid_256c&pid_006d"),
+            Some(UsbId { vid: 0x256C, pid: 0x006D })
+        );
+        assert_eq!(UsbId::from_pnp_id("WACf004"), None);
+        assert_eq!(UsbId::from_pnp_id("VID_05"), None);
+    }
+
+    #[test]
+    fn test_tool_events_from_driver_packets() {
+        let api = fake_driver();
+        let mut adapter = Adapter::load(&api);
+        let context =
+            crate::Context::open(&api, HWND(1), &mut LOGCONTEXTW::default(), true).unwrap();
+        api.push(packet(1, 100, 0, 0, 10));
+        api.push(packet(1, 110, 8191, 0b01, 20));
+        api.push(packet(1, 120, 0, 0b10, 30));
+        api.push(Packet { pkStatus: TPS::PROXIMITY, ..packet(1, 120, 0, 0, 40) });
+        let mut events = Vec::new();
+        let mut reader = crate::PacketReader::new();
+        for packet in reader.drain(&context) {
+            adapter.process(packet, &mut events);
+        }
+        let button = |button_id, pressed| tool(1, ToolEvent::Button { button_id, pressed });
+        assert_eq!(
+            rounded(events),
+            [
+                tool(1, ToolEvent::Added),
+                tool(1, ToolEvent::In { tablet: 0 }),
+                tool(1, pose(100.0, 0.0)),
+                frame(1, 10),
+                button(0, true),
+                tool(1, ToolEvent::Down),
+                tool(1, pose(110.0, 1.0)),
+                frame(1, 20),
+                button(0, false),
+                button(1, true),
+                tool(1, ToolEvent::Up),
+                tool(1, pose(120.0, 0.0)),
+                frame(1, 30),
+                button(1, false),
+                tool(1, ToolEvent::Out),
+                frame(1, 40),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_switching_tools() {
+        let mut adapter = Adapter::load(&fake_driver());
+        let mut events = Vec::new();
+        adapter.process(&packet(1, 0, 4000, 0, 1), &mut events);
+        events.clear();
+        // flipped to the eraser without leaving proximity
+        adapter.process(&Packet { pkStatus: TPS::INVERT, ..packet(2, 0, 4000, 0, 2) }, &mut events);
+        assert_eq!(
+            events[..4],
+            [
+                tool(1, ToolEvent::Up),
+                tool(1, ToolEvent::Out),
+                tool(2, ToolEvent::Added),
+                tool(2, ToolEvent::In { tablet: 0 }),
+            ]
+        );
+        assert_eq!(events[4], tool(2, ToolEvent::Down));
+        // the eraser does not report distance
+        assert!(matches!(
+            events[5],
+            Event::Tool { event: ToolEvent::Pose(Pose { distance: None, .. }), .. }
+        ));
+        events.clear();
+        adapter.leave(&mut events);
+        assert_eq!(events[0], tool(2, ToolEvent::Up));
+        assert_eq!(events[2..], [tool(2, ToolEvent::Out), tool(2, ToolEvent::Frame(None))]);
+        // coming back is not a new tool
+        events.clear();
+        adapter.process(&packet(1, 0, 0, 0, 3), &mut events);
+        assert_eq!(events[0], tool(1, ToolEvent::In { tablet: 0 }));
+    }
+
+    #[test]
+    fn test_pad_events() {
+        let mut adapter = Adapter::load(&fake_driver());
+        let ext = |control, state, ring_position, mode| PacketExt {
+            pkBase: ExtensionBase { nTime: 5, ..Default::default() },
+            pkExpKeys: ExpKeysData { nControl: control, nState: state, ..Default::default() },
+            pkTouchRing: SliderData { nPosition: ring_position, nMode: mode, ..Default::default() },
+            // the strip extension is not supported, so this is ignored
+            pkTouchStrip: SliderData { nPosition: 3, ..Default::default() },
+        };
+        let group = |event| Event::Pad { pad: 0, event: PadEvent::Group { group: 0, event } };
+        let ring = |event| group(PadGroupEvent::Ring { ring: 0, event });
+        let time = Some(Duration::from_millis(5));
+
+        let mut events = Vec::new();
+        adapter.process_ext(&ext(3, 1, 0, 0), &mut events);
+        adapter.process_ext(&ext(3, 0, 0, 0), &mut events);
+        adapter.process_ext(&ext(3, 0, 19, 0), &mut events);
+        adapter.process_ext(&ext(3, 0, 0, 1), &mut events);
+        assert_eq!(
+            events,
+            [
+                Event::Pad { pad: 0, event: PadEvent::Added },
+                Event::Pad {
+                    pad: 0,
+                    event: PadEvent::Button { button_idx: 3, pressed: true, group: 0 }
+                },
+                Event::Pad {
+                    pad: 0,
+                    event: PadEvent::Button { button_idx: 3, pressed: false, group: 0 }
+                },
+                ring(SliderEvent::Pose(TAU / 4.0)),
+                ring(SliderEvent::Frame(time)),
+                group(PadGroupEvent::Mode(1)),
+                ring(SliderEvent::Up),
+                ring(SliderEvent::Frame(time)),
+            ]
+        );
+        let pad = &adapter.pads()[0];
+        assert_eq!(pad.total_buttons, 4);
+        assert_eq!(pad.groups[0].buttons, [0, 1, 2, 3]);
+        assert_eq!(pad.groups[0].rings, [PadControl { granularity: Some(72) }]);
+        assert!(pad.groups[0].strips.is_empty());
+    }
+
+    #[test]
+    fn test_pad_added_by_mode_change() {
+        let mut adapter = Adapter::load(&fake_driver());
+        let ext = |mode| PacketExt {
+            pkTouchRing: SliderData { nMode: mode, ..Default::default() },
+            ..Default::default()
+        };
+        let mut events = Vec::new();
+        adapter.process_ext(&ext(0), &mut events);
+        assert!(events.is_empty());
+        adapter.process_ext(&ext(2), &mut events);
+        let mode = PadEvent::Group { group: 0, event: PadGroupEvent::Mode(2) };
+        assert_eq!(
+            events,
+            [Event::Pad { pad: 0, event: PadEvent::Added }, Event::Pad { pad: 0, event: mode }]
+        );
+        assert_eq!(adapter.pads().len(), 1);
+    }
+}
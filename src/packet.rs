@@ -176,6 +176,11 @@ impl Default for Packet {
     }
 }
 
+// pkContext is only an identifying handle value; it is never dereferenced, so packets can be
+// sent to other threads like any other plain data.
+unsafe impl Send for Packet {}
+unsafe impl Sync for Packet {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -197,4 +202,40 @@ mod tests {
             2usize
         );
     }
+
+    /// A packet as written by a 64 bit windows driver, decoded on whatever the host is.
+    #[cfg(target_pointer_width = "64")]
+    #[test]
+    fn test_decode_captured_packet() {
+        let mut bytes: Vec<u8> = Vec::new();
+        bytes.extend(0x0000_0000_0012_3456u64.to_le_bytes()); // pkContext
+        bytes.extend(TPS::INVERT.bits().to_le_bytes()); // pkStatus
+        bytes.extend(0xFFFF_FFF0u32.to_le_bytes()); // pkTime
+        bytes.extend(WTPKT::X.bits().to_le_bytes()); // pkChanged
+        bytes.extend(41u32.to_le_bytes()); // pkSerialNumber
+        bytes.extend(2u32.to_le_bytes()); // pkCursor
+        bytes.extend(0b101u32.to_le_bytes()); // pkButtons
+        for value in [1920i32, -5, 0] {
+            bytes.extend(value.to_le_bytes()); // pkXYZ
+        }
+        bytes.extend(8191u32.to_le_bytes()); // pkNormalPressure
+        bytes.extend(0u32.to_le_bytes()); // pkTangentPressure
+        for value in [900i32, 450, -1, 10, 20, 30] {
+            bytes.extend(value.to_le_bytes()); // pkOrientation, pkRotation
+        }
+        assert_eq!(bytes.len(), size_of::<Packet>());
+
+        let packet: Packet = unsafe { std::ptr::read_unaligned(bytes.as_ptr() as *const Packet) };
+        assert_eq!(packet.pkContext as usize, 0x12_3456);
+        assert_eq!({ packet.pkStatus }, TPS::INVERT);
+        assert_eq!({ packet.pkTime }, 0xFFFF_FFF0);
+        assert_eq!({ packet.pkChanged }, WTPKT::X);
+        assert_eq!({ packet.pkSerialNumber }, 41);
+        assert_eq!({ packet.pkCursor }, 2);
+        assert_eq!({ packet.pkButtons }, Bitmask(0b101));
+        assert_eq!({ packet.pkXYZ }, XYZ { x: 1920, y: -5, z: 0 });
+        assert_eq!({ packet.pkNormalPressure }, 8191);
+        assert_eq!({ packet.pkOrientation }, Orientation { orAzimuth: 900, orAltitude: 450, orTwist: -1 });
+        assert_eq!({ packet.pkRotation }, Rotation { roPitch: 10, roRoll: 20, roYaw: 30 });
+    }
 }
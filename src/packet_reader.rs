@@ -0,0 +1,159 @@
+use crate::{
+    c_type_aliases::INT,
+    Context, Packet, WintabApi,
+};
+
+/// Drains every queued packet from a [Context] into a reusable buffer.
+///
+/// This replaces the usual polling dance of calling `WTQueuePacketsEx` then `WTDataGet` into a
+/// fixed size array, which silently leaves packets behind when more are queued than fit.
+/// The buffer grows in chunks of [PacketReader::chunk_size] packets as needed and is never
+/// shrunk, so once it has grown large enough for the busiest frame, reading does not allocate.
+///
+/// ```ignore
+/// let mut reader = PacketReader::new();
+/// // each frame
+/// for packet in reader.drain(&context) {
+///     println!("{:?}", packet.pkXYZ);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct PacketReader {
+    buffer: Vec<Packet>,
+    filled: usize,
+    chunk_size: usize,
+}
+
+impl Default for PacketReader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl PacketReader {
+    /// The default number of packets the buffer grows by
+    pub const DEFAULT_CHUNK_SIZE: usize = 128;
+
+    pub fn new() -> Self {
+        Self::with_chunk_size(Self::DEFAULT_CHUNK_SIZE)
+    }
+
+    /// Creates a reader whose buffer grows by `chunk_size` packets at a time.
+    /// This is also the maximum number of packets requested from the driver per `WTDataGet`
+    /// call until the buffer has grown.
+    pub fn with_chunk_size(chunk_size: usize) -> Self {
+        Self {
+            buffer: Vec::new(),
+            filled: 0,
+            chunk_size: chunk_size.max(1),
+        }
+    }
+
+    /// See [PacketReader::with_chunk_size]
+    pub fn chunk_size(&self) -> usize {
+        self.chunk_size
+    }
+
+    /// The number of packets the buffer can hold without growing
+    pub fn capacity(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// The packets returned by the most recent call to [PacketReader::drain]
+    pub fn packets(&self) -> &[Packet] {
+        &self.buffer[..self.filled]
+    }
+
+    /// Removes every packet currently queued in `context` and returns them in serial order.
+    ///
+    /// The range of serial numbers is read once with `WTQueuePacketsEx`, then read with as many
+    /// `WTDataGet` calls as needed. Serial numbers are compared with wrapping arithmetic so a
+    /// range which wraps past [UINT::MAX](crate::UINT) is handled.
+    pub fn drain<A: WintabApi>(&mut self, context: &Context<A>) -> std::slice::Iter<'_, Packet> {
+        self.filled = 0;
+        let Some((mut oldest, newest)) = context.queue_extent() else {
+            return self.packets().iter();
+        };
+        loop {
+            if self.filled == self.buffer.len() {
+                self.buffer.resize(self.buffer.len() + self.chunk_size, Packet::default());
+            }
+            let space = (self.buffer.len() - self.filled).min(INT::MAX as usize);
+            let mut copied: INT = 0;
+            unsafe {
+                context.api().data_get(
+                    context.handle(),
+                    oldest,
+                    newest,
+                    space as INT,
+                    self.buffer[self.filled..].as_mut_ptr() as *mut std::ffi::c_void,
+                    &mut copied,
+                );
+            }
+            let copied = (copied.max(0) as usize).min(space);
+            if copied == 0 {
+                break;
+            }
+            self.filled += copied;
+            let last_serial = self.buffer[self.filled - 1].pkSerialNumber;
+            if last_serial == newest {
+                break;
+            }
+            oldest = last_serial.wrapping_add(1);
+        }
+        self.packets().iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FakeWintab, LOGCONTEXTW, UINT};
+    use windows::Win32::Foundation::HWND;
+
+    fn serials(packets: std::slice::Iter<'_, Packet>) -> Vec<UINT> {
+        packets.map(|packet| packet.pkSerialNumber).collect()
+    }
+
+    #[test]
+    fn test_drain_in_chunks() {
+        let api = FakeWintab::new();
+        let context = Context::open(&api, HWND(1), &mut LOGCONTEXTW::default(), true).unwrap();
+        let mut reader = PacketReader::with_chunk_size(4);
+
+        assert_eq!(reader.drain(&context).count(), 0);
+
+        for _ in 0..10 {
+            api.push(Packet::default());
+        }
+        assert_eq!(serials(reader.drain(&context)), (0..10).collect::<Vec<_>>());
+        assert_eq!(reader.capacity(), 12);
+        assert_eq!(api.state().data_get_calls, vec![4, 4, 4]);
+        assert!(api.state().queue.is_empty());
+
+        // steady state: no growth when fewer packets arrive
+        api.state().data_get_calls.clear();
+        for _ in 0..5 {
+            api.push(Packet::default());
+        }
+        assert_eq!(serials(reader.drain(&context)), (10..15).collect::<Vec<_>>());
+        assert_eq!(reader.capacity(), 12);
+        assert_eq!(api.state().data_get_calls, vec![12]);
+    }
+
+    #[test]
+    fn test_drain_across_serial_wraparound() {
+        let api = FakeWintab::new();
+        api.state().next_serial = UINT::MAX - 2;
+        let context = Context::open(&api, HWND(1), &mut LOGCONTEXTW::default(), true).unwrap();
+        let mut reader = PacketReader::with_chunk_size(2);
+        for _ in 0..6 {
+            api.push(Packet::default());
+        }
+        assert_eq!(
+            serials(reader.drain(&context)),
+            vec![UINT::MAX - 2, UINT::MAX - 1, UINT::MAX, 0, 1, 2]
+        );
+        assert_eq!(reader.packets().len(), 6);
+    }
+}
@@ -0,0 +1,310 @@
+use std::{
+    collections::VecDeque,
+    pin::Pin,
+    sync::{mpsc::TrySendError, Arc, Mutex},
+    task::{Context as TaskContext, Poll, Waker},
+    time::Duration,
+};
+
+use futures_core::Stream;
+
+use crate::{
+    Context, ContextError, Packet, PacketPump, PenEvent, PenEventStream, PumpNotifier,
+    PumpSender, WintabApi,
+};
+
+/// A bounded queue between the pump thread and the stream
+struct Shared<T> {
+    queue: VecDeque<T>,
+    capacity: usize,
+    waker: Option<Waker>,
+    /// The pump thread has stopped sending
+    sender_closed: bool,
+    /// The stream has been dropped
+    receiver_closed: bool,
+}
+
+struct Channel<T> {
+    shared: Mutex<Shared<T>>,
+}
+
+/// The pump thread's end of the [Channel]
+struct Sender<T>(Arc<Channel<T>>);
+
+impl<T: Send> PumpSender<T> for Sender<T> {
+    fn try_send(&self, item: T) -> Result<(), TrySendError<T>> {
+        let mut shared = self.0.shared.lock().unwrap();
+        if shared.receiver_closed {
+            return Err(TrySendError::Disconnected(item));
+        }
+        if shared.queue.len() >= shared.capacity {
+            return Err(TrySendError::Full(item));
+        }
+        shared.queue.push_back(item);
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+        Ok(())
+    }
+
+    /// [PacketStream::poll_next] unparks the pump when it makes space
+    fn full_retry(&self) -> Option<Duration> {
+        None
+    }
+}
+
+impl<T> Drop for Sender<T> {
+    fn drop(&mut self) {
+        let mut shared = self.0.shared.lock().unwrap();
+        shared.sender_closed = true;
+        if let Some(waker) = shared.waker.take() {
+            waker.wake();
+        }
+    }
+}
+
+/// An asynchronous [Stream] of packets, or of anything decoded from them, read by a
+/// [PacketPump] on a background thread.
+///
+/// At most `capacity` items are buffered; when the stream is not polled the pump thread stops
+/// reading, and packets wait in the driver's queue. Dropping the stream cancels it: the pump is
+/// stopped and the context is closed.
+///
+/// ```ignore
+/// let api = Arc::new(unsafe { LibloadingApi::load()? });
+/// let mut packets = PacketStream::spawn(
+///     move || Context::open(api, hwnd, &mut log_context, true),
+///     64,
+///     Duration::from_millis(2),
+/// )?;
+/// while let Some(packet) = packets.next().await {
+///     // ...
+/// }
+/// ```
+pub struct PacketStream<T> {
+    channel: Arc<Channel<T>>,
+    pump: Option<PacketPump>,
+}
+
+impl PacketStream<Packet> {
+    /// Streams every [Packet] read from the context opened by `open`. The queue is read whenever
+    /// the [PacketStream::notifier] is notified, and at least every `interval`.
+    pub fn spawn<A: WintabApi + 'static>(
+        open: impl FnOnce() -> Result<Context<A>, ContextError> + Send + 'static,
+        capacity: usize,
+        interval: Duration,
+    ) -> Result<Self, ContextError> {
+        Self::spawn_with(open, capacity, interval, |packet, items| items.push(packet.clone()))
+    }
+}
+
+impl PacketStream<PenEvent> {
+    /// Like [PacketStream::spawn], but streams the [PenEvent]s produced by a [PenEventStream]
+    pub fn spawn_pen_events<A: WintabApi + 'static>(
+        open: impl FnOnce() -> Result<Context<A>, ContextError> + Send + 'static,
+        capacity: usize,
+        interval: Duration,
+    ) -> Result<Self, ContextError> {
+        let mut stream = PenEventStream::new();
+        Self::spawn_with(open, capacity, interval, move |packet, items| {
+            stream.process(packet, items)
+        })
+    }
+}
+
+impl<T: Send + 'static> PacketStream<T> {
+    /// Streams whatever `decode` appends to the `Vec` it is given for each packet; see
+    /// [PacketPump::spawn_with]
+    pub fn spawn_with<A, F>(
+        open: impl FnOnce() -> Result<Context<A>, ContextError> + Send + 'static,
+        capacity: usize,
+        interval: Duration,
+        decode: F,
+    ) -> Result<Self, ContextError>
+    where
+        A: WintabApi + 'static,
+        F: FnMut(&Packet, &mut Vec<T>) + Send + 'static,
+    {
+        let channel = Arc::new(Channel {
+            shared: Mutex::new(Shared {
+                queue: VecDeque::new(),
+                capacity: capacity.max(1),
+                waker: None,
+                sender_closed: false,
+                receiver_closed: false,
+            }),
+        });
+        let pump = PacketPump::spawn_with(open, Sender(channel.clone()), interval, decode)?;
+        Ok(Self { channel, pump: Some(pump) })
+    }
+}
+
+impl<T> PacketStream<T> {
+    /// A handle that wakes the pump thread; see [PumpNotifier]
+    pub fn notifier(&self) -> Option<PumpNotifier> {
+        self.pump.as_ref().map(PacketPump::notifier)
+    }
+}
+
+impl<T> Stream for PacketStream<T> {
+    type Item = T;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut TaskContext<'_>) -> Poll<Option<T>> {
+        let mut shared = self.channel.shared.lock().unwrap();
+        let was_full = shared.queue.len() >= shared.capacity;
+        if let Some(item) = shared.queue.pop_front() {
+            if was_full {
+                if let Some(pump) = &self.pump {
+                    pump.notifier().notify();
+                }
+            }
+            return Poll::Ready(Some(item));
+        }
+        if shared.sender_closed {
+            return Poll::Ready(None);
+        }
+        shared.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.channel.shared.lock().unwrap().queue.len(), None)
+    }
+}
+
+impl<T> Drop for PacketStream<T> {
+    fn drop(&mut self) {
+        // the pump thread gives up if it is waiting for space, then stops
+        self.channel.shared.lock().unwrap().receiver_closed = true;
+        drop(self.pump.take());
+    }
+}
+
+impl<T> std::fmt::Debug for PacketStream<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PacketStream").field("pump", &self.pump).finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FakeWintab, LOGCONTEXTW, TPS};
+    use std::{future::poll_fn, sync::mpsc, task::Wake, thread, time::Instant};
+    use windows::Win32::Foundation::HWND;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    type FakeContext = Context<Arc<FakeWintab>>;
+
+    /// Reports each wake over a channel, so tests can wait for the pump instead of sleeping
+    struct ChannelWaker(Mutex<mpsc::Sender<()>>);
+
+    impl Wake for ChannelWaker {
+        fn wake(self: Arc<Self>) {
+            let _ = self.0.lock().unwrap().send(());
+        }
+    }
+
+    /// Polls the stream directly, waiting for a wake whenever it is pending
+    fn next_blocking<T>(stream: &mut PacketStream<T>) -> Option<T> {
+        let (sender, woken) = mpsc::channel();
+        let waker = Waker::from(Arc::new(ChannelWaker(Mutex::new(sender))));
+        let mut cx = TaskContext::from_waker(&waker);
+        loop {
+            match Pin::new(&mut *stream).poll_next(&mut cx) {
+                Poll::Ready(item) => return item,
+                Poll::Pending => woken.recv_timeout(TIMEOUT).expect("the stream was not woken"),
+            }
+        }
+    }
+
+    /// Waits until the pump has read every queued packet and filled the stream to `capacity`,
+    /// after which it can only wait for space
+    fn wait_until_full<T>(api: &FakeWintab, stream: &PacketStream<T>, capacity: usize) {
+        let start = Instant::now();
+        while !(api.state().queue.is_empty() && stream.size_hint().0 == capacity) {
+            assert!(start.elapsed() < TIMEOUT, "the pump did not fill the stream");
+            thread::yield_now();
+        }
+    }
+
+    fn open(api: &Arc<FakeWintab>) -> impl FnOnce() -> Result<FakeContext, ContextError> {
+        let api = api.clone();
+        move || Context::open(api, HWND(1), &mut LOGCONTEXTW::default(), true)
+    }
+
+    async fn next<T>(stream: &mut PacketStream<T>) -> Option<T> {
+        poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_stream_packets() {
+        let api = Arc::new(FakeWintab::new());
+        let mut stream = PacketStream::spawn(open(&api), 8, Duration::from_millis(1)).unwrap();
+        for _ in 0..3 {
+            api.push(Packet::default());
+        }
+        stream.notifier().unwrap().notify();
+        for serial in 0..3 {
+            assert_eq!(next(&mut stream).await.unwrap().pkSerialNumber, serial);
+        }
+        drop(stream);
+        assert!(api.state().open.is_empty());
+        assert_eq!(api.state().closed.len(), 1);
+    }
+
+    #[test]
+    fn test_backpressure() {
+        let api = Arc::new(FakeWintab::new());
+        let mut stream = PacketStream::spawn(open(&api), 2, Duration::from_millis(1)).unwrap();
+        for _ in 0..10 {
+            api.push(Packet::default());
+        }
+        // the pump reads the whole queue but can only hand over two packets until polled
+        wait_until_full(&api, &stream, 2);
+        assert_eq!(stream.size_hint().0, 2);
+        for serial in 0..10 {
+            assert_eq!(next_blocking(&mut stream).unwrap().pkSerialNumber, serial);
+        }
+    }
+
+    #[test]
+    fn test_cancel_while_pump_is_blocked() {
+        let api = Arc::new(FakeWintab::new());
+        let stream = PacketStream::spawn(open(&api), 1, Duration::from_millis(1)).unwrap();
+        for _ in 0..5 {
+            api.push(Packet::default());
+        }
+        wait_until_full(&api, &stream, 1);
+        // the pump thread is waiting for space; dropping must not deadlock
+        let (dropped_sender, dropped) = mpsc::channel();
+        thread::spawn(move || {
+            drop(stream);
+            dropped_sender.send(()).unwrap();
+        });
+        dropped.recv_timeout(TIMEOUT).expect("dropping the stream deadlocked");
+        assert_eq!(api.state().closed.len(), 1);
+    }
+
+    #[tokio::test(flavor = "current_thread")]
+    async fn test_pen_events_and_end_of_stream() {
+        let api = Arc::new(FakeWintab::new());
+        api.state().fail_open = true;
+        let failed = PacketStream::spawn(open(&api), 4, Duration::from_millis(1));
+        assert_eq!(failed.unwrap_err(), ContextError::OpenFailed);
+        api.state().fail_open = false;
+
+        let mut stream =
+            PacketStream::spawn_pen_events(open(&api), 4, Duration::from_millis(1)).unwrap();
+        api.push(Packet::default());
+        api.push(Packet { pkStatus: TPS::PROXIMITY, ..Default::default() });
+        assert!(matches!(next(&mut stream).await, Some(PenEvent::ProximityEnter(_))));
+        assert!(matches!(next(&mut stream).await, Some(PenEvent::Hover(_))));
+        assert!(matches!(next(&mut stream).await, Some(PenEvent::ProximityLeave(_))));
+
+        // the stream ends once the pump has stopped
+        stream.pump.as_mut().unwrap().stop_and_join().unwrap();
+        assert!(next(&mut stream).await.is_none());
+    }
+}
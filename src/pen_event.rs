@@ -0,0 +1,341 @@
+use std::{borrow::Borrow, collections::VecDeque};
+
+use crate::{
+    c_type_aliases::{DWORD, LONG, UINT},
+    Bitmask, Orientation, Packet, Rotation, TPS, XYZ,
+};
+
+/// The state of the pen as of a [PenEvent]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PenState {
+    /// See [Packet::pkXYZ]
+    pub position: XYZ<LONG>,
+    /// See [Packet::pkNormalPressure]
+    pub pressure: UINT,
+    /// See [Packet::pkTangentPressure]
+    pub tangent_pressure: UINT,
+    /// The tilt of the pen; see [Packet::pkOrientation]
+    pub orientation: Orientation,
+    /// See [Packet::pkRotation]
+    pub rotation: Rotation,
+    /// See [Packet::pkButtons]
+    pub buttons: Bitmask<u32>,
+    /// See [Packet::pkCursor]
+    pub cursor: UINT,
+    /// The pen is upside down ([TPS::INVERT]), which usually means the eraser end is in use
+    pub eraser: bool,
+    /// The pen is touching the tablet
+    pub in_contact: bool,
+    /// See [Packet::pkTime]
+    pub time: DWORD,
+    /// See [Packet::pkSerialNumber]
+    pub serial: UINT,
+}
+
+impl PenState {
+    /// The state described by a single packet. [PenState::in_contact] is not known from the
+    /// packet alone and is left `false`.
+    pub fn from_packet(packet: &Packet) -> Self {
+        Self {
+            position: packet.pkXYZ,
+            pressure: packet.pkNormalPressure,
+            tangent_pressure: packet.pkTangentPressure,
+            orientation: packet.pkOrientation,
+            rotation: packet.pkRotation,
+            buttons: packet.pkButtons,
+            cursor: packet.pkCursor,
+            eraser: { packet.pkStatus }.contains(TPS::INVERT),
+            in_contact: false,
+            time: packet.pkTime,
+            serial: packet.pkSerialNumber,
+        }
+    }
+}
+
+/// A change in the state of the pen, produced by [PenEventStream]. Every event carries the full
+/// [PenState] at the time of the event.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PenEvent {
+    /// The pen came within range of the tablet
+    ProximityEnter(PenState),
+    /// The pen moved while in range but not touching the tablet
+    Hover(PenState),
+    /// The pen touched the tablet
+    Down(PenState),
+    /// The pen moved while touching the tablet
+    Move(PenState),
+    /// The pen was lifted from the tablet
+    Up(PenState),
+    /// The pen left the range of the tablet
+    ProximityLeave(PenState),
+    /// The button with this number was pressed. The tip is usually button 0.
+    ButtonPressed(u8, PenState),
+    /// The button with this number was released
+    ButtonReleased(u8, PenState),
+    /// A different cursor (e.g. the other end of the pen, or another pen) was brought into range
+    /// without the previous one leaving first
+    CursorChanged(PenState),
+}
+
+impl PenEvent {
+    /// The pen state carried by every event
+    pub fn state(&self) -> &PenState {
+        match self {
+            PenEvent::ProximityEnter(state)
+            | PenEvent::Hover(state)
+            | PenEvent::Down(state)
+            | PenEvent::Move(state)
+            | PenEvent::Up(state)
+            | PenEvent::ProximityLeave(state)
+            | PenEvent::ButtonPressed(_, state)
+            | PenEvent::ButtonReleased(_, state)
+            | PenEvent::CursorChanged(state) => state,
+        }
+    }
+}
+
+/// Turns decoded [Packet]s into [PenEvent]s.
+///
+/// The pen is in proximity while packets arrive without [TPS::PROXIMITY] set, and in contact
+/// while [Packet::pkNormalPressure] is above [PenEventStream::with_contact_threshold].
+/// For each packet the events are produced in the order
+/// enter / cursor changed, button releases, button presses, then one of down / move / up / hover.
+/// When the pen leaves, it is lifted and its buttons are released before
+/// [PenEvent::ProximityLeave].
+#[derive(Clone, Debug, Default)]
+pub struct PenEventStream {
+    contact_threshold: UINT,
+    /// `None` while out of proximity
+    state: Option<PenState>,
+}
+
+impl PenEventStream {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The pen is considered to be touching the tablet while the normal pressure is greater than
+    /// `threshold`. Defaults to zero.
+    pub fn with_contact_threshold(threshold: UINT) -> Self {
+        Self { contact_threshold: threshold, state: None }
+    }
+
+    /// The current pen state, or `None` if the pen is out of proximity
+    pub fn state(&self) -> Option<&PenState> {
+        self.state.as_ref()
+    }
+
+    /// Updates the pen state with `packet` and appends the resulting events to `events`
+    pub fn process(&mut self, packet: &Packet, events: &mut impl Extend<PenEvent>) {
+        if { packet.pkStatus }.contains(TPS::PROXIMITY) {
+            self.leave(events);
+            return;
+        }
+        let mut state = PenState::from_packet(packet);
+        state.in_contact = state.pressure > self.contact_threshold;
+        let previous = match self.state {
+            None => {
+                events.extend([PenEvent::ProximityEnter(PenState { in_contact: false, ..state })]);
+                PenState { buttons: Bitmask(0), in_contact: false, ..state }
+            }
+            Some(previous) if previous.cursor != state.cursor => {
+                events.extend([PenEvent::CursorChanged(state)]);
+                previous
+            }
+            Some(previous) => previous,
+        };
+        let (pressed, released) = state.buttons.diff(previous.buttons);
+        events.extend(released.iter().map(|n| PenEvent::ButtonReleased(n as u8, state)));
+        events.extend(pressed.iter().map(|n| PenEvent::ButtonPressed(n as u8, state)));
+        events.extend([match (previous.in_contact, state.in_contact) {
+            (false, true) => PenEvent::Down(state),
+            (true, true) => PenEvent::Move(state),
+            (true, false) => PenEvent::Up(state),
+            (false, false) => PenEvent::Hover(state),
+        }]);
+        self.state = Some(state);
+    }
+
+    /// Ends proximity without a packet, e.g. on [WT::PROXIMITY](crate::WT::PROXIMITY) or when the
+    /// context loses focus. Does nothing if the pen is already out of proximity.
+    pub fn leave(&mut self, events: &mut impl Extend<PenEvent>) {
+        let Some(mut state) = self.state.take() else {
+            return;
+        };
+        if state.in_contact {
+            state.in_contact = false;
+            state.pressure = 0;
+            events.extend([PenEvent::Up(state)]);
+        }
+        let held = std::mem::take(&mut state.buttons);
+        events.extend(held.iter().map(|n| PenEvent::ButtonReleased(n as u8, state)));
+        events.extend([PenEvent::ProximityLeave(state)]);
+    }
+
+    /// Wraps a packet iterator, yielding the events produced by each packet in turn
+    ///
+    /// ```ignore
+    /// let mut pen = PenEventStream::new();
+    /// for event in pen.events(reader.drain(&context)) {
+    ///     if let PenEvent::Down(state) = event {
+    ///         println!("down at {:?}", state.position);
+    ///     }
+    /// }
+    /// ```
+    pub fn events<I>(&mut self, packets: I) -> PenEvents<'_, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Packet>,
+    {
+        PenEvents {
+            stream: self,
+            packets: packets.into_iter(),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// See [PenEventStream::events]
+#[derive(Debug)]
+pub struct PenEvents<'a, I> {
+    stream: &'a mut PenEventStream,
+    packets: I,
+    pending: VecDeque<PenEvent>,
+}
+
+impl<I> Iterator for PenEvents<'_, I>
+where
+    I: Iterator,
+    I::Item: Borrow<Packet>,
+{
+    type Item = PenEvent;
+
+    fn next(&mut self) -> Option<PenEvent> {
+        while self.pending.is_empty() {
+            let packet = self.packets.next()?;
+            self.stream.process(packet.borrow(), &mut self.pending);
+        }
+        self.pending.pop_front()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(x: LONG, pressure: UINT, buttons: u32, status: TPS) -> Packet {
+        Packet {
+            pkXYZ: XYZ { x, y: 0, z: 0 },
+            pkNormalPressure: pressure,
+            pkButtons: Bitmask(buttons),
+            pkStatus: status,
+            pkCursor: 1,
+            ..Default::default()
+        }
+    }
+
+    fn names(events: &[PenEvent]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                PenEvent::ButtonPressed(n, _) => format!("ButtonPressed({n})"),
+                PenEvent::ButtonReleased(n, _) => format!("ButtonReleased({n})"),
+                other => format!("{other:?}").split('(').next().unwrap().to_string(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_stroke_lifecycle() {
+        let packets = [
+            packet(0, 0, 0, TPS::empty()),
+            packet(1, 0, 0, TPS::empty()),
+            packet(2, 100, 1, TPS::empty()),
+            packet(3, 200, 1, TPS::empty()),
+            packet(4, 0, 0, TPS::empty()),
+            packet(5, 0, 0, TPS::PROXIMITY),
+        ];
+        let events: Vec<_> = PenEventStream::new().events(&packets).collect();
+        assert_eq!(
+            names(&events),
+            [
+                "ProximityEnter",
+                "Hover",
+                "Hover",
+                "ButtonPressed(0)",
+                "Down",
+                "Move",
+                "ButtonReleased(0)",
+                "Up",
+                "ProximityLeave",
+            ]
+        );
+        let PenEvent::Move(state) = events[5] else { panic!() };
+        assert_eq!(state.position.x, 3);
+        assert_eq!(state.pressure, 200);
+        assert!(state.in_contact);
+        assert!(!events[8].state().in_contact);
+    }
+
+    #[test]
+    fn test_leave_while_in_contact_lifts_and_releases() {
+        let mut stream = PenEventStream::new();
+        let mut events = Vec::new();
+        stream.process(&packet(0, 100, 0b101, TPS::empty()), &mut events);
+        events.clear();
+        stream.leave(&mut events);
+        assert_eq!(
+            names(&events),
+            ["Up", "ButtonReleased(0)", "ButtonReleased(2)", "ProximityLeave"]
+        );
+        assert!(stream.state().is_none());
+        events.clear();
+        stream.leave(&mut events);
+        assert!(events.is_empty());
+    }
+
+    #[test]
+    fn test_barrel_buttons_while_hovering() {
+        let packets = [
+            packet(0, 0, 0, TPS::empty()),
+            packet(0, 0, 0b010, TPS::empty()),
+            packet(0, 0, 0b100, TPS::empty()),
+        ];
+        let events: Vec<_> = PenEventStream::new().events(&packets).collect();
+        assert_eq!(
+            names(&events),
+            [
+                "ProximityEnter",
+                "Hover",
+                "ButtonPressed(1)",
+                "Hover",
+                "ButtonReleased(1)",
+                "ButtonPressed(2)",
+                "Hover",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_cursor_change_and_eraser() {
+        let mut eraser = packet(0, 0, 0, TPS::INVERT);
+        eraser.pkCursor = 2;
+        let packets = [packet(0, 0, 0, TPS::empty()), eraser];
+        let events: Vec<_> = PenEventStream::new().events(&packets).collect();
+        assert_eq!(names(&events), ["ProximityEnter", "Hover", "CursorChanged", "Hover"]);
+        assert!(!events[1].state().eraser);
+        assert!(events[2].state().eraser);
+        assert_eq!(events[2].state().cursor, 2);
+    }
+
+    #[test]
+    fn test_contact_threshold() {
+        let packets = [
+            packet(0, 10, 0, TPS::empty()),
+            packet(0, 30, 0, TPS::empty()),
+            packet(0, 20, 0, TPS::empty()),
+        ];
+        let events: Vec<_> = PenEventStream::with_contact_threshold(20).events(&packets).collect();
+        assert_eq!(names(&events), ["ProximityEnter", "Hover", "Down", "Up"]);
+    }
+}
@@ -0,0 +1,246 @@
+use std::collections::HashMap;
+
+use crate::{
+    c_type_aliases::{DWORD, UINT},
+    information_categories::{CRC, CSR, IFC, WTI},
+    Packet, WintabApi, TPS,
+};
+
+/// Which tool, or which end of a pen, produced a packet. See [ToolTable::tool].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub enum PenTool {
+    /// The writing end of a pen
+    Tip,
+    /// The eraser end of a pen, or a dedicated eraser
+    Eraser,
+    /// A mouse or lens cursor
+    Puck,
+    /// A pen with a finger wheel, reported in [Packet::pkTangentPressure]
+    Airbrush,
+    /// A pen with barrel rotation, reported in [Packet::pkOrientation]
+    ArtPen,
+    #[default]
+    Unknown,
+}
+
+impl PenTool {
+    /// Interprets a Wacom [CSR::TYPE] tool id.
+    /// The general kind of tool is `cursor_type & 0x0F06`, and bit `0x0008` marks the eraser end.
+    pub fn from_cursor_type(cursor_type: UINT) -> Self {
+        let general = cursor_type & 0x0F06;
+        let eraser = cursor_type & 0x0008 != 0;
+        match general {
+            0x0802 | 0x0902 | 0x0804 if eraser => PenTool::Eraser,
+            0x0802 => PenTool::Tip,
+            0x0902 => PenTool::Airbrush,
+            0x0804 => PenTool::ArtPen,
+            0x0004 | 0x0006 => PenTool::Puck,
+            _ => PenTool::Unknown,
+        }
+    }
+
+    /// Wacom drivers list cursors in groups of three per tablet: puck, pen tip, then eraser.
+    /// This is only a fallback for when [CSR::TYPE] is not available.
+    pub fn from_wacom_cursor_index(cursor: UINT) -> Self {
+        match cursor % 3 {
+            0 => PenTool::Puck,
+            1 => PenTool::Tip,
+            _ => PenTool::Eraser,
+        }
+    }
+}
+
+/// The identifying details of a cursor, read from its [WTI::CURSORS] category
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct CursorInfo {
+    /// [CSR::TYPE], if the driver reports it
+    pub cursor_type: Option<UINT>,
+    /// [CSR::PHYSID]. Every cursor of one physical tool has the same id, e.g. both ends of a pen.
+    pub physical_id: DWORD,
+    /// [CSR::CAPABILITIES]
+    pub capabilities: CRC,
+}
+
+impl CursorInfo {
+    /// Reads the details of `cursor`, the zero based cursor index as in [Packet::pkCursor]
+    pub fn load<A: WintabApi + ?Sized>(api: &A, cursor: UINT) -> Self {
+        let category = WTI::CURSORS as UINT + cursor;
+        Self {
+            cursor_type: api.info_value(category, CSR::TYPE as UINT),
+            physical_id: api.info_value(category, CSR::PHYSID as UINT).unwrap_or(0),
+            capabilities: CRC::from_bits_truncate(
+                api.info_value(category, CSR::CAPABILITIES as UINT).unwrap_or(0),
+            ),
+        }
+    }
+}
+
+/// Identifies the [PenTool] in use from the cursor table of the driver.
+///
+/// ```ignore
+/// let tools = ToolTable::load(&api);
+/// for packet in reader.drain(&context) {
+///     if tools.tool(packet) == PenTool::Eraser {
+///         // ...
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ToolTable {
+    cursors: HashMap<UINT, CursorInfo>,
+    wacom: bool,
+}
+
+impl ToolTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Reads every cursor ([IFC::NCURSORS]) from the driver. The Wacom cursor index convention
+    /// is used as a fallback if the interface id ([IFC::WINTABID]) mentions Wacom.
+    pub fn load<A: WintabApi + ?Sized>(api: &A) -> Self {
+        let count: UINT = api
+            .info_value(WTI::INTERFACE as UINT, IFC::NCURSORS as UINT)
+            .unwrap_or(0);
+        let wacom = api
+            .interface_id()
+            .is_some_and(|id| id.to_ascii_lowercase().contains("wacom"));
+        let mut table = Self::new().with_wacom_convention(wacom);
+        for cursor in 0..count {
+            table.insert(cursor, CursorInfo::load(api, cursor));
+        }
+        table
+    }
+
+    /// Whether to fall back on [PenTool::from_wacom_cursor_index]
+    pub fn with_wacom_convention(mut self, wacom: bool) -> Self {
+        self.wacom = wacom;
+        self
+    }
+
+    pub fn insert(&mut self, cursor: UINT, info: CursorInfo) {
+        self.cursors.insert(cursor, info);
+    }
+
+    pub fn cursor(&self, cursor: UINT) -> Option<&CursorInfo> {
+        self.cursors.get(&cursor)
+    }
+
+    /// The tool described by `cursor`, without considering whether the pen is inverted
+    pub fn cursor_tool(&self, cursor: UINT) -> PenTool {
+        let info = self.cursors.get(&cursor).copied().unwrap_or_default();
+        if info.capabilities.contains(CRC::CRC_INVERT) {
+            return PenTool::Eraser;
+        }
+        match info.cursor_type.map(PenTool::from_cursor_type) {
+            Some(tool) if tool != PenTool::Unknown => tool,
+            _ if self.wacom => PenTool::from_wacom_cursor_index(cursor),
+            _ => PenTool::Unknown,
+        }
+    }
+
+    /// The tool which produced `packet`. [TPS::INVERT] always means the eraser.
+    pub fn tool(&self, packet: &Packet) -> PenTool {
+        if { packet.pkStatus }.contains(TPS::INVERT) {
+            PenTool::Eraser
+        } else {
+            self.cursor_tool(packet.pkCursor)
+        }
+    }
+
+    /// Whether two cursors are ends or modes of the same physical tool, according to their
+    /// [CursorInfo::physical_id]
+    pub fn same_physical_tool(&self, a: UINT, b: UINT) -> bool {
+        match (self.cursors.get(&a), self.cursors.get(&b)) {
+            (Some(a), Some(b)) => a.physical_id != 0 && a.physical_id == b.physical_id,
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FakeWintab;
+
+    /// A Wacom style table for one tablet: puck, pen, pen eraser, then an airbrush and its
+    /// eraser, an art pen and a cursor with no type
+    fn fake_cursors() -> FakeWintab {
+        let api = FakeWintab::new();
+        api.set_info_string(WTI::INTERFACE as UINT, IFC::WINTABID as UINT, "WACOM Tablet");
+        let cursors: [(Option<UINT>, DWORD, CRC); 7] = [
+            (Some(0x0006), 0, CRC::empty()),
+            (Some(0x0802), 0x1234, CRC::empty()),
+            (Some(0x080A), 0x1234, CRC::empty()),
+            (Some(0x0902), 0x5678, CRC::empty()),
+            (Some(0x090A), 0x5678, CRC::empty()),
+            (Some(0x0804), 0x9ABC, CRC::empty()),
+            (None, 0, CRC::empty()),
+        ];
+        api.set_info(WTI::INTERFACE as UINT, IFC::NCURSORS as UINT, &[cursors.len() as UINT]);
+        for (index, (cursor_type, physical_id, capabilities)) in cursors.into_iter().enumerate() {
+            let category = WTI::CURSORS as UINT + index as UINT;
+            if let Some(cursor_type) = cursor_type {
+                api.set_info(category, CSR::TYPE as UINT, &[cursor_type]);
+            }
+            api.set_info(category, CSR::PHYSID as UINT, &[physical_id]);
+            api.set_info(category, CSR::CAPABILITIES as UINT, &[capabilities.bits()]);
+        }
+        api
+    }
+
+    fn packet(cursor: UINT, status: TPS) -> Packet {
+        Packet { pkCursor: cursor, pkStatus: status, ..Default::default() }
+    }
+
+    #[test]
+    fn test_cursor_types() {
+        let tools = ToolTable::load(&fake_cursors());
+        let found: Vec<_> = (0..7).map(|cursor| tools.cursor_tool(cursor)).collect();
+        assert_eq!(
+            found,
+            [
+                PenTool::Puck,
+                PenTool::Tip,
+                PenTool::Eraser,
+                PenTool::Airbrush,
+                PenTool::Eraser,
+                PenTool::ArtPen,
+                // no type, so the index convention is used; 6 starts a new group of three
+                PenTool::Puck,
+            ]
+        );
+        // not in the table at all
+        assert_eq!(tools.cursor_tool(7), PenTool::Tip);
+    }
+
+    #[test]
+    fn test_invert_status_means_eraser() {
+        let tools = ToolTable::load(&fake_cursors());
+        assert_eq!(tools.tool(&packet(1, TPS::empty())), PenTool::Tip);
+        assert_eq!(tools.tool(&packet(1, TPS::INVERT)), PenTool::Eraser);
+        assert_eq!(tools.tool(&packet(3, TPS::INVERT | TPS::PROXIMITY)), PenTool::Eraser);
+    }
+
+    #[test]
+    fn test_invert_capability_and_non_wacom() {
+        let mut tools = ToolTable::new();
+        tools.insert(0, CursorInfo { capabilities: CRC::CRC_INVERT, ..Default::default() });
+        tools.insert(1, CursorInfo::default());
+        assert_eq!(tools.cursor_tool(0), PenTool::Eraser);
+        // without the wacom convention there is nothing to go on
+        assert_eq!(tools.cursor_tool(1), PenTool::Unknown);
+        assert_eq!(tools.with_wacom_convention(true).cursor_tool(1), PenTool::Tip);
+    }
+
+    #[test]
+    fn test_physical_tools() {
+        let tools = ToolTable::load(&fake_cursors());
+        assert!(tools.same_physical_tool(1, 2));
+        assert!(tools.same_physical_tool(3, 4));
+        assert!(!tools.same_physical_tool(2, 3));
+        // an unknown physical id is never the same tool
+        assert!(!tools.same_physical_tool(0, 6));
+        assert_eq!(tools.cursor(5).unwrap().cursor_type, Some(0x0804));
+    }
+}
@@ -0,0 +1,303 @@
+use crate::{
+    c_type_aliases::UINT,
+    information_categories::{CSR, DVC, WTI},
+    WintabApi, AXIS,
+};
+
+/// The shape of a [PressureCurve], mapping normalised input pressure in `[0, 1]` to output
+/// pressure in `[0, 1]`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum CurveShape {
+    Linear,
+    /// `output = input ^ gamma`. Values above one make the pen feel softer, below one harder.
+    Gamma(f64),
+    /// Straight lines between `(input, output)` points, sorted by input. Inputs before the first
+    /// point or after the last take the output of that point.
+    Piecewise(Vec<(f64, f64)>),
+    /// A cubic bezier from `(0, 0)` to `(1, 1)` with these two control points, like CSS
+    /// `cubic-bezier()`. Control point inputs are clamped to `[0, 1]` so the curve is a function.
+    Bezier((f64, f64), (f64, f64)),
+}
+
+impl CurveShape {
+    fn apply(&self, input: f64) -> f64 {
+        match self {
+            CurveShape::Linear => input,
+            CurveShape::Gamma(gamma) => input.powf(*gamma),
+            CurveShape::Piecewise(points) => piecewise(points, input),
+            CurveShape::Bezier(p1, p2) => bezier(*p1, *p2, input),
+        }
+    }
+}
+
+fn piecewise(points: &[(f64, f64)], input: f64) -> f64 {
+    let Some(first) = points.first() else {
+        return input;
+    };
+    if input <= first.0 {
+        return first.1;
+    }
+    for pair in points.windows(2) {
+        let ((x0, y0), (x1, y1)) = (pair[0], pair[1]);
+        if input <= x1 {
+            if x1 <= x0 {
+                return y1;
+            }
+            return y0 + (y1 - y0) * (input - x0) / (x1 - x0);
+        }
+    }
+    points[points.len() - 1].1
+}
+
+fn bezier(p1: (f64, f64), p2: (f64, f64), input: f64) -> f64 {
+    let x1 = p1.0.clamp(0.0, 1.0);
+    let x2 = p2.0.clamp(0.0, 1.0);
+    let cubic = |a: f64, b: f64, t: f64| {
+        let u = 1.0 - t;
+        3.0 * u * u * t * a + 3.0 * u * t * t * b + t * t * t
+    };
+    // x(t) is monotonic once the controls are within [0, 1], so bisection always converges
+    let (mut low, mut high) = (0.0, 1.0);
+    for _ in 0..48 {
+        let middle = (low + high) / 2.0;
+        if cubic(x1, x2, middle) < input {
+            low = middle;
+        } else {
+            high = middle;
+        }
+    }
+    cubic(p1.1, p2.1, (low + high) / 2.0)
+}
+
+/// Maps raw [Packet::pkNormalPressure](crate::Packet::pkNormalPressure) or
+/// [Packet::pkTangentPressure](crate::Packet::pkTangentPressure) values to `[0, 1]`.
+///
+/// The raw value is first normalised using the `min` and `max` of the range, then the deadzones
+/// are removed from each end, then the [CurveShape] is applied and the result clamped.
+///
+/// ```ignore
+/// let curve = PressureCurve::load_normal(&api, 0)
+///     .unwrap_or_default()
+///     .with_shape(CurveShape::Gamma(1.5))
+///     .with_deadzones(0.02, 0.0);
+/// let pressure = curve.map(packet.pkNormalPressure);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PressureCurve {
+    /// The raw value which maps to zero
+    pub min: UINT,
+    /// The raw value which maps to one
+    pub max: UINT,
+    /// The fraction of the normalised range at the bottom which maps to zero
+    pub deadzone_low: f64,
+    /// The fraction of the normalised range at the top which maps to one
+    pub deadzone_high: f64,
+    pub shape: CurveShape,
+}
+
+impl Default for PressureCurve {
+    /// Linear over the 1024 levels common to older tablets
+    fn default() -> Self {
+        Self::linear(0, 1023)
+    }
+}
+
+impl PressureCurve {
+    pub fn linear(min: UINT, max: UINT) -> Self {
+        Self {
+            min,
+            max,
+            deadzone_low: 0.0,
+            deadzone_high: 0.0,
+            shape: CurveShape::Linear,
+        }
+    }
+
+    /// A linear curve over the range of a pressure axis such as [DVC::NPRESSURE]
+    pub fn from_axis(axis: &AXIS) -> Self {
+        Self::linear(axis.axMin.max(0) as UINT, axis.axMax.max(0) as UINT)
+    }
+
+    /// A linear curve over the [DVC::NPRESSURE] axis of `device`, the zero based device index
+    pub fn load_normal<A: WintabApi + ?Sized>(api: &A, device: UINT) -> Option<Self> {
+        let axis: AXIS = api.info_value(WTI::DEVICES as UINT + device, DVC::NPRESSURE as UINT)?;
+        Some(Self::from_axis(&axis))
+    }
+
+    /// A linear curve over the [DVC::TPRESSURE] axis of `device`, which is the finger wheel of an
+    /// airbrush
+    pub fn load_tangent<A: WintabApi + ?Sized>(api: &A, device: UINT) -> Option<Self> {
+        let axis: AXIS = api.info_value(WTI::DEVICES as UINT + device, DVC::TPRESSURE as UINT)?;
+        Some(Self::from_axis(&axis))
+    }
+
+    pub fn with_shape(mut self, shape: CurveShape) -> Self {
+        self.shape = shape;
+        self
+    }
+
+    /// Sets the deadzones at each end, as fractions of the range
+    pub fn with_deadzones(mut self, low: f64, high: f64) -> Self {
+        self.deadzone_low = low.clamp(0.0, 1.0);
+        self.deadzone_high = high.clamp(0.0, 1.0 - self.deadzone_low);
+        self
+    }
+
+    /// Uses a response curve in the form of [CSR::NPRESPONSE] or [CSR::TPRESPONSE]: raw output
+    /// levels for evenly spaced input levels across the range. Fewer than two levels leaves the
+    /// shape unchanged.
+    pub fn with_response(mut self, response: &[UINT]) -> Self {
+        if response.len() < 2 {
+            return self;
+        }
+        let last = (response.len() - 1) as f64;
+        let points = response
+            .iter()
+            .enumerate()
+            .map(|(index, level)| (index as f64 / last, self.normalise(*level)))
+            .collect();
+        self.shape = CurveShape::Piecewise(points);
+        self
+    }
+
+    /// Uses the button marks in the form of [CSR::NPBTNMARKS] (`[release, press]`): pressure at
+    /// or below the release mark is treated as the pen being lifted and maps to zero.
+    pub fn with_button_marks(mut self, marks: [UINT; 2]) -> Self {
+        self.min = marks[0].clamp(self.min, self.max);
+        self
+    }
+
+    /// Applies the driver's own [CSR::NPRESPONSE] curve and [CSR::NPBTNMARKS] for `cursor`, the
+    /// zero based cursor index, where the driver reports them.
+    pub fn with_cursor_response<A: WintabApi + ?Sized>(self, api: &A, cursor: UINT) -> Self {
+        let category = WTI::CURSORS as UINT + cursor;
+        // the marks first, so the response levels are normalised over the final range
+        let curve = match api.info_value::<[UINT; 2]>(category, CSR::NPBTNMARKS as UINT) {
+            Some(marks) => self.with_button_marks(marks),
+            None => self,
+        };
+        match api.info_array::<UINT>(category, CSR::NPRESPONSE as UINT) {
+            Some(response) => curve.with_response(&response),
+            None => curve,
+        }
+    }
+
+    /// The raw value as a fraction of the range, clamped to `[0, 1]`
+    pub fn normalise(&self, raw: UINT) -> f64 {
+        if self.max <= self.min {
+            return if raw > self.min { 1.0 } else { 0.0 };
+        }
+        ((raw as f64 - self.min as f64) / (self.max as f64 - self.min as f64)).clamp(0.0, 1.0)
+    }
+
+    /// Maps a raw pressure value to `[0, 1]`
+    pub fn map(&self, raw: UINT) -> f64 {
+        let live = 1.0 - self.deadzone_low - self.deadzone_high;
+        let input = if live <= 0.0 {
+            if self.normalise(raw) > self.deadzone_low { 1.0 } else { 0.0 }
+        } else {
+            ((self.normalise(raw) - self.deadzone_low) / live).clamp(0.0, 1.0)
+        };
+        self.shape.apply(input).clamp(0.0, 1.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FakeWintab;
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-6, "{a} != {b}");
+    }
+
+    #[test]
+    fn test_linear() {
+        let curve = PressureCurve::linear(0, 1000);
+        assert_close(curve.map(0), 0.0);
+        assert_close(curve.map(250), 0.25);
+        assert_close(curve.map(1000), 1.0);
+        assert_close(curve.map(5000), 1.0);
+        let curve = PressureCurve::linear(100, 200);
+        assert_close(curve.map(50), 0.0);
+        assert_close(curve.map(150), 0.5);
+        // an empty range
+        let curve = PressureCurve::linear(10, 10);
+        assert_close(curve.map(10), 0.0);
+        assert_close(curve.map(11), 1.0);
+    }
+
+    #[test]
+    fn test_deadzones() {
+        let curve = PressureCurve::linear(0, 100).with_deadzones(0.1, 0.2);
+        assert_close(curve.map(10), 0.0);
+        assert_close(curve.map(45), 0.5);
+        assert_close(curve.map(80), 1.0);
+        assert_close(curve.map(95), 1.0);
+    }
+
+    #[test]
+    fn test_gamma() {
+        let curve = PressureCurve::linear(0, 100).with_shape(CurveShape::Gamma(2.0));
+        assert_close(curve.map(50), 0.25);
+        assert_close(curve.map(100), 1.0);
+    }
+
+    #[test]
+    fn test_piecewise() {
+        let points = vec![(0.2, 0.0), (0.5, 0.8), (1.0, 1.0)];
+        let curve = PressureCurve::linear(0, 100).with_shape(CurveShape::Piecewise(points));
+        assert_close(curve.map(10), 0.0);
+        assert_close(curve.map(35), 0.4);
+        assert_close(curve.map(75), 0.9);
+        let empty = PressureCurve::linear(0, 100).with_shape(CurveShape::Piecewise(vec![]));
+        assert_close(empty.map(30), 0.3);
+    }
+
+    #[test]
+    fn test_bezier() {
+        let linear = CurveShape::Bezier((0.25, 0.25), (0.75, 0.75));
+        let curve = PressureCurve::linear(0, 100).with_shape(linear);
+        assert_close(curve.map(30), 0.3);
+        let soft = CurveShape::Bezier((0.0, 0.6), (0.4, 1.0));
+        let curve = PressureCurve::linear(0, 100).with_shape(soft);
+        assert!(curve.map(30) > 0.5);
+        assert_close(curve.map(0), 0.0);
+        assert_close(curve.map(100), 1.0);
+        let values: Vec<_> = (0..=100).map(|raw| curve.map(raw)).collect();
+        assert!(values.windows(2).all(|pair| pair[0] <= pair[1]));
+    }
+
+    #[test]
+    fn test_driver_curve() {
+        let api = FakeWintab::new();
+        let npressure = AXIS { axMin: 0, axMax: 1023, ..Default::default() };
+        api.set_info(WTI::DEVICES as UINT, DVC::NPRESSURE as UINT, &[npressure]);
+        let tpressure = AXIS { axMin: 0, axMax: 511, ..Default::default() };
+        api.set_info(WTI::DEVICES as UINT, DVC::TPRESSURE as UINT, &[tpressure]);
+        let cursor = WTI::CURSORS as UINT + 1;
+        // a soft response with output levels for inputs 0, 1/2 and 1
+        api.set_info(cursor, CSR::NPRESPONSE as UINT, &[0 as UINT, 767, 1023]);
+        api.set_info(cursor, CSR::NPBTNMARKS as UINT, &[23 as UINT, 40]);
+
+        let curve = PressureCurve::load_normal(&api, 0).unwrap();
+        assert_eq!(curve.max, 1023);
+        assert_close(curve.map(1023), 1.0);
+
+        let curve = curve.with_cursor_response(&api, 1);
+        assert_eq!(curve.min, 23);
+        assert_close(curve.map(23), 0.0);
+        assert_close(curve.map(1023), 1.0);
+        // half way along the live range maps to the middle response level
+        assert_close(curve.map(523), (767.0 - 23.0) / 1000.0);
+
+        // a cursor without a response curve is unchanged
+        let unchanged = PressureCurve::load_normal(&api, 0).unwrap().with_cursor_response(&api, 0);
+        assert_eq!(unchanged, PressureCurve::linear(0, 1023));
+
+        let wheel = PressureCurve::load_tangent(&api, 0).unwrap();
+        assert_close(wheel.map(511), 1.0);
+        assert_close(wheel.map(0), 0.0);
+        assert_eq!(PressureCurve::load_tangent(&api, 1), None);
+    }
+}
@@ -0,0 +1,224 @@
+use crate::{
+    axis::TU,
+    c_type_aliases::{LONG, UINT},
+    information_categories::{DVC, WTI},
+    ScreenRect, WintabApi, AXIS, LOGCONTEXTW, XYZ,
+};
+
+/// How [TabletArea::force_proportions] makes the tablet match the shape of the screen
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ProportionMode {
+    /// Use the largest centred part of the tablet with the shape of the output, so the whole
+    /// output is reachable but the edges of the tablet are not used
+    #[default]
+    Crop,
+    /// Use the whole tablet and shrink the output to the shape of the tablet instead, so part of
+    /// the output is not reachable. Wintab clips the input area to the tablet, so letterboxing
+    /// is done on the output side.
+    Letterbox,
+}
+
+/// Input and output areas with the same physical proportions; see
+/// [TabletArea::force_proportions]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProportionalMapping {
+    /// For [LOGCONTEXTW::lcInOrgXYZ]
+    pub in_org: XYZ<LONG>,
+    /// For [LOGCONTEXTW::lcInExtXYZ]
+    pub in_ext: XYZ<LONG>,
+    /// For [LOGCONTEXTW::lcOutOrgXYZ] and [LOGCONTEXTW::lcOutExtXYZ]; see
+    /// [ScreenRect::apply_output]
+    pub output: ScreenRect,
+}
+
+impl ProportionalMapping {
+    /// Sets the x and y of the input and output areas of `log_context`, leaving z as it is
+    pub fn apply(&self, log_context: &mut LOGCONTEXTW) {
+        log_context.lcInOrgXYZ = XYZ { z: log_context.lcInOrgXYZ.z, ..self.in_org };
+        log_context.lcInExtXYZ = XYZ { z: log_context.lcInExtXYZ.z, ..self.in_ext };
+        self.output.apply_output(log_context);
+    }
+}
+
+/// The active area of a tablet, from its [DVC::X] and [DVC::Y] axes.
+///
+/// The two axes may have different resolutions, so the shape of the tablet is worked out from
+/// the physical size of each axis rather than from the raw counts.
+///
+/// ```ignore
+/// let area = TabletArea::load(&api, 0).unwrap();
+/// let output = layout.output_rect(OutputPreset::PrimaryMonitor).unwrap();
+/// area.force_proportions(output, ProportionMode::Crop).apply(&mut log_context);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TabletArea {
+    pub x: AXIS,
+    pub y: AXIS,
+}
+
+impl TabletArea {
+    pub fn new(x: AXIS, y: AXIS) -> Self {
+        Self { x, y }
+    }
+
+    /// Reads the [DVC::X] and [DVC::Y] axes of `device`, the zero based device index
+    pub fn load<A: WintabApi + ?Sized>(api: &A, device: UINT) -> Option<Self> {
+        let category = WTI::DEVICES as UINT + device;
+        Some(Self {
+            x: api.info_value(category, DVC::X as UINT)?,
+            y: api.info_value(category, DVC::Y as UINT)?,
+        })
+    }
+
+    /// The size of the area in counts
+    pub fn counts(&self) -> (LONG, LONG) {
+        (self.x.axMax - self.x.axMin, self.y.axMax - self.y.axMin)
+    }
+
+    /// The width and height in millimetres, or `None` if either axis is not given in inches or
+    /// centimetres
+    pub fn size_mm(&self) -> Option<(f64, f64)> {
+        let (width, height) = self.counts();
+        Some((width as f64 / counts_per_mm(&self.x)?, height as f64 / counts_per_mm(&self.y)?))
+    }
+
+    /// Physical width over height, e.g. for [OutputPreset::Region](crate::OutputPreset::Region).
+    /// Falls back on the ratio of the counts if the physical size is unknown.
+    pub fn aspect(&self) -> f64 {
+        let (width, height) = match self.size_mm() {
+            Some(size) => size,
+            None => {
+                let (width, height) = self.counts();
+                (width as f64, height as f64)
+            }
+        };
+        width / height
+    }
+
+    /// The input and output areas which map `output` without stretching, so circles drawn on
+    /// the tablet stay round on screen. Screen pixels are assumed to be square.
+    pub fn force_proportions(
+        &self,
+        output: ScreenRect,
+        mode: ProportionMode,
+    ) -> ProportionalMapping {
+        let (width, height) = self.counts();
+        let whole = ProportionalMapping {
+            in_org: XYZ { x: self.x.axMin, y: self.y.axMin, z: 0 },
+            in_ext: XYZ { x: width, y: height, z: 0 },
+            output,
+        };
+        let tablet_aspect = self.aspect();
+        let output_aspect = output.aspect();
+        if !(tablet_aspect.is_finite() && output_aspect.is_finite()) || output.is_empty() {
+            return whole;
+        }
+        match mode {
+            ProportionMode::Letterbox => {
+                ProportionalMapping { output: output.fit_aspect(tablet_aspect), ..whole }
+            }
+            ProportionMode::Crop if tablet_aspect > output_aspect => {
+                let cropped = (width as f64 * output_aspect / tablet_aspect).round() as LONG;
+                ProportionalMapping {
+                    in_org: XYZ { x: self.x.axMin + (width - cropped) / 2, ..whole.in_org },
+                    in_ext: XYZ { x: cropped, ..whole.in_ext },
+                    ..whole
+                }
+            }
+            ProportionMode::Crop => {
+                let cropped = (height as f64 * tablet_aspect / output_aspect).round() as LONG;
+                ProportionalMapping {
+                    in_org: XYZ { y: self.y.axMin + (height - cropped) / 2, ..whole.in_org },
+                    in_ext: XYZ { y: cropped, ..whole.in_ext },
+                    ..whole
+                }
+            }
+        }
+    }
+}
+
+/// The resolution of a position axis in counts per millimetre
+fn counts_per_mm(axis: &AXIS) -> Option<f64> {
+    let resolution = axis.axResolution.to_f64();
+    let per_mm = match axis.units()? {
+        TU::INCHES => resolution / 25.4,
+        TU::CENTIMETERS => resolution / 10.0,
+        TU::NONE | TU::CIRCLE => return None,
+    };
+    Some(per_mm).filter(|per_mm| *per_mm > 0.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FakeWintab, FIX32};
+
+    fn axis(max: LONG, units: TU, resolution: f64) -> AXIS {
+        AXIS { axMin: 0, axMax: max, axUnits: units as UINT, axResolution: FIX32::from(resolution) }
+    }
+
+    /// 200 mm by 125 mm, with fewer counts per mm vertically: physically 1.6:1, but 2:1 in counts
+    fn area() -> TabletArea {
+        TabletArea::new(axis(20000, TU::CENTIMETERS, 1000.0), axis(10000, TU::CENTIMETERS, 800.0))
+    }
+
+    #[test]
+    fn test_physical_size() {
+        let area = area();
+        assert_eq!(area.size_mm(), Some((200.0, 125.0)));
+        assert_eq!(area.aspect(), 1.6);
+        let inches =
+            TabletArea::new(axis(25400, TU::INCHES, 2540.0), axis(12700, TU::INCHES, 2540.0));
+        assert_eq!(inches.size_mm(), Some((254.0, 127.0)));
+        // without units the counts are all there is
+        let unknown = TabletArea::new(axis(3000, TU::NONE, 0.0), axis(2000, TU::NONE, 0.0));
+        assert_eq!(unknown.size_mm(), None);
+        assert_eq!(unknown.aspect(), 1.5);
+    }
+
+    #[test]
+    fn test_crop() {
+        let area = area();
+        // the 16:9 screen is wider than the tablet, so the top and bottom are cropped:
+        // 200 mm wide needs 112.5 mm, which is 9000 counts at 80 per mm
+        let output = ScreenRect::new(0, 0, 1920, 1080);
+        let mapping = area.force_proportions(output, ProportionMode::Crop);
+        assert_eq!(mapping.in_org, XYZ { x: 0, y: 500, z: 0 });
+        assert_eq!(mapping.in_ext, XYZ { x: 20000, y: 9000, z: 0 });
+        assert_eq!(mapping.output, output);
+        // a 4:3 screen is narrower, so the sides are cropped: 125 mm tall needs 166.7 mm
+        let output = ScreenRect::new(-1024, 0, 1024, 768);
+        let mapping = area.force_proportions(output, ProportionMode::Crop);
+        assert_eq!(mapping.in_org, XYZ { x: 1666, y: 0, z: 0 });
+        assert_eq!(mapping.in_ext, XYZ { x: 16667, y: 10000, z: 0 });
+        // an already matching screen is left alone
+        let output = ScreenRect::new(0, 0, 1600, 1000);
+        let mapping = area.force_proportions(output, ProportionMode::Crop);
+        assert_eq!(mapping.in_ext, XYZ { x: 20000, y: 10000, z: 0 });
+    }
+
+    #[test]
+    fn test_letterbox() {
+        let output = ScreenRect::new(0, 0, 1920, 1080);
+        let mapping = area().force_proportions(output, ProportionMode::Letterbox);
+        assert_eq!(mapping.in_ext, XYZ { x: 20000, y: 10000, z: 0 });
+        assert_eq!(mapping.output, ScreenRect::new(96, 0, 1728, 1080));
+
+        let mut log_context = LOGCONTEXTW::default();
+        log_context.lcInExtXYZ.z = 1023;
+        mapping.apply(&mut log_context);
+        assert_eq!(log_context.lcInExtXYZ, XYZ { x: 20000, y: 10000, z: 1023 });
+        assert_eq!(log_context.lcOutOrgXYZ, XYZ { x: 96, y: 0, z: 0 });
+        assert_eq!(log_context.lcOutExtXYZ, XYZ { x: 1728, y: -1080, z: 0 });
+    }
+
+    #[test]
+    fn test_load() {
+        let api = FakeWintab::new();
+        assert_eq!(TabletArea::load(&api, 0), None);
+        let area = area();
+        api.set_info(WTI::DEVICES as UINT, DVC::X as UINT, &[area.x]);
+        api.set_info(WTI::DEVICES as UINT, DVC::Y as UINT, &[area.y]);
+        assert_eq!(TabletArea::load(&api, 0), Some(area));
+    }
+}
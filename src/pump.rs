@@ -0,0 +1,380 @@
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, TrySendError},
+        Arc,
+    },
+    thread::{self, JoinHandle, Thread},
+    time::Duration,
+};
+
+use crate::{Context, ContextError, Packet, PacketReader, PenEvent, PenEventStream, WintabApi};
+
+/// How long to wait before trying again to send to a full bounded channel
+pub(crate) const FULL_RETRY: Duration = Duration::from_millis(1);
+
+/// The sending half of a channel used by [PacketPump]
+pub trait PumpSender<T>: Send {
+    /// Sends `item` without blocking. A full bounded channel hands the item back with
+    /// [TrySendError::Full]; the pump then waits and tries again, which holds packets in the
+    /// driver's queue until the receiver catches up.
+    fn try_send(&self, item: T) -> Result<(), TrySendError<T>>;
+
+    /// How long the pump waits before trying a full channel again. `None` waits until the pump
+    /// thread is unparked, for channels whose receiver does that when it makes space.
+    fn full_retry(&self) -> Option<Duration> {
+        Some(FULL_RETRY)
+    }
+}
+
+impl<T: Send> PumpSender<T> for mpsc::Sender<T> {
+    fn try_send(&self, item: T) -> Result<(), TrySendError<T>> {
+        self.send(item).map_err(|error| TrySendError::Disconnected(error.0))
+    }
+}
+
+impl<T: Send> PumpSender<T> for mpsc::SyncSender<T> {
+    fn try_send(&self, item: T) -> Result<(), TrySendError<T>> {
+        mpsc::SyncSender::try_send(self, item)
+    }
+}
+
+#[cfg(feature = "crossbeam")]
+impl<T: Send> PumpSender<T> for crossbeam_channel::Sender<T> {
+    fn try_send(&self, item: T) -> Result<(), TrySendError<T>> {
+        crossbeam_channel::Sender::try_send(self, item).map_err(|error| match error {
+            crossbeam_channel::TrySendError::Full(item) => TrySendError::Full(item),
+            crossbeam_channel::TrySendError::Disconnected(item) => TrySendError::Disconnected(item),
+        })
+    }
+}
+
+/// Sends `item`, calling `wait` between attempts while the channel is full. Returns `false`
+/// if the receiver has gone away, or if `should_stop` returns `true` while waiting, so a
+/// receiver which stops draining can never keep the sending thread from shutting down.
+pub(crate) fn send_or_stop<T, S: PumpSender<T> + ?Sized>(
+    sender: &S,
+    mut item: T,
+    should_stop: impl Fn() -> bool,
+    mut wait: impl FnMut(),
+) -> bool {
+    loop {
+        match sender.try_send(item) {
+            Ok(()) => return true,
+            Err(TrySendError::Disconnected(_)) => return false,
+            Err(TrySendError::Full(_)) if should_stop() => return false,
+            Err(TrySendError::Full(returned)) => {
+                item = returned;
+                wait();
+            }
+        }
+    }
+}
+
+/// Wakes a [PacketPump] so it reads packets immediately instead of at the end of its interval.
+/// Call [PumpNotifier::notify] when the window receives [WT::PACKET](crate::WT::PACKET).
+#[derive(Clone, Debug)]
+pub struct PumpNotifier {
+    thread: Thread,
+}
+
+impl PumpNotifier {
+    pub fn notify(&self) {
+        self.thread.unpark();
+    }
+}
+
+/// Reads packets on a background thread and sends them over a channel, so pen latency does
+/// not depend on the render loop.
+///
+/// The context is opened on the pump thread and closed there when the pump is shut down or
+/// dropped, or when the receiver is dropped.
+///
+/// ```ignore
+/// let api = Arc::new(unsafe { LibloadingApi::load()? });
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// let pump = PacketPump::spawn(
+///     move || Context::open(api, hwnd, &mut log_context, true),
+///     sender,
+///     Duration::from_millis(2),
+/// )?;
+/// let notifier = pump.notifier();
+/// // in the window procedure, on WT::PACKET
+/// notifier.notify();
+/// // elsewhere
+/// for packet in receiver.try_iter() { /* ... */ }
+/// ```
+#[derive(Debug)]
+pub struct PacketPump {
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+    notifier: PumpNotifier,
+}
+
+impl PacketPump {
+    /// Sends every [Packet] read from the context opened by `open`.
+    /// The queue is read whenever the pump is notified, and at least every `interval`.
+    pub fn spawn<A, S>(
+        open: impl FnOnce() -> Result<Context<A>, ContextError> + Send + 'static,
+        sender: S,
+        interval: Duration,
+    ) -> Result<Self, ContextError>
+    where
+        A: WintabApi + 'static,
+        S: PumpSender<Packet> + 'static,
+    {
+        Self::spawn_with(open, sender, interval, |packet, items| items.push(packet.clone()))
+    }
+
+    /// Like [PacketPump::spawn], but sends the [PenEvent]s produced by a [PenEventStream]
+    pub fn spawn_pen_events<A, S>(
+        open: impl FnOnce() -> Result<Context<A>, ContextError> + Send + 'static,
+        sender: S,
+        interval: Duration,
+    ) -> Result<Self, ContextError>
+    where
+        A: WintabApi + 'static,
+        S: PumpSender<PenEvent> + 'static,
+    {
+        let mut stream = PenEventStream::new();
+        Self::spawn_with(open, sender, interval, move |packet, items| {
+            stream.process(packet, items)
+        })
+    }
+
+    /// Runs `decode` on the pump thread for each packet, and sends whatever it appends to the
+    /// `Vec` it is given.
+    pub fn spawn_with<A, T, S, F>(
+        open: impl FnOnce() -> Result<Context<A>, ContextError> + Send + 'static,
+        sender: S,
+        interval: Duration,
+        mut decode: F,
+    ) -> Result<Self, ContextError>
+    where
+        A: WintabApi + 'static,
+        T: Send + 'static,
+        S: PumpSender<T> + 'static,
+        F: FnMut(&Packet, &mut Vec<T>) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let (ready_sender, ready) = mpsc::sync_channel(1);
+        let thread = thread::Builder::new()
+            .name("wintab packet pump".to_string())
+            .spawn({
+                let stop = stop.clone();
+                move || {
+                    let context = match open() {
+                        Ok(context) => context,
+                        Err(error) => {
+                            let _ = ready_sender.send(Err(error));
+                            return;
+                        }
+                    };
+                    let _ = ready_sender.send(Ok(()));
+                    let mut reader = PacketReader::new();
+                    let mut items = Vec::new();
+                    while !stop.load(Ordering::Acquire) {
+                        for packet in reader.drain(&context) {
+                            decode(packet, &mut items);
+                        }
+                        for item in items.drain(..) {
+                            let should_stop = || stop.load(Ordering::Acquire);
+                            // woken early by `stop_and_join`, or by a notification
+                            let wait = || match sender.full_retry() {
+                                Some(timeout) => thread::park_timeout(timeout),
+                                None => thread::park(),
+                            };
+                            if !send_or_stop(&sender, item, should_stop, wait) {
+                                return;
+                            }
+                        }
+                        thread::park_timeout(interval);
+                    }
+                }
+            })
+            .expect("failed to spawn the packet pump thread");
+        let notifier = PumpNotifier { thread: thread.thread().clone() };
+        match ready.recv() {
+            Ok(Ok(())) => Ok(Self { stop, thread: Some(thread), notifier }),
+            Ok(Err(error)) => {
+                let _ = thread.join();
+                Err(error)
+            }
+            // the open function panicked
+            Err(_) => match thread.join() {
+                Err(panic) => std::panic::resume_unwind(panic),
+                Ok(()) => unreachable!("the pump thread exited without reporting"),
+            },
+        }
+    }
+
+    /// A handle that can wake the pump from another thread
+    pub fn notifier(&self) -> PumpNotifier {
+        self.notifier.clone()
+    }
+
+    /// `false` once the pump thread has stopped, e.g. because the receiver was dropped
+    pub fn is_running(&self) -> bool {
+        self.thread.as_ref().is_some_and(|thread| !thread.is_finished())
+    }
+
+    /// Stops the pump thread, closing the context, and waits for it to finish
+    pub fn shutdown(mut self) -> thread::Result<()> {
+        self.stop_and_join()
+    }
+
+    pub(crate) fn stop_and_join(&mut self) -> thread::Result<()> {
+        self.stop.store(true, Ordering::Release);
+        self.notifier.notify();
+        match self.thread.take() {
+            Some(thread) => thread.join(),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for PacketPump {
+    fn drop(&mut self) {
+        let _ = self.stop_and_join();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FakeWintab, LOGCONTEXTW, TPS};
+    use windows::Win32::Foundation::HWND;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    type FakeContext = Context<Arc<FakeWintab>>;
+
+    fn open(api: &Arc<FakeWintab>) -> impl FnOnce() -> Result<FakeContext, ContextError> {
+        let api = api.clone();
+        move || Context::open(api, HWND(1), &mut LOGCONTEXTW::default(), true)
+    }
+
+    #[test]
+    fn test_packets_are_sent_and_context_closed() {
+        let api = Arc::new(FakeWintab::new());
+        let (sender, receiver) = mpsc::channel();
+        // a long interval, so only notifying wakes the pump
+        let pump = PacketPump::spawn(open(&api), sender, Duration::from_secs(60)).unwrap();
+        assert_eq!(api.state().open.len(), 1);
+        for _ in 0..3 {
+            api.push(Packet::default());
+        }
+        pump.notifier().notify();
+        let serials: Vec<_> = (0..3)
+            .map(|_| receiver.recv_timeout(TIMEOUT).unwrap().pkSerialNumber)
+            .collect();
+        assert_eq!(serials, [0, 1, 2]);
+        assert!(pump.is_running());
+
+        pump.shutdown().unwrap();
+        assert!(api.state().open.is_empty());
+        assert_eq!(api.state().closed.len(), 1);
+        assert!(receiver.recv().is_err());
+    }
+
+    #[test]
+    fn test_dropped_receiver_stops_the_pump() {
+        let api = Arc::new(FakeWintab::new());
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let pump = PacketPump::spawn(open(&api), sender, Duration::from_millis(1)).unwrap();
+        drop(receiver);
+        api.push(Packet::default());
+        let start = std::time::Instant::now();
+        while pump.is_running() {
+            assert!(start.elapsed() < TIMEOUT);
+            thread::sleep(Duration::from_millis(1));
+        }
+        assert_eq!(api.state().closed.len(), 1);
+        drop(pump);
+    }
+
+    #[test]
+    fn test_open_failure() {
+        let api = Arc::new(FakeWintab::new());
+        api.state().fail_open = true;
+        let (sender, _receiver) = mpsc::channel::<Packet>();
+        let result = PacketPump::spawn(open(&api), sender, Duration::from_millis(1));
+        assert_eq!(result.unwrap_err(), ContextError::OpenFailed);
+    }
+
+    #[test]
+    fn test_pen_events() {
+        let api = Arc::new(FakeWintab::new());
+        let (sender, receiver) = mpsc::channel();
+        let pump =
+            PacketPump::spawn_pen_events(open(&api), sender, Duration::from_millis(1)).unwrap();
+        api.push(Packet { pkNormalPressure: 10, ..Default::default() });
+        api.push(Packet { pkStatus: TPS::PROXIMITY, ..Default::default() });
+        let mut events = Vec::new();
+        while events.len() < 4 {
+            events.push(receiver.recv_timeout(TIMEOUT).unwrap());
+        }
+        assert!(matches!(events[0], PenEvent::ProximityEnter(_)));
+        assert!(matches!(events[1], PenEvent::Down(_)));
+        assert!(matches!(events[2], PenEvent::Up(_)));
+        assert!(matches!(events[3], PenEvent::ProximityLeave(_)));
+        drop(pump);
+        assert!(api.state().open.is_empty());
+    }
+
+    #[test]
+    fn test_drop_with_full_channel() {
+        let api = Arc::new(FakeWintab::new());
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let pump = PacketPump::spawn(open(&api), sender, Duration::from_millis(1)).unwrap();
+        for _ in 0..3 {
+            api.push(Packet::default());
+        }
+        pump.notifier().notify();
+        // the pump has read every packet, and is waiting for space after sending the first
+        let start = std::time::Instant::now();
+        while !api.state().queue.is_empty() {
+            assert!(start.elapsed() < TIMEOUT);
+            thread::sleep(Duration::from_millis(1));
+        }
+        let (dropped_sender, dropped) = mpsc::channel();
+        thread::spawn(move || {
+            drop(pump);
+            dropped_sender.send(()).unwrap();
+        });
+        // the receiver is alive but never drained, which must not keep the pump running
+        dropped.recv_timeout(TIMEOUT).expect("dropping the pump deadlocked");
+        assert_eq!(api.state().closed.len(), 1);
+        assert_eq!(receiver.try_iter().count(), 1);
+    }
+
+    #[cfg(feature = "crossbeam")]
+    #[test]
+    fn test_full_crossbeam_channel() {
+        let api = Arc::new(FakeWintab::new());
+        let (sender, _receiver) = crossbeam_channel::bounded(1);
+        let pump = PacketPump::spawn(open(&api), sender, Duration::from_millis(1)).unwrap();
+        api.push(Packet::default());
+        api.push(Packet::default());
+        pump.notifier().notify();
+        let start = std::time::Instant::now();
+        while !api.state().queue.is_empty() {
+            assert!(start.elapsed() < TIMEOUT);
+            thread::sleep(Duration::from_millis(1));
+        }
+        pump.shutdown().unwrap();
+        assert_eq!(api.state().closed.len(), 1);
+    }
+
+    #[cfg(feature = "crossbeam")]
+    #[test]
+    fn test_crossbeam_channel() {
+        let api = Arc::new(FakeWintab::new());
+        let (sender, receiver) = crossbeam_channel::bounded(4);
+        let pump = PacketPump::spawn(open(&api), sender, Duration::from_millis(1)).unwrap();
+        api.push(Packet::default());
+        assert_eq!(receiver.recv_timeout(TIMEOUT).unwrap().pkSerialNumber, 0);
+        pump.shutdown().unwrap();
+        assert_eq!(api.state().closed.len(), 1);
+    }
+}
@@ -0,0 +1,295 @@
+use std::{borrow::Borrow, collections::VecDeque};
+
+use crate::{
+    c_type_aliases::{HCTX, UINT},
+    Packet, TPS,
+};
+
+/// Something noteworthy about the continuity of a packet stream, reported by [SerialTracker]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerialEvent {
+    /// The packets with serial numbers `from` to `to` inclusive never arrived. The range may wrap
+    /// past [UINT::MAX](crate::UINT), in which case `to < from`.
+    Dropped { from: UINT, to: UINT },
+    /// The packet with this serial number was flagged with [TPS::QUEUE_ERR]; the context's packet
+    /// queue overflowed and input was lost. Consider growing the queue with `WTQueueSizeSet`.
+    QueueOverflow { serial: UINT },
+}
+
+impl SerialEvent {
+    /// The number of packets lost, if known
+    pub fn dropped_count(&self) -> Option<u64> {
+        match *self {
+            SerialEvent::Dropped { from, to } => Some(to.wrapping_sub(from) as u64 + 1),
+            SerialEvent::QueueOverflow { .. } => None,
+        }
+    }
+}
+
+/// Tracks [Packet::pkSerialNumber] per context to detect lost packets.
+///
+/// Serial numbers are assigned consecutively by each context, so a jump means packets were
+/// dropped. Packets with a serial number at or before the last one seen (e.g. a packet read twice
+/// with `WTPacket`) are not treated as gaps.
+#[derive(Debug, Default, Clone)]
+pub struct SerialTracker {
+    /// `(context handle, last serial number seen)`; there is rarely more than one context
+    last_serials: Vec<(usize, UINT)>,
+}
+
+impl SerialTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The last serial number seen from `context`
+    pub fn last_serial(&self, context: *mut HCTX) -> Option<UINT> {
+        self.last_serials
+            .iter()
+            .find(|(handle, _)| *handle == context as usize)
+            .map(|(_, serial)| *serial)
+    }
+
+    /// Forgets the serial numbers of `context`, e.g. after it is closed or the queue was flushed
+    pub fn reset(&mut self, context: *mut HCTX) {
+        self.last_serials.retain(|(handle, _)| *handle != context as usize);
+    }
+
+    /// Records `packet` and returns the gap before it, if any, and whether it reported a queue
+    /// overflow.
+    pub fn observe(&mut self, packet: &Packet) -> (Option<SerialEvent>, Option<SerialEvent>) {
+        let context = packet.pkContext as usize;
+        let serial = packet.pkSerialNumber;
+        let status = packet.pkStatus;
+        let overflow = status
+            .contains(TPS::QUEUE_ERR)
+            .then_some(SerialEvent::QueueOverflow { serial });
+
+        let Some(entry) = self.last_serials.iter_mut().find(|(handle, _)| *handle == context) else {
+            self.last_serials.push((context, serial));
+            return (None, overflow);
+        };
+        let expected = entry.1.wrapping_add(1);
+        let ahead = serial.wrapping_sub(expected);
+        // anything more than half the serial space ahead is really behind: a repeated or
+        // out of order packet
+        if ahead > UINT::MAX / 2 {
+            return (None, overflow);
+        }
+        entry.1 = serial;
+        let dropped = (ahead != 0).then_some(SerialEvent::Dropped {
+            from: expected,
+            to: serial.wrapping_sub(1),
+        });
+        (dropped, overflow)
+    }
+
+    /// Wraps a packet iterator so that [SerialEvent]s are reported in line with the packets.
+    ///
+    /// ```ignore
+    /// let mut tracker = SerialTracker::new();
+    /// for item in tracker.track(reader.drain(&context)) {
+    ///     match item {
+    ///         Tracked::Packet(packet) => { /* ... */ }
+    ///         Tracked::Event(event) => eprintln!("input was lost: {event:?}"),
+    ///     }
+    /// }
+    /// ```
+    pub fn track<I>(&mut self, packets: I) -> TrackedPackets<'_, I::IntoIter>
+    where
+        I: IntoIterator,
+        I::Item: Borrow<Packet>,
+    {
+        TrackedPackets {
+            tracker: self,
+            packets: packets.into_iter(),
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+/// An item yielded by [TrackedPackets]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Tracked {
+    Packet(Packet),
+    /// Reported immediately before the packet it was detected on
+    Event(SerialEvent),
+}
+
+/// See [SerialTracker::track]
+#[derive(Debug)]
+pub struct TrackedPackets<'a, I> {
+    tracker: &'a mut SerialTracker,
+    packets: I,
+    pending: VecDeque<Tracked>,
+}
+
+impl<I> Iterator for TrackedPackets<'_, I>
+where
+    I: Iterator,
+    I::Item: Borrow<Packet>,
+{
+    type Item = Tracked;
+
+    fn next(&mut self) -> Option<Tracked> {
+        if let Some(item) = self.pending.pop_front() {
+            return Some(item);
+        }
+        let packet = self.packets.next()?;
+        let packet = packet.borrow();
+        let (dropped, overflow) = self.tracker.observe(packet);
+        self.pending.extend(overflow.map(Tracked::Event));
+        self.pending.push_back(Tracked::Packet(packet.clone()));
+        match dropped {
+            Some(event) => Some(Tracked::Event(event)),
+            None => self.pending.pop_front(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(context: usize, serial: UINT, status: TPS) -> Packet {
+        Packet {
+            pkContext: context as *mut HCTX,
+            pkSerialNumber: serial,
+            pkStatus: status,
+            ..Default::default()
+        }
+    }
+
+    fn events(packets: &[Packet]) -> Vec<SerialEvent> {
+        SerialTracker::new()
+            .track(packets)
+            .filter_map(|item| match item {
+                Tracked::Event(event) => Some(event),
+                Tracked::Packet(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_consecutive_serials() {
+        let packets: Vec<_> = (5..10).map(|serial| packet(1, serial, TPS::empty())).collect();
+        assert_eq!(events(&packets), vec![]);
+    }
+
+    #[test]
+    fn test_gap() {
+        let packets = [
+            packet(1, 5, TPS::empty()),
+            packet(1, 6, TPS::empty()),
+            packet(1, 10, TPS::empty()),
+        ];
+        let items: Vec<_> = SerialTracker::new().track(&packets).collect();
+        assert_eq!(
+            items,
+            vec![
+                Tracked::Packet(packets[0].clone()),
+                Tracked::Packet(packets[1].clone()),
+                Tracked::Event(SerialEvent::Dropped { from: 7, to: 9 }),
+                Tracked::Packet(packets[2].clone()),
+            ]
+        );
+        assert_eq!(SerialEvent::Dropped { from: 7, to: 9 }.dropped_count(), Some(3));
+    }
+
+    #[test]
+    fn test_wraparound() {
+        let packets = [
+            packet(1, UINT::MAX - 1, TPS::empty()),
+            packet(1, UINT::MAX, TPS::empty()),
+            packet(1, 0, TPS::empty()),
+            packet(1, 3, TPS::empty()),
+        ];
+        assert_eq!(events(&packets), vec![SerialEvent::Dropped { from: 1, to: 2 }]);
+
+        let packets = [packet(1, UINT::MAX - 1, TPS::empty()), packet(1, 1, TPS::empty())];
+        let dropped = SerialEvent::Dropped { from: UINT::MAX, to: 0 };
+        assert_eq!(events(&packets), vec![dropped]);
+        assert_eq!(dropped.dropped_count(), Some(2));
+    }
+
+    #[test]
+    fn test_repeated_and_out_of_order_packets_are_not_gaps() {
+        let packets = [
+            packet(1, 5, TPS::empty()),
+            packet(1, 5, TPS::empty()),
+            packet(1, 3, TPS::empty()),
+            packet(1, 6, TPS::empty()),
+        ];
+        assert_eq!(events(&packets), vec![]);
+    }
+
+    #[test]
+    fn test_contexts_are_tracked_separately() {
+        let packets = [
+            packet(0x1000, 5, TPS::empty()),
+            packet(0x2000, 100, TPS::empty()),
+            packet(0x1000, 6, TPS::empty()),
+            packet(0x2000, 102, TPS::empty()),
+        ];
+        assert_eq!(events(&packets), vec![SerialEvent::Dropped { from: 101, to: 101 }]);
+
+        let mut tracker = SerialTracker::new();
+        tracker.track(&packets).for_each(drop);
+        assert_eq!(tracker.last_serial(0x2000 as *mut HCTX), Some(102));
+        tracker.reset(0x2000 as *mut HCTX);
+        assert_eq!(tracker.last_serial(0x2000 as *mut HCTX), None);
+        assert_eq!(tracker.last_serial(0x1000 as *mut HCTX), Some(6));
+    }
+
+    #[test]
+    fn test_queue_overflow() {
+        let packets = [
+            packet(1, 1, TPS::empty()),
+            packet(1, 4, TPS::QUEUE_ERR | TPS::PROXIMITY),
+        ];
+        let items: Vec<_> = SerialTracker::new().track(&packets).collect();
+        assert_eq!(
+            items,
+            vec![
+                Tracked::Packet(packets[0].clone()),
+                Tracked::Event(SerialEvent::Dropped { from: 2, to: 3 }),
+                Tracked::Event(SerialEvent::QueueOverflow { serial: 4 }),
+                Tracked::Packet(packets[1].clone()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_with_fake_driver_overflow() {
+        use crate::{test_support::FakeWintab, Context, PacketReader, LOGCONTEXTW};
+        use windows::Win32::Foundation::HWND;
+
+        let api = FakeWintab::new();
+        api.state().queue_size = 4;
+        let context = Context::open(&api, HWND(1), &mut LOGCONTEXTW::default(), true).unwrap();
+        let mut reader = PacketReader::new();
+        let mut tracker = SerialTracker::new();
+        for _ in 0..2 {
+            api.push(Packet { pkContext: context.handle(), ..Default::default() });
+        }
+        assert_eq!(tracker.track(reader.drain(&context)).count(), 2);
+        for _ in 0..6 {
+            api.push(Packet { pkContext: context.handle(), ..Default::default() });
+        }
+        let events: Vec<_> = tracker
+            .track(reader.drain(&context))
+            .filter_map(|item| match item {
+                Tracked::Event(event) => Some(event),
+                Tracked::Packet(_) => None,
+            })
+            .collect();
+        assert_eq!(
+            events,
+            vec![
+                SerialEvent::Dropped { from: 2, to: 3 },
+                SerialEvent::QueueOverflow { serial: 6 },
+                SerialEvent::QueueOverflow { serial: 7 },
+            ]
+        );
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{
+    c_type_aliases::{DWORD, LONG, UINT},
+    Orientation, Packet, Rotation, TPS, XY, XYZ,
+};
+
+/// One point along a [Stroke]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StrokeSample {
+    /// See [Packet::pkXYZ]
+    pub position: XYZ<LONG>,
+    /// See [Packet::pkNormalPressure]
+    pub pressure: UINT,
+    /// See [Packet::pkTangentPressure]
+    pub tangent_pressure: UINT,
+    /// See [Packet::pkOrientation]
+    pub orientation: Orientation,
+    /// See [Packet::pkRotation]
+    pub rotation: Rotation,
+    /// See [Packet::pkTime]
+    pub time: DWORD,
+}
+
+impl StrokeSample {
+    pub fn from_packet(packet: &Packet) -> Self {
+        Self {
+            position: packet.pkXYZ,
+            pressure: packet.pkNormalPressure,
+            tangent_pressure: packet.pkTangentPressure,
+            orientation: packet.pkOrientation,
+            rotation: packet.pkRotation,
+            time: packet.pkTime,
+        }
+    }
+}
+
+/// Which end of the pen drew a [Stroke]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum StrokeKind {
+    #[default]
+    Draw,
+    /// The pen was inverted ([TPS::INVERT]) for the whole stroke
+    Erase,
+}
+
+/// Why a [Stroke] ended
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StrokeEnd {
+    /// The pressure dropped to the release threshold
+    Lifted,
+    /// The pen left proximity without the pressure being released first
+    ProximityLost,
+    /// A different cursor, or the other end of the pen, produced the next packet
+    CursorChanged,
+    /// [StrokeBuilder::finish] was called
+    Finished,
+}
+
+/// A continuous line drawn while the pen was touching the tablet
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Stroke {
+    pub kind: StrokeKind,
+    /// See [Packet::pkCursor]
+    pub cursor: UINT,
+    /// The device the stroke was drawn on, as given to [StrokeBuilder::with_device]
+    pub device: UINT,
+    /// Always contains at least one sample
+    pub samples: Vec<StrokeSample>,
+    /// `None` while the stroke is still being drawn
+    pub end: Option<StrokeEnd>,
+}
+
+impl Stroke {
+    /// The smallest and largest x and y of all samples
+    pub fn bounding_box(&self) -> (XY<LONG>, XY<LONG>) {
+        self.samples.iter().fold(
+            (
+                XY { x: LONG::MAX, y: LONG::MAX },
+                XY { x: LONG::MIN, y: LONG::MIN },
+            ),
+            |(min, max), sample| {
+                let XYZ { x, y, .. } = sample.position;
+                (
+                    XY { x: min.x.min(x), y: min.y.min(y) },
+                    XY { x: max.x.max(x), y: max.y.max(y) },
+                )
+            },
+        )
+    }
+
+    /// The length of the path through the x and y of each sample, in tablet units
+    pub fn length(&self) -> f64 {
+        self.samples
+            .windows(2)
+            .map(|pair| {
+                let dx = pair[1].position.x as f64 - pair[0].position.x as f64;
+                let dy = pair[1].position.y as f64 - pair[0].position.y as f64;
+                dx.hypot(dy)
+            })
+            .sum()
+    }
+
+    /// The milliseconds between the first and last sample. Assumes absolute mode
+    /// [Packet::pkTime]; use a [TimeTracker](crate::TimeTracker) for anything more precise.
+    pub fn duration(&self) -> DWORD {
+        let first = self.samples.first().map_or(0, |sample| sample.time);
+        let last = self.samples.last().map_or(0, |sample| sample.time);
+        last.wrapping_sub(first)
+    }
+}
+
+/// Groups consecutive packets where the pen is touching the tablet into [Stroke]s.
+///
+/// A stroke starts when [Packet::pkNormalPressure] rises above the press threshold and ends when
+/// it falls to the release threshold or below. Having the release threshold lower than the press
+/// threshold stops a noisy, light stroke from being broken into pieces.
+///
+/// ```ignore
+/// let mut strokes = StrokeBuilder::new().with_thresholds(20, 10);
+/// for packet in reader.drain(&context) {
+///     if let Some(stroke) = strokes.push(packet) {
+///         canvas.add(stroke);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct StrokeBuilder {
+    press_threshold: UINT,
+    release_threshold: UINT,
+    device: UINT,
+    current: Option<Stroke>,
+}
+
+impl StrokeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the pressure a stroke starts above and the pressure it ends at or below.
+    /// `release` is capped at `press`. Both default to zero.
+    pub fn with_thresholds(mut self, press: UINT, release: UINT) -> Self {
+        self.press_threshold = press;
+        self.release_threshold = release.min(press);
+        self
+    }
+
+    /// Sets the device recorded in each [Stroke::device], e.g. the
+    /// [LOGCONTEXT::lcDevice](crate::LOGCONTEXT::lcDevice) of the context
+    pub fn with_device(mut self, device: UINT) -> Self {
+        self.device = device;
+        self
+    }
+
+    /// The stroke currently being drawn, for live preview
+    pub fn current(&self) -> Option<&Stroke> {
+        self.current.as_ref()
+    }
+
+    /// Adds a packet, returning the stroke it completed if any
+    pub fn push(&mut self, packet: &Packet) -> Option<Stroke> {
+        let status = packet.pkStatus;
+        if status.contains(TPS::PROXIMITY) {
+            return self.end(StrokeEnd::ProximityLost);
+        }
+        let kind = if status.contains(TPS::INVERT) {
+            StrokeKind::Erase
+        } else {
+            StrokeKind::Draw
+        };
+        let cursor = packet.pkCursor;
+        let pressure = packet.pkNormalPressure;
+
+        let mut completed = None;
+        if let Some(stroke) = &mut self.current {
+            if stroke.kind != kind || stroke.cursor != cursor {
+                completed = self.end(StrokeEnd::CursorChanged);
+            } else if pressure <= self.release_threshold {
+                return self.end(StrokeEnd::Lifted);
+            } else {
+                stroke.samples.push(StrokeSample::from_packet(packet));
+                return None;
+            }
+        }
+        if pressure > self.press_threshold {
+            self.current = Some(Stroke {
+                kind,
+                cursor,
+                device: self.device,
+                samples: vec![StrokeSample::from_packet(packet)],
+                end: None,
+            });
+        }
+        completed
+    }
+
+    /// Ends the current stroke, if any, e.g. when the context loses focus
+    pub fn finish(&mut self) -> Option<Stroke> {
+        self.end(StrokeEnd::Finished)
+    }
+
+    fn end(&mut self, end: StrokeEnd) -> Option<Stroke> {
+        let mut stroke = self.current.take()?;
+        stroke.end = Some(end);
+        Some(stroke)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(x: LONG, y: LONG, pressure: UINT, time: DWORD, status: TPS) -> Packet {
+        Packet {
+            pkXYZ: XYZ { x, y, z: 0 },
+            pkNormalPressure: pressure,
+            pkTime: time,
+            pkStatus: status,
+            pkCursor: 1,
+            ..Default::default()
+        }
+    }
+
+    fn push_all(builder: &mut StrokeBuilder, packets: &[Packet]) -> Vec<Stroke> {
+        packets.iter().filter_map(|packet| builder.push(packet)).collect()
+    }
+
+    #[test]
+    fn test_single_stroke() {
+        let mut builder = StrokeBuilder::new().with_device(2);
+        let strokes = push_all(
+            &mut builder,
+            &[
+                packet(0, 0, 0, 100, TPS::empty()),
+                packet(0, 0, 10, 110, TPS::empty()),
+                packet(3, 4, 20, 120, TPS::empty()),
+                packet(3, 10, 15, 130, TPS::empty()),
+                packet(3, 10, 0, 140, TPS::empty()),
+                packet(3, 10, 0, 150, TPS::empty()),
+            ],
+        );
+        assert_eq!(strokes.len(), 1);
+        let stroke = &strokes[0];
+        assert_eq!(stroke.kind, StrokeKind::Draw);
+        assert_eq!(stroke.end, Some(StrokeEnd::Lifted));
+        assert_eq!(stroke.device, 2);
+        assert_eq!(stroke.cursor, 1);
+        assert_eq!(stroke.samples.len(), 3);
+        assert_eq!(stroke.bounding_box(), (XY { x: 0, y: 0 }, XY { x: 3, y: 10 }));
+        assert_eq!(stroke.length(), 11.0);
+        assert_eq!(stroke.duration(), 20);
+        assert!(builder.current().is_none());
+    }
+
+    #[test]
+    fn test_thresholds() {
+        let mut builder = StrokeBuilder::new().with_thresholds(20, 10);
+        let strokes = push_all(
+            &mut builder,
+            &[
+                packet(0, 0, 15, 0, TPS::empty()),
+                packet(1, 0, 25, 0, TPS::empty()),
+                packet(2, 0, 15, 0, TPS::empty()),
+                packet(3, 0, 11, 0, TPS::empty()),
+                packet(4, 0, 10, 0, TPS::empty()),
+            ],
+        );
+        assert_eq!(strokes.len(), 1);
+        let xs: Vec<_> = strokes[0].samples.iter().map(|sample| sample.position.x).collect();
+        assert_eq!(xs, [1, 2, 3]);
+    }
+
+    #[test]
+    fn test_proximity_lost_mid_stroke() {
+        let mut builder = StrokeBuilder::new();
+        let strokes = push_all(
+            &mut builder,
+            &[
+                packet(0, 0, 50, 0, TPS::empty()),
+                packet(1, 0, 50, 0, TPS::empty()),
+                packet(1, 0, 50, 0, TPS::PROXIMITY),
+            ],
+        );
+        assert_eq!(strokes.len(), 1);
+        assert_eq!(strokes[0].end, Some(StrokeEnd::ProximityLost));
+        assert_eq!(strokes[0].samples.len(), 2);
+    }
+
+    #[test]
+    fn test_eraser_is_a_separate_stroke() {
+        let mut builder = StrokeBuilder::new();
+        let strokes = push_all(
+            &mut builder,
+            &[
+                packet(0, 0, 50, 0, TPS::empty()),
+                packet(1, 0, 50, 0, TPS::INVERT),
+                packet(2, 0, 50, 0, TPS::INVERT),
+            ],
+        );
+        assert_eq!(strokes.len(), 1);
+        assert_eq!(strokes[0].kind, StrokeKind::Draw);
+        assert_eq!(strokes[0].end, Some(StrokeEnd::CursorChanged));
+        let erasing = builder.finish().unwrap();
+        assert_eq!(erasing.kind, StrokeKind::Erase);
+        assert_eq!(erasing.end, Some(StrokeEnd::Finished));
+        assert_eq!(erasing.samples.len(), 2);
+        assert_eq!(builder.finish(), None);
+    }
+
+    #[test]
+    fn test_duration_across_tick_wraparound() {
+        let mut builder = StrokeBuilder::new();
+        push_all(
+            &mut builder,
+            &[
+                packet(0, 0, 50, DWORD::MAX - 4, TPS::empty()),
+                packet(0, 0, 50, 5, TPS::empty()),
+            ],
+        );
+        assert_eq!(builder.finish().unwrap().duration(), 10);
+    }
+}
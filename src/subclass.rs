@@ -0,0 +1,86 @@
+use windows::Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+    UI::{
+        Shell::{DefSubclassProc, RemoveWindowSubclass, SetWindowSubclass},
+        WindowsAndMessaging::WM_NCDESTROY,
+    },
+};
+
+use crate::MessageRouter;
+
+/// Hooks the window procedure of an existing window (e.g. one created by `winit`) with
+/// `SetWindowSubclass`, so the wintab messages it receives are passed to a [MessageRouter]
+/// instead of being lost. Every other message goes on to the original window procedure.
+///
+/// The subclass is removed when this is dropped, or when the window is destroyed.
+/// Several may be installed on one window, e.g. one per context; each is identified by the
+/// address of its router, so installing or dropping one leaves the others in place.
+/// Like all window procedure hooks, it must be installed and dropped on the thread which owns
+/// the window.
+///
+/// ```ignore
+/// let (sender, receiver) = std::sync::mpsc::channel();
+/// let router = MessageRouter::with_sender(log_context.lcMsgBase, sender);
+/// let _subclass = unsafe { WindowSubclass::install(hwnd, router)? };
+/// // later, in the event loop
+/// for message in receiver.try_iter() { /* ... */ }
+/// ```
+#[derive(Debug)]
+pub struct WindowSubclass {
+    window: HWND,
+    router: *mut MessageRouter,
+}
+
+impl WindowSubclass {
+    /// Installs `router` on `window`.
+    ///
+    /// # Safety
+    /// `window` must be a valid window owned by the calling thread, and the returned value must
+    /// be dropped on that same thread.
+    pub unsafe fn install(
+        window: HWND,
+        router: MessageRouter,
+    ) -> Result<Self, windows::core::Error> {
+        let router = Box::into_raw(Box::new(router));
+        // the router's address is unique while it is installed, so it doubles as the id
+        let id = router as usize;
+        if SetWindowSubclass(window, Some(subclass_proc), id, router as usize).as_bool() {
+            Ok(Self { window, router })
+        } else {
+            let error = windows::core::Error::from_win32();
+            drop(Box::from_raw(router));
+            Err(error)
+        }
+    }
+
+    pub fn window(&self) -> HWND {
+        self.window
+    }
+}
+
+impl Drop for WindowSubclass {
+    fn drop(&mut self) {
+        unsafe {
+            // fails harmlessly if the window was destroyed and the subclass already removed
+            let _ = RemoveWindowSubclass(self.window, Some(subclass_proc), self.router as usize);
+            drop(Box::from_raw(self.router));
+        }
+    }
+}
+
+unsafe extern "system" fn subclass_proc(
+    window: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+    id: usize,
+    router: usize,
+) -> LRESULT {
+    if msg == WM_NCDESTROY {
+        // the router is freed by WindowSubclass, which may outlive the window
+        let _ = RemoveWindowSubclass(window, Some(subclass_proc), id);
+    } else if (*(router as *mut MessageRouter)).route(msg, wparam, lparam) {
+        return LRESULT(0);
+    }
+    DefSubclassProc(window, msg, wparam, lparam)
+}
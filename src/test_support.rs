@@ -0,0 +1,204 @@
+//! A fake wintab driver for unit tests, so the safe layers can be exercised on any host.
+#![allow(non_snake_case)]
+use std::{
+    collections::{HashMap, VecDeque},
+    sync::{Mutex, MutexGuard},
+};
+
+use windows::Win32::Foundation::HWND;
+
+use crate::{
+    c_type_aliases::{BOOL, HCTX, INT, LPVOID, UINT},
+    Packet, LOGCONTEXTW, TPS,
+};
+
+/// The mutable state of [FakeWintab]
+#[derive(Default)]
+pub(crate) struct FakeState {
+    /// Packets waiting to be read, oldest first
+    pub queue: VecDeque<Packet>,
+    /// The maximum number of packets the queue holds before setting [TPS::QUEUE_ERR]
+    pub queue_size: usize,
+    /// The serial number given to the next pushed packet
+    pub next_serial: UINT,
+    /// Responses to `WTInfoW`, keyed by `(wCategory, nIndex)`
+    pub info: HashMap<(UINT, UINT), Vec<u8>>,
+    /// Handles returned by `open_w` which have not been closed yet
+    pub open: Vec<usize>,
+    /// Handles which have been passed to `close`
+    pub closed: Vec<usize>,
+    /// The context most recently passed to `open_w` or `set_w`
+    pub log_context: LOGCONTEXTW,
+    /// Every `cMaxPkts` passed to `data_get`, to check chunking
+    pub data_get_calls: Vec<INT>,
+    /// When set, `open_w` fails
+    pub fail_open: bool,
+    /// Every `(hCtx, fEnable)` passed to `enable`
+    pub enable_calls: Vec<(usize, bool)>,
+}
+
+/// Behaves like a driver with one tablet. Packets are queued with [FakeWintab::push].
+pub(crate) struct FakeWintab {
+    state: Mutex<FakeState>,
+}
+
+impl FakeWintab {
+    pub fn new() -> Self {
+        Self {
+            state: Mutex::new(FakeState { queue_size: 1000, ..Default::default() }),
+        }
+    }
+
+    pub fn state(&self) -> MutexGuard<'_, FakeState> {
+        self.state.lock().unwrap()
+    }
+
+    /// Queues a packet, assigning it the next serial number.
+    /// If the queue is full the oldest packet is dropped and the new one is flagged with
+    /// [TPS::QUEUE_ERR], like a real driver would.
+    pub fn push(&self, mut packet: Packet) {
+        let mut state = self.state();
+        packet.pkSerialNumber = state.next_serial;
+        state.next_serial = state.next_serial.wrapping_add(1);
+        if state.queue.len() >= state.queue_size {
+            state.queue.pop_front();
+            packet.pkStatus |= TPS::QUEUE_ERR;
+        }
+        state.queue.push_back(packet);
+    }
+
+    /// Sets the response to a `WTInfoW` query
+    pub fn set_info<T: Copy>(&self, category: UINT, index: UINT, value: &[T]) {
+        let bytes = unsafe {
+            std::slice::from_raw_parts(value.as_ptr() as *const u8, std::mem::size_of_val(value))
+        };
+        self.state().info.insert((category, index), bytes.to_vec());
+    }
+
+    /// Sets the response to a `WTInfoW` string query; the string is zero terminated UTF-16
+    pub fn set_info_string(&self, category: UINT, index: UINT, value: &str) {
+        let wide: Vec<u16> = value.encode_utf16().chain([0]).collect();
+        self.set_info(category, index, &wide);
+    }
+}
+
+unsafe impl crate::WintabApi for FakeWintab {
+    unsafe fn info_w(&self, wCategory: UINT, nIndex: UINT, lpOutput: LPVOID) -> UINT {
+        let state = self.state();
+        match state.info.get(&(wCategory, nIndex)) {
+            Some(bytes) => {
+                if !lpOutput.is_null() {
+                    std::ptr::copy_nonoverlapping(bytes.as_ptr(), lpOutput as *mut u8, bytes.len());
+                }
+                bytes.len() as UINT
+            }
+            None => 0,
+        }
+    }
+
+    unsafe fn open_w(&self, _hWnd: HWND, lpLogCtx: *mut LOGCONTEXTW, _fEnable: BOOL) -> *mut HCTX {
+        let mut state = self.state();
+        if state.fail_open {
+            return std::ptr::null_mut();
+        }
+        state.log_context = *lpLogCtx;
+        let handle = 0x1000 + state.open.len() + state.closed.len();
+        state.open.push(handle);
+        handle as *mut HCTX
+    }
+
+    unsafe fn close(&self, hCtx: *mut HCTX) -> BOOL {
+        let mut state = self.state();
+        match state.open.iter().position(|handle| *handle == hCtx as usize) {
+            Some(index) => {
+                state.open.remove(index);
+                state.closed.push(hCtx as usize);
+                1
+            }
+            None => 0,
+        }
+    }
+
+    unsafe fn enable(&self, hCtx: *mut HCTX, fEnable: BOOL) -> BOOL {
+        self.state().enable_calls.push((hCtx as usize, fEnable != 0));
+        1
+    }
+
+    unsafe fn get_w(&self, _hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL {
+        *lpLogCtx = self.state().log_context;
+        1
+    }
+
+    unsafe fn set_w(&self, _hCtx: *mut HCTX, lpLogCtx: *mut LOGCONTEXTW) -> BOOL {
+        self.state().log_context = *lpLogCtx;
+        1
+    }
+
+    unsafe fn packet(&self, _hCtx: *mut HCTX, wSerial: UINT, lpPkt: LPVOID) -> BOOL {
+        let mut state = self.state();
+        match state.queue.iter().position(|packet| packet.pkSerialNumber == wSerial) {
+            Some(index) => {
+                let packet = state.queue.drain(..=index).next_back().unwrap();
+                if !lpPkt.is_null() {
+                    std::ptr::write_unaligned(lpPkt as *mut Packet, packet);
+                }
+                1
+            }
+            None => 0,
+        }
+    }
+
+    unsafe fn packets_get(&self, _hCtx: *mut HCTX, cMaxPkts: INT, lpPkts: LPVOID) -> INT {
+        let mut state = self.state();
+        let count = state.queue.len().min(cMaxPkts.max(0) as usize);
+        for (index, packet) in state.queue.drain(..count).enumerate() {
+            if !lpPkts.is_null() {
+                std::ptr::write_unaligned((lpPkts as *mut Packet).add(index), packet);
+            }
+        }
+        count as INT
+    }
+
+    unsafe fn data_get(
+        &self,
+        _hCtx: *mut HCTX,
+        wBegin: UINT,
+        wEnd: UINT,
+        cMaxPkts: INT,
+        lpPkts: LPVOID,
+        lpNPkts: *mut INT,
+    ) -> INT {
+        let mut state = self.state();
+        state.data_get_calls.push(cMaxPkts);
+        let span = wEnd.wrapping_sub(wBegin);
+        let in_range = |packet: &Packet| packet.pkSerialNumber.wrapping_sub(wBegin) <= span;
+        let total = state.queue.iter().filter(|packet| in_range(packet)).count();
+        let mut copied = 0;
+        let mut remove_until = 0;
+        for (index, packet) in state.queue.iter().enumerate() {
+            if copied == cMaxPkts.max(0) as usize {
+                break;
+            }
+            if in_range(packet) {
+                std::ptr::write_unaligned((lpPkts as *mut Packet).add(copied), packet.clone());
+                copied += 1;
+                remove_until = index + 1;
+            }
+        }
+        state.queue.drain(..remove_until);
+        *lpNPkts = copied as INT;
+        total as INT
+    }
+
+    unsafe fn queue_packets_ex(&self, _hCtx: *mut HCTX, lpOld: *mut UINT, lpNew: *mut UINT) -> BOOL {
+        let state = self.state();
+        match (state.queue.front(), state.queue.back()) {
+            (Some(oldest), Some(newest)) => {
+                *lpOld = oldest.pkSerialNumber;
+                *lpNew = newest.pkSerialNumber;
+                1
+            }
+            _ => 0,
+        }
+    }
+}
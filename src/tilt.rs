@@ -0,0 +1,246 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use crate::{
+    axis::TU,
+    c_type_aliases::{INT, UINT},
+    information_categories::{DVC, WTI},
+    Orientation, WintabApi, AXIS,
+};
+
+/// The size of one increment of each [Orientation] field in radians, from the
+/// [DVC::ORIENTATION] axes of a device.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OrientationScale {
+    pub azimuth: f64,
+    pub altitude: f64,
+    pub twist: f64,
+}
+
+impl Default for OrientationScale {
+    fn default() -> Self {
+        Self::TENTHS_OF_DEGREES
+    }
+}
+
+impl OrientationScale {
+    /// The scale used by Wacom tablets, where each field is in tenths of a degree
+    pub const TENTHS_OF_DEGREES: Self = Self {
+        azimuth: TAU / 3600.0,
+        altitude: TAU / 3600.0,
+        twist: TAU / 3600.0,
+    };
+
+    /// The scale of the azimuth, altitude and twist axes, in that order as returned by
+    /// [DVC::ORIENTATION]. An axis which does not give its resolution in [TU::CIRCLE] is
+    /// assumed to be in tenths of a degree.
+    pub fn from_axes(axes: &[AXIS; 3]) -> Self {
+        let scale = |axis: &AXIS| {
+            let resolution = axis.axResolution.to_f64();
+            if axis.units() == Some(TU::CIRCLE) && resolution > 0.0 {
+                TAU / resolution
+            } else {
+                TAU / 3600.0
+            }
+        };
+        Self {
+            azimuth: scale(&axes[0]),
+            altitude: scale(&axes[1]),
+            twist: scale(&axes[2]),
+        }
+    }
+
+    /// Reads the [DVC::ORIENTATION] axes of `device`, the zero based device index.
+    /// Returns `None` if the device does not report orientation.
+    pub fn load<A: WintabApi + ?Sized>(api: &A, device: UINT) -> Option<Self> {
+        let axes: [AXIS; 3] =
+            api.info_value(WTI::DEVICES as UINT + device, DVC::ORIENTATION as UINT)?;
+        Some(Self::from_axes(&axes))
+    }
+
+    /// Converts raw orientation to radians
+    pub fn to_angles(&self, orientation: Orientation) -> PenAngles {
+        PenAngles {
+            azimuth: orientation.orAzimuth as f64 * self.azimuth,
+            altitude: orientation.orAltitude as f64 * self.altitude,
+            twist: orientation.orTwist as f64 * self.twist,
+        }
+    }
+
+    /// Converts radians back to raw orientation, rounding to the nearest increment. The azimuth
+    /// and twist are wrapped into one positive revolution.
+    pub fn from_angles(&self, angles: PenAngles) -> Orientation {
+        Orientation {
+            orAzimuth: (angles.azimuth.rem_euclid(TAU) / self.azimuth).round() as INT,
+            orAltitude: (angles.altitude / self.altitude).round() as INT,
+            orTwist: (angles.twist.rem_euclid(TAU) / self.twist).round() as INT,
+        }
+    }
+}
+
+/// The orientation of the pen in radians.
+///
+/// The tablet is taken to have x to the right, y towards the top, and z up out of the surface.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct PenAngles {
+    /// Clockwise from the y axis (the top of the tablet) to the direction the pen leans in
+    pub azimuth: f64,
+    /// Angle between the pen and the surface; `π/2` is upright. Negative when the pen is
+    /// inverted, in which case the magnitude is used for [PenAngles::tilt] and
+    /// [PenAngles::direction].
+    pub altitude: f64,
+    /// Clockwise rotation of the pen about its own axis
+    pub twist: f64,
+}
+
+/// Tilt as used by Windows Ink and web `PointerEvent`s, in radians within `[-π/2, π/2]`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Tilt {
+    /// The angle between the pen and the y-z plane; positive when the pen leans to the right
+    pub x: f64,
+    /// The angle between the pen and the x-z plane; positive when the pen leans towards the
+    /// user (the bottom of the tablet)
+    pub y: f64,
+}
+
+impl PenAngles {
+    /// The unit vector from the tip along the body of the pen, in tablet coordinates
+    pub fn direction(&self) -> [f64; 3] {
+        let altitude = self.altitude.abs();
+        let (sin_azimuth, cos_azimuth) = self.azimuth.sin_cos();
+        [
+            sin_azimuth * altitude.cos(),
+            cos_azimuth * altitude.cos(),
+            altitude.sin(),
+        ]
+    }
+
+    /// The angles of a pen pointing along `direction` (which need not be normalised) with the
+    /// given `twist`. A direction below the surface is treated as above it.
+    pub fn from_direction(direction: [f64; 3], twist: f64) -> Self {
+        let [x, y, z] = direction;
+        let azimuth = if x == 0.0 && y == 0.0 { 0.0 } else { x.atan2(y).rem_euclid(TAU) };
+        Self {
+            azimuth,
+            altitude: z.abs().atan2(x.hypot(y)),
+            twist,
+        }
+    }
+
+    /// The X/Y tilt of the pen; see [Tilt]
+    pub fn tilt(&self) -> Tilt {
+        let [x, y, z] = self.direction();
+        Tilt { x: x.atan2(z), y: (-y).atan2(z) }
+    }
+
+    /// The angles of a pen with the given `tilt` and `twist`
+    pub fn from_tilt(tilt: Tilt, twist: f64) -> Self {
+        let clamp = |angle: f64| angle.clamp(-FRAC_PI_2, FRAC_PI_2);
+        let (sin_x, cos_x) = clamp(tilt.x).sin_cos();
+        let (sin_y, cos_y) = clamp(tilt.y).sin_cos();
+        // tan(x) and -tan(y), scaled by cos(x)cos(y) to avoid dividing by zero at ±π/2
+        Self::from_direction([sin_x * cos_y, -sin_y * cos_x, cos_x * cos_y], twist)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{test_support::FakeWintab, FIX32};
+    use std::f64::consts::{FRAC_PI_4, PI};
+
+    fn assert_close(a: f64, b: f64) {
+        assert!((a - b).abs() < 1e-9, "{a} != {b}");
+    }
+
+    fn angles(azimuth_degrees: f64, altitude_degrees: f64) -> PenAngles {
+        PenAngles {
+            azimuth: azimuth_degrees.to_radians(),
+            altitude: altitude_degrees.to_radians(),
+            twist: 0.0,
+        }
+    }
+
+    #[test]
+    fn test_scale_from_axes() {
+        let api = FakeWintab::new();
+        let circle = |resolution: f64| AXIS {
+            axMin: 0,
+            axMax: 3599,
+            axUnits: TU::CIRCLE as UINT,
+            axResolution: FIX32::from(resolution),
+        };
+        let axes = [circle(3600.0), circle(3600.0), AXIS::default()];
+        api.set_info(WTI::DEVICES as UINT, DVC::ORIENTATION as UINT, &axes);
+        let scale = OrientationScale::load(&api, 0).unwrap();
+        assert_eq!(scale, OrientationScale::TENTHS_OF_DEGREES);
+        assert_eq!(OrientationScale::load(&api, 1), None);
+
+        let scale = OrientationScale::from_axes(&[circle(TAU), circle(360.0), circle(1.0)]);
+        assert!((scale.azimuth - 1.0).abs() < 1e-4);
+        assert_close(scale.altitude, 1f64.to_radians());
+        assert_close(scale.twist, TAU);
+    }
+
+    #[test]
+    fn test_raw_round_trip() {
+        let scale = OrientationScale::default();
+        let raw = Orientation { orAzimuth: 900, orAltitude: 450, orTwist: 3599 };
+        let angles = scale.to_angles(raw);
+        assert_close(angles.azimuth, FRAC_PI_2);
+        assert_close(angles.altitude, FRAC_PI_4);
+        assert_eq!(scale.from_angles(angles), raw);
+        let wrapped = PenAngles { azimuth: -FRAC_PI_2, ..angles };
+        assert_eq!(scale.from_angles(wrapped).orAzimuth, 2700);
+    }
+
+    #[test]
+    fn test_tilt() {
+        // upright
+        let tilt = angles(0.0, 90.0).tilt();
+        assert_close(tilt.x, 0.0);
+        assert_close(tilt.y, 0.0);
+        // leaning 45° to the right
+        let tilt = angles(90.0, 45.0).tilt();
+        assert_close(tilt.x, FRAC_PI_4);
+        assert_close(tilt.y, 0.0);
+        // leaning 30° away from the user, towards the top of the tablet
+        let tilt = angles(0.0, 60.0).tilt();
+        assert_close(tilt.x, 0.0);
+        assert_close(tilt.y, -30f64.to_radians());
+        // leaning towards the user; inverted pens use the magnitude of the altitude
+        let tilt = angles(180.0, -60.0).tilt();
+        assert_close(tilt.y, 30f64.to_radians());
+    }
+
+    #[test]
+    fn test_tilt_round_trip() {
+        for azimuth in [0.0, 30.0, 135.0, 200.0, 315.0] {
+            for altitude in [20.0, 45.0, 80.0] {
+                let original = angles(azimuth, altitude);
+                let back = PenAngles::from_tilt(original.tilt(), 0.0);
+                assert_close(back.azimuth, original.azimuth);
+                assert_close(back.altitude, original.altitude);
+            }
+        }
+        let flat = PenAngles::from_tilt(Tilt { x: FRAC_PI_2, y: 0.0 }, 1.0);
+        assert_close(flat.azimuth, FRAC_PI_2);
+        assert_close(flat.altitude, 0.0);
+        assert_close(flat.twist, 1.0);
+    }
+
+    #[test]
+    fn test_direction() {
+        let [x, y, z] = angles(90.0, 0.0).direction();
+        assert_close(x, 1.0);
+        assert_close(y, 0.0);
+        assert_close(z, 0.0);
+        let direction = angles(225.0, 30.0).direction();
+        assert_close(direction.iter().map(|v| v * v).sum::<f64>(), 1.0);
+        let back = PenAngles::from_direction(direction.map(|v| v * 3.0), PI);
+        assert_close(back.azimuth, 225f64.to_radians());
+        assert_close(back.altitude, 30f64.to_radians());
+        let upright = PenAngles::from_direction([0.0, 0.0, 1.0], 0.0);
+        assert_close(upright.azimuth, 0.0);
+        assert_close(upright.altitude, FRAC_PI_2);
+    }
+}
@@ -0,0 +1,193 @@
+use std::time::{Duration, Instant};
+
+use crate::{c_type_aliases::DWORD, WTPKT};
+
+/// How [Packet::pkTime](crate::Packet::pkTime) should be interpreted.
+/// A context reports relative times when [WTPKT::TIME] is set in
+/// [LOGCONTEXT::lcPktMode](crate::LOGCONTEXT::lcPktMode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum TimeMode {
+    /// The system tick in milliseconds (`GetTickCount`) at which the packet was posted.
+    /// Wraps around about every 49.7 days.
+    #[default]
+    Absolute,
+    /// The milliseconds elapsed since the previous packet
+    Relative,
+}
+
+impl TimeMode {
+    /// The mode used by a context opened with this `lcPktMode`
+    pub fn from_packet_mode(packet_mode: WTPKT) -> Self {
+        if packet_mode.contains(WTPKT::TIME) {
+            TimeMode::Relative
+        } else {
+            TimeMode::Absolute
+        }
+    }
+}
+
+/// Turns the 32 bit [Packet::pkTime](crate::Packet::pkTime) into a 64 bit millisecond timeline
+/// that does not wrap, and optionally into [Instant]s comparable with other input sources.
+///
+/// In [TimeMode::Absolute] the timeline is the system tick count extended to 64 bits. Packets which
+/// arrive out of order (up to about 24 days early) map to an earlier time rather than being taken
+/// as a wraparound. In [TimeMode::Relative] the timeline starts at zero and each delta is added.
+///
+/// ```ignore
+/// let mut time = TimeTracker::new(TimeMode::from_packet_mode(log_context.lcPktMode));
+/// for packet in reader.drain(&context) {
+///     let at = time.observe_at(packet.pkTime, Instant::now());
+///     let instant = time.to_instant(at);
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TimeTracker {
+    mode: TimeMode,
+    /// The raw and unwrapped time of the latest packet seen
+    latest: Option<(DWORD, u64)>,
+    /// A point on the timeline and the [Instant] it is estimated to correspond to
+    anchor: Option<(u64, Instant)>,
+}
+
+impl TimeTracker {
+    pub fn new(mode: TimeMode) -> Self {
+        Self { mode, ..Default::default() }
+    }
+
+    pub fn mode(&self) -> TimeMode {
+        self.mode
+    }
+
+    /// The unwrapped time of the latest packet seen, in milliseconds
+    pub fn latest(&self) -> Option<u64> {
+        self.latest.map(|(_, time)| time)
+    }
+
+    /// Forgets all packets seen and any clock correlation
+    pub fn reset(&mut self) {
+        *self = Self::new(self.mode);
+    }
+
+    /// Places a packet's `pkTime` on the timeline and returns its time in milliseconds
+    pub fn observe(&mut self, pk_time: DWORD) -> u64 {
+        match (self.mode, self.latest) {
+            (TimeMode::Absolute, None) => {
+                self.latest = Some((pk_time, pk_time as u64));
+                pk_time as u64
+            }
+            (TimeMode::Absolute, Some((raw, time))) => {
+                // the signed difference allows for packets from slightly in the past
+                let delta = pk_time.wrapping_sub(raw) as i32 as i64;
+                let unwrapped = time.saturating_add_signed(delta);
+                if delta > 0 {
+                    self.latest = Some((pk_time, unwrapped));
+                }
+                unwrapped
+            }
+            (TimeMode::Relative, latest) => {
+                let time = latest.map_or(0, |(_, time)| time) + pk_time as u64;
+                self.latest = Some((pk_time, time));
+                time
+            }
+        }
+    }
+
+    /// Like [TimeTracker::observe], and also refines the clock correlation using the instant the
+    /// packet was received. A packet is never received before it was posted, so the estimate is
+    /// the receipt with the least delay seen so far.
+    pub fn observe_at(&mut self, pk_time: DWORD, received: Instant) -> u64 {
+        let time = self.observe(pk_time);
+        let earlier = match self.anchor {
+            None => true,
+            Some((anchor_time, anchor_instant)) => {
+                match received.checked_duration_since(anchor_instant) {
+                    Some(elapsed) => (elapsed.as_millis() as u64) < time.saturating_sub(anchor_time),
+                    None => true,
+                }
+            }
+        };
+        if earlier {
+            self.anchor = Some((time, received));
+        }
+        time
+    }
+
+    /// The [Instant] corresponding to a time on the timeline, once at least one packet was passed
+    /// to [TimeTracker::observe_at]
+    pub fn to_instant(&self, time: u64) -> Option<Instant> {
+        let (anchor_time, anchor_instant) = self.anchor?;
+        if time >= anchor_time {
+            anchor_instant.checked_add(Duration::from_millis(time - anchor_time))
+        } else {
+            anchor_instant.checked_sub(Duration::from_millis(anchor_time - time))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_absolute_wraparound() {
+        let mut tracker = TimeTracker::new(TimeMode::Absolute);
+        assert_eq!(tracker.observe(DWORD::MAX - 10), DWORD::MAX as u64 - 10);
+        assert_eq!(tracker.observe(DWORD::MAX), DWORD::MAX as u64);
+        assert_eq!(tracker.observe(5), DWORD::MAX as u64 + 6);
+        assert_eq!(tracker.observe(20), DWORD::MAX as u64 + 21);
+        // a second wraparound
+        for raw in (0..=DWORD::MAX).step_by(1 << 30).chain([10]) {
+            tracker.observe(raw);
+        }
+        assert_eq!(tracker.latest(), Some(2 * (1 << 32) + 10));
+    }
+
+    #[test]
+    fn test_absolute_out_of_order() {
+        let mut tracker = TimeTracker::new(TimeMode::Absolute);
+        tracker.observe(DWORD::MAX - 1);
+        assert_eq!(tracker.observe(3), (1 << 32) + 3);
+        // older than the latest packet, from before the wrap
+        assert_eq!(tracker.observe(DWORD::MAX - 2), (1 << 32) - 3);
+        assert_eq!(tracker.latest(), Some((1 << 32) + 3));
+        // still unwrapped relative to the latest packet, not the out of order one
+        assert_eq!(tracker.observe(4), (1 << 32) + 4);
+
+        let mut tracker = TimeTracker::new(TimeMode::Absolute);
+        tracker.observe(1000);
+        assert_eq!(tracker.observe(990), 990);
+        assert_eq!(tracker.latest(), Some(1000));
+    }
+
+    #[test]
+    fn test_relative_deltas() {
+        let mut tracker = TimeTracker::new(TimeMode::from_packet_mode(WTPKT::TIME | WTPKT::X));
+        assert_eq!(tracker.mode(), TimeMode::Relative);
+        assert_eq!(tracker.observe(0), 0);
+        assert_eq!(tracker.observe(5), 5);
+        assert_eq!(tracker.observe(7), 12);
+        assert_eq!(tracker.observe(DWORD::MAX), 12 + DWORD::MAX as u64);
+        tracker.reset();
+        assert_eq!(tracker.latest(), None);
+        assert_eq!(tracker.observe(3), 3);
+        assert_eq!(TimeMode::from_packet_mode(WTPKT::X), TimeMode::Absolute);
+    }
+
+    #[test]
+    fn test_instant_correlation() {
+        let start = Instant::now();
+        let mut tracker = TimeTracker::new(TimeMode::Absolute);
+        assert_eq!(tracker.to_instant(0), None);
+        // received 8ms after it was posted
+        tracker.observe_at(1000, start + Duration::from_millis(8));
+        assert_eq!(tracker.to_instant(1000), Some(start + Duration::from_millis(8)));
+        // received only 2ms after it was posted; a better estimate
+        tracker.observe_at(1010, start + Duration::from_millis(12));
+        assert_eq!(tracker.to_instant(1000), Some(start + Duration::from_millis(2)));
+        // received late; ignored for the estimate
+        tracker.observe_at(1020, start + Duration::from_millis(50));
+        assert_eq!(tracker.to_instant(1020), Some(start + Duration::from_millis(22)));
+        // times before the anchor
+        assert_eq!(tracker.to_instant(1005), Some(start + Duration::from_millis(7)));
+    }
+}
@@ -0,0 +1,125 @@
+use raw_window_handle::{HandleError, HasWindowHandle, RawWindowHandle};
+use windows::Win32::Foundation::HWND;
+
+use crate::{Context, ContextError, WintabApi, LOGCONTEXTW};
+
+/// The ways opening a [Context] for a window from another crate can fail
+#[derive(Debug, Clone)]
+pub enum WindowHandleError {
+    /// The window could not provide a handle, e.g. because it is not currently active
+    Unavailable(HandleError),
+    /// The window is not a Win32 window, so wintab cannot be used with it
+    NotWin32,
+    /// The window handle was fine, but the context could not be opened
+    Open(ContextError),
+}
+
+impl std::fmt::Display for WindowHandleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WindowHandleError::Unavailable(error) => {
+                write!(f, "the window handle is not available: {error}")
+            }
+            WindowHandleError::NotWin32 => write!(f, "wintab requires a Win32 window handle"),
+            WindowHandleError::Open(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for WindowHandleError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WindowHandleError::Unavailable(error) => Some(error),
+            WindowHandleError::NotWin32 => None,
+            WindowHandleError::Open(error) => Some(error),
+        }
+    }
+}
+
+impl From<ContextError> for WindowHandleError {
+    fn from(error: ContextError) -> Self {
+        WindowHandleError::Open(error)
+    }
+}
+
+/// The [HWND] of a window from any crate implementing [HasWindowHandle], such as `winit`
+pub fn window_hwnd(window: &impl HasWindowHandle) -> Result<HWND, WindowHandleError> {
+    let handle = window.window_handle().map_err(WindowHandleError::Unavailable)?;
+    match handle.as_raw() {
+        RawWindowHandle::Win32(handle) => Ok(HWND(handle.hwnd.get())),
+        _ => Err(WindowHandleError::NotWin32),
+    }
+}
+
+impl<A: WintabApi> Context<A> {
+    /// Like [Context::open], for a window from any crate implementing [HasWindowHandle].
+    ///
+    /// ```ignore
+    /// let window = WindowBuilder::new().build(&event_loop)?;
+    /// let context = Context::open_for_window(&api, &window, &mut log_context, true)?;
+    /// ```
+    pub fn open_for_window(
+        api: A,
+        window: &impl HasWindowHandle,
+        log_context: &mut LOGCONTEXTW,
+        enable: bool,
+    ) -> Result<Self, WindowHandleError> {
+        let hwnd = window_hwnd(window)?;
+        Ok(Context::open(api, hwnd, log_context, enable)?)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::FakeWintab;
+    use raw_window_handle::{Win32WindowHandle, WindowHandle, XlibWindowHandle};
+    use std::num::NonZeroIsize;
+
+    struct FakeWindow(Option<RawWindowHandle>);
+
+    impl HasWindowHandle for FakeWindow {
+        fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
+            match self.0 {
+                Some(raw) => Ok(unsafe { WindowHandle::borrow_raw(raw) }),
+                None => Err(HandleError::Unavailable),
+            }
+        }
+    }
+
+    fn win32_window(hwnd: isize) -> FakeWindow {
+        let handle = Win32WindowHandle::new(NonZeroIsize::new(hwnd).unwrap());
+        FakeWindow(Some(RawWindowHandle::Win32(handle)))
+    }
+
+    #[test]
+    fn test_window_hwnd() {
+        assert_eq!(window_hwnd(&win32_window(0x1234)).unwrap(), HWND(0x1234));
+        let xlib = FakeWindow(Some(RawWindowHandle::Xlib(XlibWindowHandle::new(7))));
+        assert!(matches!(window_hwnd(&xlib), Err(WindowHandleError::NotWin32)));
+        assert!(matches!(
+            window_hwnd(&FakeWindow(None)),
+            Err(WindowHandleError::Unavailable(HandleError::Unavailable))
+        ));
+    }
+
+    #[test]
+    fn test_open_for_window() {
+        let api = FakeWintab::new();
+        let mut log_context = LOGCONTEXTW::default();
+        let context = Context::open_for_window(&api, &win32_window(1), &mut log_context, true);
+        assert!(context.is_ok());
+        drop(context);
+        assert_eq!(api.state().closed.len(), 1);
+
+        let xlib = FakeWindow(Some(RawWindowHandle::Xlib(XlibWindowHandle::new(7))));
+        let error = Context::open_for_window(&api, &xlib, &mut log_context, true).unwrap_err();
+        assert_eq!(error.to_string(), "wintab requires a Win32 window handle");
+        // nothing was opened for the foreign window
+        assert!(api.state().open.is_empty());
+
+        api.state().fail_open = true;
+        let error = Context::open_for_window(&api, &win32_window(1), &mut log_context, true);
+        assert!(matches!(error, Err(WindowHandleError::Open(ContextError::OpenFailed))));
+    }
+}
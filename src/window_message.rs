@@ -3,6 +3,13 @@
 /// 
 const WT_DEFBASE: u32 = 32752;
 
+/// The default [LOGCONTEXT::lcMsgBase](crate::LOGCONTEXT::lcMsgBase). The constants below are
+/// only correct for contexts using this base.
+pub const DEFBASE: u32 = WT_DEFBASE;
+/// The highest offset from the message base reserved by wintab; a context uses the messages
+/// `lcMsgBase..=lcMsgBase + MAXOFFSET`
+pub const MAXOFFSET: u32 = 0xF;
+
 
 /// Sent to windows that have requested messaging for their context.
 /// 
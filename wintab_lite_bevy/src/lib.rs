@@ -0,0 +1,645 @@
+//! A [bevy](https://bevyengine.org) plugin which reads pen tablet input with `wintab_lite`.
+//!
+//! [WintabPlugin] opens a wintab context for the primary window once it exists, and each frame
+//! publishes what the pen did as [PenInput] events and keeps the [PenState] resource up to date.
+//! Positions are in the logical coordinates of the window, like bevy's cursor position.
+//!
+//! ```ignore
+//! let api = Arc::new(unsafe { LibloadingApi::load()? });
+//! App::new()
+//!     .add_plugins((DefaultPlugins, WintabPlugin::new(api)))
+//!     .add_systems(Update, |pen: Res<PenState>| println!("{:?}", pen.position))
+//!     .run();
+//! ```
+//!
+//! Everything after the packets are read is ordinary bevy systems, so the plugin can be tested
+//! headless by pushing packets into the [PacketQueue] resource.
+use bevy_app::{App, Plugin, PreUpdate};
+use bevy_ecs::prelude::*;
+use bevy_math::Vec2;
+use bevy_window::{
+    PrimaryWindow, RawHandleWrapper, Window, WindowFocused, WindowMoved, WindowPosition,
+    WindowResized, WindowScaleFactorChanged,
+};
+use wintab_lite::{
+    ClientMapper, Context, OrientationScale, Packet, PacketReader, PenEvent, PenEventStream,
+    PenTool, PressureCurve, ToolTable, WindowHandleError, WintabApi, LOGCONTEXTW, WTPKT, XY,
+};
+
+/// Adds pen tablet input to an [App]. `A` is the wintab function table, e.g.
+/// `Arc<LibloadingApi>`.
+pub struct WintabPlugin<A> {
+    api: A,
+    log_context: Option<LOGCONTEXTW>,
+}
+
+impl<A: WintabApi + Clone + Send + Sync + 'static> WintabPlugin<A> {
+    /// Opens a context based on the default system context, mapped to the whole desktop with
+    /// y pointing down, as required by [WindowMapping].
+    pub fn new(api: A) -> Self {
+        Self { api, log_context: None }
+    }
+
+    /// Opens the given context instead. Its output must be in screen pixels with y pointing
+    /// down, and it must request every packet field ([WTPKT::all]).
+    pub fn with_log_context(mut self, log_context: LOGCONTEXTW) -> Self {
+        self.log_context = Some(log_context);
+        self
+    }
+}
+
+impl<A: WintabApi + Clone + Send + Sync + 'static> Plugin for WintabPlugin<A> {
+    fn build(&self, app: &mut App) {
+        app.add_event::<PenInput>()
+            .add_event::<WindowResized>()
+            .add_event::<WindowScaleFactorChanged>()
+            .add_event::<WindowMoved>()
+            .add_event::<WindowFocused>()
+            .init_resource::<PacketQueue>()
+            .init_resource::<PenState>()
+            .init_resource::<PenTracker>()
+            .init_resource::<TabletCalibration>()
+            .init_resource::<WindowMapping>()
+            .insert_non_send_resource(WintabContext {
+                api: self.api.clone(),
+                log_context: self.log_context,
+                context: None,
+                window: None,
+                error: None,
+            })
+            .add_systems(
+                PreUpdate,
+                (
+                    open_context::<A>,
+                    update_window_mapping,
+                    handle_focus::<A>,
+                    read_packets::<A>,
+                    process_packets,
+                )
+                    .chain()
+                    .in_set(WintabSet),
+            );
+    }
+}
+
+/// The systems which read the tablet and update [PenState]; they run in [PreUpdate]
+#[derive(SystemSet, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct WintabSet;
+
+/// Why the plugin could not open a context; see [WintabContext::error]
+#[derive(Debug, Clone)]
+pub enum WintabPluginError {
+    /// The driver has no default context, which usually means no tablet is installed
+    NoTablet,
+    /// The primary window could not be used
+    Window(WindowHandleError),
+}
+
+impl std::fmt::Display for WintabPluginError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WintabPluginError::NoTablet => write!(f, "the wintab driver has no default context"),
+            WintabPluginError::Window(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for WintabPluginError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            WintabPluginError::NoTablet => None,
+            WintabPluginError::Window(error) => Some(error),
+        }
+    }
+}
+
+/// The open wintab context. This is a non-send resource, since contexts belong to the thread of
+/// the window which owns them.
+pub struct WintabContext<A: WintabApi> {
+    api: A,
+    log_context: Option<LOGCONTEXTW>,
+    context: Option<Context<A>>,
+    window: Option<Entity>,
+    error: Option<WintabPluginError>,
+}
+
+impl<A: WintabApi> WintabContext<A> {
+    pub fn context(&self) -> Option<&Context<A>> {
+        self.context.as_ref()
+    }
+
+    /// The window the context was opened for
+    pub fn window(&self) -> Option<Entity> {
+        self.window
+    }
+
+    /// Set if opening the context failed; the plugin does not try again
+    pub fn error(&self) -> Option<&WintabPluginError> {
+        self.error.as_ref()
+    }
+
+    fn default_log_context(&self) -> Option<LOGCONTEXTW> {
+        let mut log_context = self.api.default_system_context()?;
+        log_context.lcPktData = WTPKT::all();
+        log_context.lcPktMode = WTPKT::empty();
+        log_context.lcMoveMask = WTPKT::all();
+        // wintab's y axis points up by default; screen and window coordinates point down
+        log_context.lcOutExtXYZ.y = -log_context.lcOutExtXYZ.y.abs();
+        Some(log_context)
+    }
+}
+
+/// Packets read this frame, oldest first. They are turned into [PenInput] events and then
+/// cleared; push synthetic packets here to drive the plugin without a tablet.
+#[derive(Resource, Default, Debug)]
+pub struct PacketQueue(pub Vec<Packet>);
+
+/// How to interpret raw packet values, read from the driver when the context is opened
+#[derive(Resource, Default, Debug, Clone)]
+pub struct TabletCalibration {
+    pub pressure: PressureCurve,
+    pub orientation: OrientationScale,
+    pub tools: ToolTable,
+}
+
+/// Maps screen pixels, as reported by the context, to the logical coordinates of the window.
+/// Updated when the window is moved, resized or changes DPI.
+#[derive(Resource, Debug, Clone, Copy, PartialEq)]
+pub struct WindowMapping {
+    /// The top left corner of the client area of the window, in screen pixels
+    pub client_origin: Vec2,
+    /// Physical pixels per logical pixel
+    pub scale_factor: f32,
+}
+
+impl Default for WindowMapping {
+    fn default() -> Self {
+        Self { client_origin: Vec2::ZERO, scale_factor: 1.0 }
+    }
+}
+
+impl WindowMapping {
+    /// The same mapping as a [ClientMapper], e.g. for sub-pixel precision with a second context
+    pub fn client_mapper(&self) -> ClientMapper {
+        let client_origin = XY { x: self.client_origin.x as f64, y: self.client_origin.y as f64 };
+        ClientMapper::new(client_origin, self.scale_factor as f64)
+    }
+
+    pub fn to_logical(&self, screen: Vec2) -> Vec2 {
+        let logical =
+            self.client_mapper().screen_to_client(XY { x: screen.x as f64, y: screen.y as f64 });
+        Vec2::new(logical.x as f32, logical.y as f32)
+    }
+}
+
+/// The pen as of the latest packet
+#[derive(Resource, Default, Debug, Clone, PartialEq)]
+pub struct PenState {
+    /// In logical window coordinates, with y pointing down
+    pub position: Vec2,
+    /// Normal pressure from `0.0` to `1.0`, after the [TabletCalibration::pressure] curve
+    pub pressure: f32,
+    /// X and Y tilt in radians; see [wintab_lite::Tilt]
+    pub tilt: Vec2,
+    pub tool: PenTool,
+    /// One bit per logical button
+    pub buttons: u32,
+    pub in_proximity: bool,
+    pub in_contact: bool,
+}
+
+/// What happened in a [PenInput] event
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PenInputKind {
+    ProximityEnter,
+    Hover,
+    Down,
+    Move,
+    Up,
+    ProximityLeave,
+    ButtonPressed(u8),
+    ButtonReleased(u8),
+    ToolChanged,
+}
+
+/// Something the pen did, with the [PenState] it left behind
+#[derive(Event, Debug, Clone, PartialEq)]
+pub struct PenInput {
+    pub kind: PenInputKind,
+    pub state: PenState,
+}
+
+/// The [PenEventStream] which turns packets into events
+#[derive(Resource, Default, Debug)]
+pub struct PenTracker(pub PenEventStream);
+
+fn open_context<A: WintabApi + Clone + 'static>(
+    mut wintab: NonSendMut<WintabContext<A>>,
+    windows: Query<(Entity, &RawHandleWrapper), With<PrimaryWindow>>,
+    mut calibration: ResMut<TabletCalibration>,
+) {
+    if wintab.context.is_some() || wintab.error.is_some() {
+        return;
+    }
+    let Ok((entity, handle)) = windows.get_single() else {
+        return;
+    };
+    let Some(mut log_context) = wintab.log_context.or_else(|| wintab.default_log_context()) else {
+        wintab.error = Some(WintabPluginError::NoTablet);
+        return;
+    };
+    // systems using non-send resources run on the main thread, which owns the window
+    let window = unsafe { handle.get_handle() };
+    match Context::open_for_window(wintab.api.clone(), &window, &mut log_context, true) {
+        Ok(context) => {
+            let api = &wintab.api;
+            let device = log_context.lcDevice;
+            *calibration = TabletCalibration {
+                pressure: PressureCurve::load_normal(api, device).unwrap_or_default(),
+                orientation: OrientationScale::load(api, device).unwrap_or_default(),
+                tools: ToolTable::load(api),
+            };
+            wintab.context = Some(context);
+            wintab.window = Some(entity);
+        }
+        Err(error) => wintab.error = Some(WintabPluginError::Window(error)),
+    }
+}
+
+/// The top left of the client area of `window` in screen pixels
+#[cfg(windows)]
+fn client_origin(window: &Window, handle: Option<&RawHandleWrapper>) -> Vec2 {
+    use raw_window_handle::RawWindowHandle;
+    use windows::Win32::{
+        Foundation::{HWND, POINT},
+        Graphics::Gdi::ClientToScreen,
+    };
+
+    if let Some(RawWindowHandle::Win32(handle)) = handle.map(|handle| handle.window_handle) {
+        let mut point = POINT::default();
+        if unsafe { ClientToScreen(HWND(handle.hwnd.get()), &mut point) }.as_bool() {
+            return Vec2::new(point.x as f32, point.y as f32);
+        }
+    }
+    window_position(window)
+}
+
+#[cfg(not(windows))]
+fn client_origin(window: &Window, _handle: Option<&RawHandleWrapper>) -> Vec2 {
+    window_position(window)
+}
+
+fn window_position(window: &Window) -> Vec2 {
+    match window.position {
+        WindowPosition::At(position) => position.as_vec2(),
+        _ => Vec2::ZERO,
+    }
+}
+
+fn update_window_mapping(
+    windows: Query<(Ref<Window>, Option<&RawHandleWrapper>), With<PrimaryWindow>>,
+    mut resized: EventReader<WindowResized>,
+    mut rescaled: EventReader<WindowScaleFactorChanged>,
+    mut moved: EventReader<WindowMoved>,
+    mut mapping: ResMut<WindowMapping>,
+) {
+    let changed = resized.read().count() + rescaled.read().count() + moved.read().count() > 0;
+    let Ok((window, handle)) = windows.get_single() else {
+        return;
+    };
+    if changed || window.is_added() {
+        mapping.set_if_neq(WindowMapping {
+            client_origin: client_origin(&window, handle),
+            scale_factor: window.scale_factor(),
+        });
+    }
+}
+
+fn handle_focus<A: WintabApi + 'static>(
+    wintab: NonSend<WintabContext<A>>,
+    mut focused: EventReader<WindowFocused>,
+    mut tracker: ResMut<PenTracker>,
+    mut pen: ResMut<PenState>,
+    mut inputs: EventWriter<PenInput>,
+    context: (Res<TabletCalibration>, Res<WindowMapping>),
+) {
+    let (calibration, mapping) = context;
+    for event in focused.read() {
+        if Some(event.window) != wintab.window {
+            continue;
+        }
+        if let Some(context) = &wintab.context {
+            context.enable(event.focused);
+        }
+        if !event.focused {
+            // the pen may leave while we are not listening
+            let mut events = Vec::new();
+            tracker.0.leave(&mut events);
+            publish(&events, &calibration, &mapping, &mut pen, &mut inputs);
+        }
+    }
+}
+
+fn read_packets<A: WintabApi + 'static>(
+    wintab: NonSend<WintabContext<A>>,
+    mut reader: Local<PacketReader>,
+    mut queue: ResMut<PacketQueue>,
+) {
+    if let Some(context) = &wintab.context {
+        queue.0.extend(reader.drain(context).cloned());
+    }
+}
+
+fn process_packets(
+    mut queue: ResMut<PacketQueue>,
+    mut tracker: ResMut<PenTracker>,
+    mut pen: ResMut<PenState>,
+    mut inputs: EventWriter<PenInput>,
+    calibration: Res<TabletCalibration>,
+    mapping: Res<WindowMapping>,
+) {
+    let mut events = Vec::new();
+    for packet in queue.0.drain(..) {
+        tracker.0.process(&packet, &mut events);
+    }
+    publish(&events, &calibration, &mapping, &mut pen, &mut inputs);
+}
+
+fn publish(
+    events: &[PenEvent],
+    calibration: &TabletCalibration,
+    mapping: &WindowMapping,
+    pen: &mut PenState,
+    inputs: &mut EventWriter<PenInput>,
+) {
+    for event in events {
+        let raw = event.state();
+        let kind = match *event {
+            PenEvent::ProximityEnter(_) => PenInputKind::ProximityEnter,
+            PenEvent::Hover(_) => PenInputKind::Hover,
+            PenEvent::Down(_) => PenInputKind::Down,
+            PenEvent::Move(_) => PenInputKind::Move,
+            PenEvent::Up(_) => PenInputKind::Up,
+            PenEvent::ProximityLeave(_) => PenInputKind::ProximityLeave,
+            PenEvent::ButtonPressed(button, _) => PenInputKind::ButtonPressed(button),
+            PenEvent::ButtonReleased(button, _) => PenInputKind::ButtonReleased(button),
+            PenEvent::CursorChanged(_) => PenInputKind::ToolChanged,
+        };
+        let tilt = calibration.orientation.to_angles(raw.orientation).tilt();
+        *pen = PenState {
+            position: mapping.to_logical(Vec2::new(raw.position.x as f32, raw.position.y as f32)),
+            pressure: calibration.pressure.map(raw.pressure) as f32,
+            tilt: Vec2::new(tilt.x as f32, tilt.y as f32),
+            tool: if raw.eraser {
+                PenTool::Eraser
+            } else {
+                calibration.tools.cursor_tool(raw.cursor)
+            },
+            buttons: raw.buttons.0,
+            in_proximity: kind != PenInputKind::ProximityLeave,
+            in_contact: raw.in_contact,
+        };
+        inputs.send(PenInput { kind, state: pen.clone() });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy_app::App;
+    use bevy_math::IVec2;
+    use bevy_window::WindowResolution;
+    use raw_window_handle::{
+        RawDisplayHandle, RawWindowHandle, Win32WindowHandle, WindowsDisplayHandle,
+    };
+    use std::{
+        num::NonZeroIsize,
+        sync::{Arc, Mutex},
+    };
+    use wintab_lite::{Orientation, BOOL, CXO, HCTX, INT, LPVOID, TPS, UINT, WTI, XYZ};
+
+    /// A driver which opens contexts, records `WTEnable` and never has any packets
+    #[derive(Default)]
+    struct FakeDriver {
+        enabled: Mutex<Vec<bool>>,
+        closed: Mutex<usize>,
+    }
+
+    #[allow(non_snake_case)]
+    unsafe impl WintabApi for FakeDriver {
+        unsafe fn info_w(&self, wCategory: UINT, _nIndex: UINT, lpOutput: LPVOID) -> UINT {
+            // only the default system context is available
+            if wCategory != WTI::DEFSYSCTX as UINT {
+                return 0;
+            }
+            if !lpOutput.is_null() {
+                let log_context = LOGCONTEXTW {
+                    lcOptions: CXO::SYSTEM,
+                    lcOutExtXYZ: XYZ { x: 1920, y: 1080, z: 0 },
+                    ..Default::default()
+                };
+                *(lpOutput as *mut LOGCONTEXTW) = log_context;
+            }
+            std::mem::size_of::<LOGCONTEXTW>() as UINT
+        }
+        unsafe fn open_w(
+            &self,
+            _: windows::Win32::Foundation::HWND,
+            lpLogCtx: *mut LOGCONTEXTW,
+            _: BOOL,
+        ) -> *mut HCTX {
+            assert!({ (*lpLogCtx).lcOutExtXYZ.y } < 0);
+            0x1000 as *mut HCTX
+        }
+        unsafe fn close(&self, _: *mut HCTX) -> BOOL {
+            *self.closed.lock().unwrap() += 1;
+            1
+        }
+        unsafe fn enable(&self, _: *mut HCTX, fEnable: BOOL) -> BOOL {
+            self.enabled.lock().unwrap().push(fEnable != 0);
+            1
+        }
+        unsafe fn get_w(&self, _: *mut HCTX, _: *mut LOGCONTEXTW) -> BOOL {
+            0
+        }
+        unsafe fn set_w(&self, _: *mut HCTX, _: *mut LOGCONTEXTW) -> BOOL {
+            0
+        }
+        unsafe fn packet(&self, _: *mut HCTX, _: UINT, _: LPVOID) -> BOOL {
+            0
+        }
+        unsafe fn packets_get(&self, _: *mut HCTX, _: INT, _: LPVOID) -> INT {
+            0
+        }
+        unsafe fn data_get(
+            &self,
+            _: *mut HCTX,
+            _: UINT,
+            _: UINT,
+            _: INT,
+            _: LPVOID,
+            _: *mut INT,
+        ) -> INT {
+            0
+        }
+        unsafe fn queue_packets_ex(&self, _: *mut HCTX, _: *mut UINT, _: *mut UINT) -> BOOL {
+            0
+        }
+    }
+
+    fn app(driver: &Arc<FakeDriver>) -> App {
+        let mut app = App::new();
+        app.add_plugins(WintabPlugin::new(driver.clone()));
+        app
+    }
+
+    fn spawn_window(app: &mut App, position: IVec2, scale_factor: f32) -> Entity {
+        let window = Window {
+            position: WindowPosition::At(position),
+            resolution: WindowResolution::new(800.0, 600.0)
+                .with_scale_factor_override(scale_factor),
+            ..Default::default()
+        };
+        let handle = RawHandleWrapper {
+            window_handle: RawWindowHandle::Win32(Win32WindowHandle::new(
+                NonZeroIsize::new(0x1234).unwrap(),
+            )),
+            display_handle: RawDisplayHandle::Windows(WindowsDisplayHandle::new()),
+        };
+        app.world.spawn((window, handle, PrimaryWindow)).id()
+    }
+
+    fn push(app: &mut App, packet: Packet) {
+        app.world.resource_mut::<PacketQueue>().0.push(packet);
+    }
+
+    fn inputs(app: &mut App) -> Vec<PenInput> {
+        app.world.resource_mut::<Events<PenInput>>().drain().collect()
+    }
+
+    /// A packet from an upright pen
+    fn packet(x: i32, y: i32, pressure: UINT) -> Packet {
+        Packet {
+            pkXYZ: XYZ { x, y, z: 0 },
+            pkNormalPressure: pressure,
+            pkOrientation: Orientation { orAzimuth: 0, orAltitude: 900, orTwist: 0 },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_opens_context_for_primary_window() {
+        let driver = Arc::new(FakeDriver::default());
+        let mut app = app(&driver);
+        app.update();
+        assert!(app
+            .world
+            .non_send_resource::<WintabContext<Arc<FakeDriver>>>()
+            .context()
+            .is_none());
+        let window = spawn_window(&mut app, IVec2::ZERO, 1.0);
+        app.update();
+        let wintab = app.world.non_send_resource::<WintabContext<Arc<FakeDriver>>>();
+        assert!(wintab.context().is_some());
+        assert_eq!(wintab.window(), Some(window));
+        assert!(wintab.error().is_none());
+        drop(app);
+        assert_eq!(*driver.closed.lock().unwrap(), 1);
+    }
+
+    #[test]
+    fn test_pen_events_and_state() {
+        let driver = Arc::new(FakeDriver::default());
+        let mut app = app(&driver);
+        spawn_window(&mut app, IVec2::new(100, 50), 2.0);
+        app.update();
+
+        push(&mut app, packet(300, 250, 0));
+        push(&mut app, packet(302, 250, 1023));
+        app.update();
+        let kinds: Vec<_> = inputs(&mut app).into_iter().map(|input| input.kind).collect();
+        assert_eq!(kinds, [PenInputKind::ProximityEnter, PenInputKind::Hover, PenInputKind::Down]);
+        let pen = app.world.resource::<PenState>().clone();
+        assert_eq!(pen.position, Vec2::new(101.0, 100.0));
+        assert_eq!(pen.pressure, 1.0);
+        assert!(pen.in_proximity && pen.in_contact);
+        assert!(pen.tilt.abs_diff_eq(Vec2::ZERO, 1e-6));
+
+        push(&mut app, Packet { pkStatus: TPS::PROXIMITY, ..packet(302, 250, 0) });
+        app.update();
+        let kinds: Vec<_> = inputs(&mut app).into_iter().map(|input| input.kind).collect();
+        assert_eq!(kinds, [PenInputKind::Up, PenInputKind::ProximityLeave]);
+        assert!(!app.world.resource::<PenState>().in_proximity);
+    }
+
+    #[test]
+    fn test_tilt_and_eraser() {
+        let driver = Arc::new(FakeDriver::default());
+        let mut app = app(&driver);
+        spawn_window(&mut app, IVec2::ZERO, 1.0);
+        app.update();
+        push(
+            &mut app,
+            Packet {
+                pkStatus: TPS::INVERT,
+                // leaning 45° to the right, in tenths of a degree
+                pkOrientation: Orientation { orAzimuth: 900, orAltitude: 450, orTwist: 0 },
+                ..packet(0, 0, 0)
+            },
+        );
+        app.update();
+        let pen = app.world.resource::<PenState>();
+        assert_eq!(pen.tool, PenTool::Eraser);
+        assert!((pen.tilt.x - std::f32::consts::FRAC_PI_4).abs() < 1e-5);
+        assert!(pen.tilt.y.abs() < 1e-5);
+    }
+
+    #[test]
+    fn test_focus_loss_disables_context() {
+        let driver = Arc::new(FakeDriver::default());
+        let mut app = app(&driver);
+        let window = spawn_window(&mut app, IVec2::ZERO, 1.0);
+        app.update();
+        push(&mut app, packet(10, 10, 0));
+        app.update();
+        inputs(&mut app);
+
+        app.world.send_event(WindowFocused { window, focused: false });
+        app.update();
+        assert_eq!(*driver.enabled.lock().unwrap(), [false]);
+        let leaving = inputs(&mut app);
+        assert_eq!(leaving.len(), 1);
+        assert_eq!(leaving[0].kind, PenInputKind::ProximityLeave);
+
+        app.world.send_event(WindowFocused { window, focused: true });
+        app.update();
+        assert_eq!(*driver.enabled.lock().unwrap(), [false, true]);
+        assert!(inputs(&mut app).is_empty());
+    }
+
+    #[test]
+    fn test_resize_and_dpi_change_update_mapping() {
+        let driver = Arc::new(FakeDriver::default());
+        let mut app = app(&driver);
+        let window = spawn_window(&mut app, IVec2::new(10, 20), 1.0);
+        app.update();
+        assert_eq!(
+            *app.world.resource::<WindowMapping>(),
+            WindowMapping { client_origin: Vec2::new(10.0, 20.0), scale_factor: 1.0 }
+        );
+
+        let mut entity = app.world.entity_mut(window);
+        let mut state = entity.get_mut::<Window>().unwrap();
+        state.resolution.set_scale_factor_override(Some(1.5));
+        state.position = WindowPosition::At(IVec2::new(40, 60));
+        // nothing changes until the window reports it
+        app.update();
+        assert_eq!(app.world.resource::<WindowMapping>().scale_factor, 1.0);
+
+        app.world.send_event(WindowScaleFactorChanged { window, scale_factor: 1.5 });
+        app.update();
+        let mapping = *app.world.resource::<WindowMapping>();
+        assert_eq!(mapping.scale_factor, 1.5);
+        assert_eq!(mapping.to_logical(Vec2::new(70.0, 90.0)), Vec2::new(20.0, 20.0));
+    }
+}